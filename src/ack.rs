@@ -0,0 +1,173 @@
+use embedded_hal::serial::Read;
+
+use crate::buffer::{Buffer, BufferOverflow};
+use crate::{Name, SendPackage, WriteIter};
+
+/// Receiver accepted package
+pub const ACK: u8 = 0x06;
+/// Receiver rejected package, it is sent again
+pub const NACK: u8 = 0x15;
+
+/// Errors of reliable delivery
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DeliveryError<E, R> {
+    /// Package does not fit in buffer
+    Overflow,
+    /// Transport error
+    Transport(E),
+    /// Error of reading answer
+    Receive(R),
+    /// Package is not acknowledged after all retries
+    NotAcknowledged,
+}
+
+/// Transport retransmitting package until receiver answers with [`ACK`],
+/// for links dropping bursts like Bluetooth SPP.
+///
+/// Package (up to `B` bytes) is kept in buffer and sent up to `1 + retries` times.
+/// Answer is awaited `timeout` polls of `rx`, other bytes are ignored
+pub struct Acknowledged<Tx, Rx, const B: usize> {
+    tx: Tx,
+    rx: Rx,
+    buffer: Buffer<B>,
+    retries: u8,
+    timeout: u32,
+    retransmits: u32,
+}
+
+impl<Tx, Rx, const B: usize> Acknowledged<Tx, Rx, B>
+where
+    Tx: WriteIter,
+    Rx: Read<u8>,
+{
+    /// Wrap transport and receiver of answers
+    pub fn new(tx: Tx, rx: Rx, retries: u8, timeout: u32) -> Self {
+        Self {
+            tx,
+            rx,
+            buffer: Buffer::new(),
+            retries,
+            timeout,
+            retransmits: 0,
+        }
+    }
+
+    /// Release transport and receiver
+    pub fn free(self) -> (Tx, Rx) {
+        (self.tx, self.rx)
+    }
+
+    /// Total number of repeated packages
+    pub fn retransmits(&self) -> u32 {
+        self.retransmits
+    }
+
+    /// Wait for answer. `None` on timeout
+    fn answer(&mut self) -> Result<Option<bool>, Rx::Error> {
+        for _ in 0..self.timeout {
+            match self.rx.read() {
+                Ok(ACK) => return Ok(Some(true)),
+                Ok(NACK) => return Ok(Some(false)),
+                Ok(_) | Err(nb::Error::WouldBlock) => {}
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<Tx, Rx, V, const B: usize> SendPackage<V> for Acknowledged<Tx, Rx, B>
+where
+    Tx: WriteIter,
+    Rx: Read<u8>,
+    Buffer<B>: SendPackage<V, Error = BufferOverflow>,
+{
+    type Error = DeliveryError<Tx::Error, Rx::Error>;
+
+    fn send_package(&mut self, module: &Name, values: &V) -> Result<(), Self::Error> {
+        self.buffer.clear();
+        self.buffer
+            .send_package(module, values)
+            .map_err(|_| DeliveryError::Overflow)?;
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                self.retransmits = self.retransmits.wrapping_add(1);
+            }
+            self.tx
+                .bwrite_iter(self.buffer.iter().cloned())
+                .map_err(DeliveryError::Transport)?;
+            self.tx.bflush().map_err(DeliveryError::Transport)?;
+            self.tx.bend_frame().map_err(DeliveryError::Transport)?;
+            if self.answer().map_err(DeliveryError::Receive)? == Some(true) {
+                return Ok(());
+            }
+        }
+        Err(DeliveryError::NotAcknowledged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+    use heapless::Deque;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    /// Receiver of queued answers, `0xFF` is read error
+    struct Answers(Deque<u8, 8>);
+
+    impl Read<u8> for Answers {
+        type Error = ();
+        fn read(&mut self) -> nb::Result<u8, ()> {
+            match self.0.pop_front() {
+                Some(0xFF) => Err(nb::Error::Other(())),
+                Some(b) => Ok(b),
+                None => Err(nb::Error::WouldBlock),
+            }
+        }
+    }
+
+    fn answers(bytes: &[u8]) -> Answers {
+        let mut answers = Answers(Deque::new());
+        for &b in bytes {
+            answers.0.push_back(b).unwrap();
+        }
+        answers
+    }
+
+    #[test]
+    fn retransmit() {
+        let mut map = SVMap::<1, 2>::new();
+        map.set(&A, 1).unwrap();
+        let mut size = Mock::<2048>::new();
+        size.send_package(&MODULE, &map).unwrap();
+        let size = size.as_slice().len();
+
+        let rx = answers(&[NACK, b'x', ACK, ACK]);
+        let mut tx = Acknowledged::<_, _, 2048>::new(Mock::<2048>::new(), rx, 2, 3);
+        tx.send_package(&MODULE, &map).unwrap();
+        assert_eq!(tx.retransmits(), 1);
+        tx.send_package(&MODULE, &map).unwrap();
+        assert_eq!(tx.retransmits(), 1);
+
+        // No answer in `timeout` polls
+        assert_eq!(
+            tx.send_package(&MODULE, &map),
+            Err(DeliveryError::NotAcknowledged)
+        );
+        assert_eq!(tx.retransmits(), 3);
+        assert_eq!(tx.tx.as_slice().len(), 6 * size);
+
+        tx.rx = answers(&[0xFF]);
+        assert_eq!(
+            tx.send_package(&MODULE, &map),
+            Err(DeliveryError::Receive(()))
+        );
+        let mut tx = Acknowledged::<_, _, 16>::new(Mock::<2048>::new(), answers(&[]), 0, 1);
+        assert_eq!(tx.send_package(&MODULE, &map), Err(DeliveryError::Overflow));
+    }
+}
@@ -0,0 +1,288 @@
+use crate::decoder::{trim, DecodeError};
+use crate::protocol::{padded, write_trailer, Protocol, SVisual};
+use crate::store::SignalStore;
+use crate::{Name, SVStruct, Sample, ValueType, WriteIter, NAME_SIZE};
+
+/// Marker of alarm frame
+pub const MARKER: &[u8] = b"=alarm=";
+
+/// Maximum number of alarms waiting for sending
+pub const MAX_ALARMS: usize = 4;
+
+/// Size of alarm frame
+pub const FRAME_SIZE: usize = MARKER.len() + 2 * NAME_SIZE + 12 + SVisual::END.len();
+
+/// Maximum number of checked entries of limit or threshold table
+pub(crate) const MAX_CHECKED: usize = 32;
+
+/// Active entries of limit or threshold table, bit `i` is entry `i`
+#[derive(Clone, Copy)]
+pub(crate) struct Active(u32);
+
+impl Active {
+    /// No active entries
+    pub(crate) const NONE: Self = Self(0);
+
+    /// Checks if any entry is active
+    pub(crate) fn any(self) -> bool {
+        self.0 != 0
+    }
+
+    /// Checks if entry `i` is active
+    pub(crate) fn get(self, i: usize) -> bool {
+        self.0 & (1 << i) != 0
+    }
+
+    /// Store state of entry `i`, returns `true` if entry became active
+    pub(crate) fn set(&mut self, i: usize, active: bool) -> bool {
+        let was = self.get(i);
+        if active {
+            self.0 |= 1 << i;
+        } else {
+            self.0 &= !(1 << i);
+        }
+        active && !was
+    }
+}
+
+/// Checked entries of `table` that belong to signal `name` with their indices
+pub(crate) fn entries<'a: 'n, 'n, T>(
+    table: &'a [T],
+    name: &'n Name,
+    key: fn(&T) -> &Name,
+) -> impl Iterator<Item = (usize, &'a T)> + 'n {
+    table
+        .iter()
+        .enumerate()
+        .take(MAX_CHECKED)
+        .filter(move |(_, e)| key(e) == name)
+}
+
+/// Allowed range of signal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limit {
+    /// Signal name
+    pub name: Name,
+    /// Minimal allowed value
+    pub min: f32,
+    /// Maximal allowed value
+    pub max: f32,
+}
+
+/// Violation of limit waiting for sending
+#[derive(Clone, Copy)]
+pub(crate) struct Alarm {
+    limit: u8,
+    value: f32,
+    slot: u32,
+}
+
+impl<S: SignalStore> SVStruct<S> {
+    /// Set allowed ranges of signals. First 32 limits are checked.
+    ///
+    /// Value leaving range raises alarm sent by [`send_alarms`](Self::send_alarms)
+    /// in separate frame, next alarm of signal is raised after return to range.
+    ///
+    /// Limits are compared with raw value after [`NonFinite`](crate::NonFinite) policy
+    /// and before profile, so they are set in units of the source
+    pub fn set_alarms(&mut self, limits: &'static [Limit]) {
+        self.limits = limits;
+        self.alarm_active = Active::NONE;
+        self.alarms = [None; MAX_ALARMS];
+    }
+
+    /// Check value against limits, alarms that do not fit in queue are lost
+    pub(crate) fn check_alarms(&mut self, name: &Name, vtype: ValueType, val: i32) {
+        let value = Sample::new(vtype, val).as_f32();
+        for (i, limit) in entries(self.limits, name, |l| &l.name) {
+            let violated = !(limit.min <= value && value <= limit.max);
+            if self.alarm_active.set(i, violated) {
+                if let Some(slot) = self.alarms.iter_mut().find(|a| a.is_none()) {
+                    *slot = Some(Alarm {
+                        limit: i as u8,
+                        value,
+                        slot: self.current as u32,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Checks if any alarm is waiting for sending
+    pub fn has_alarms(&self) -> bool {
+        self.alarms.iter().any(Option::is_some)
+    }
+
+    /// Send frames of raised alarms: module, signal, value, violated limit and time position.
+    ///
+    /// Returns number of sent alarms
+    pub fn send_alarms<Tx: WriteIter>(
+        &mut self,
+        tx: &mut Tx,
+        module: &Name,
+    ) -> Result<usize, Tx::Error> {
+        let mut sent = 0;
+        for pending in self.alarms.iter_mut() {
+            let alarm = match pending {
+                Some(alarm) => *alarm,
+                None => continue,
+            };
+            let limit = &self.limits[alarm.limit as usize];
+            let bound = if alarm.value < limit.min {
+                limit.min
+            } else {
+                limit.max
+            };
+            tx.bwrite_iter(
+                MARKER
+                    .iter()
+                    .cloned()
+                    .chain(padded::<SVisual, _>(module.bytes()))
+                    .chain(padded::<SVisual, _>(limit.name.bytes()))
+                    .chain(alarm.value.to_le_bytes())
+                    .chain(bound.to_le_bytes())
+                    .chain(alarm.slot.to_le_bytes()),
+            )?;
+            write_trailer::<SVisual, _>(tx)?;
+            *pending = None;
+            sent += 1;
+        }
+        Ok(sent)
+    }
+}
+
+/// Borrowed view of alarm frame
+#[derive(Clone, Copy, Debug)]
+pub struct AlarmView<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> AlarmView<'a> {
+    /// Parse alarm frame at the start of `bytes`.
+    ///
+    /// Returns frame view and the rest of bytes after the frame
+    pub fn parse(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), DecodeError> {
+        if bytes.len() < FRAME_SIZE {
+            return Err(DecodeError::Incomplete);
+        }
+        if !bytes.starts_with(MARKER) || !bytes[..FRAME_SIZE].ends_with(SVisual::END) {
+            return Err(DecodeError::InvalidMarker);
+        }
+        Ok((
+            Self {
+                bytes: &bytes[..FRAME_SIZE],
+            },
+            &bytes[FRAME_SIZE..],
+        ))
+    }
+
+    fn field(&self, offset: usize) -> [u8; 4] {
+        let b = &self.bytes[MARKER.len() + 2 * NAME_SIZE + offset..];
+        [b[0], b[1], b[2], b[3]]
+    }
+
+    /// Module name bytes without padding
+    pub fn module(&self) -> &'a [u8] {
+        trim(&self.bytes[MARKER.len()..MARKER.len() + NAME_SIZE])
+    }
+
+    /// Signal name bytes without padding
+    pub fn signal(&self) -> &'a [u8] {
+        trim(&self.bytes[MARKER.len() + NAME_SIZE..MARKER.len() + 2 * NAME_SIZE])
+    }
+
+    /// Value that raised alarm
+    pub fn value(&self) -> f32 {
+        f32::from_le_bytes(self.field(0))
+    }
+
+    /// Violated limit
+    pub fn limit(&self) -> f32 {
+        f32::from_le_bytes(self.field(4))
+    }
+
+    /// Time position in package
+    pub fn slot(&self) -> u32 {
+        u32::from_le_bytes(self.field(8))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+    use crate::profile::{ProfileRule, Transform};
+    use crate::NonFinite;
+
+    const MODULE: Name = Name::new("module");
+    const TEMP: Name = Name::new("temp");
+    const LIMITS: [Limit; 1] = [Limit {
+        name: TEMP,
+        min: 0.,
+        max: 100.,
+    }];
+
+    #[test]
+    fn raised_once_per_violation() {
+        let mut map = SVMap::<2, 4>::new();
+        map.set_alarms(&LIMITS);
+        // Alarm is checked on raw value, not on normalized one
+        map.set_profile(&[ProfileRule {
+            name: TEMP,
+            transform: Transform::Normalize {
+                min: 0.,
+                max: 1000.,
+            },
+        }]);
+        map.set(&TEMP, 50.).unwrap();
+        assert!(!map.has_alarms());
+        map.next(|_| {});
+        map.set(&TEMP, 120.).unwrap();
+        map.set(&TEMP, 130.).unwrap();
+        map.next(|_| {});
+        map.set(&TEMP, -5.).unwrap();
+
+        let mut buf = Buffer::<512>::new();
+        assert_eq!(map.send_alarms(&mut buf, &MODULE), Ok(1));
+        assert!(!map.has_alarms());
+        let (alarm, rest) = AlarmView::parse(&buf).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(alarm.module(), b"module");
+        assert_eq!(alarm.signal(), b"temp");
+        assert_eq!(
+            (alarm.value(), alarm.limit(), alarm.slot()),
+            (120., 100., 1)
+        );
+        assert_eq!(
+            AlarmView::parse(&buf[..FRAME_SIZE - 1]).unwrap_err(),
+            DecodeError::Incomplete
+        );
+
+        // -5 is still out of range, next alarm needs return to range
+        map.set(&TEMP, 50.).unwrap();
+        map.set(&TEMP, -5.).unwrap();
+        assert!(map.has_alarms());
+    }
+
+    #[test]
+    fn after_non_finite_policy() {
+        let mut map = SVMap::<2, 4>::new();
+        map.set_alarms(&LIMITS);
+        map.set(&TEMP, 50.).unwrap();
+        map.set_non_finite_policy(NonFinite::Hold);
+        map.set(&TEMP, f32::NAN).unwrap();
+        assert!(!map.has_alarms());
+        assert_eq!(map.get(&TEMP), Some(50.));
+
+        map.set_non_finite_policy(NonFinite::Clamp);
+        map.set(&TEMP, f32::INFINITY).unwrap();
+        assert!(map.has_alarms());
+        assert_eq!(map.get(&TEMP), Some(f32::MAX));
+
+        map.set_alarms(&LIMITS);
+        map.set_non_finite_policy(NonFinite::Pass);
+        map.set(&TEMP, f32::NAN).unwrap();
+        assert!(map.has_alarms());
+    }
+}
@@ -0,0 +1,65 @@
+use embedded_hal::serial::Write;
+use heapless::Vec;
+
+/// Buffer is full
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BufferOverflow;
+
+/// In-memory transport. Collects package bytes to be processed as a whole
+#[derive(Clone, Debug, Default)]
+pub struct Buffer<const B: usize>(Vec<u8, B>);
+
+impl<const B: usize> Buffer<B> {
+    /// Create empty buffer
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Written bytes
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Written bytes
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+
+    /// Remove all bytes
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Append bytes
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), BufferOverflow> {
+        self.0.extend_from_slice(bytes).map_err(|_| BufferOverflow)
+    }
+}
+
+impl<const B: usize> core::ops::Deref for Buffer<B> {
+    type Target = Vec<u8, B>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const B: usize> core::ops::DerefMut for Buffer<B> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<const B: usize> Write<u8> for Buffer<B> {
+    type Error = BufferOverflow;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.0
+            .push(word)
+            .map_err(|_| nb::Error::Other(BufferOverflow))
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+
+use crate::{AddError, Name, NextValue, SVMap, Value};
+
+/// Map shared between interrupt handlers and main loop, e.g. in `static`.
+///
+/// Every call runs in [`critical_section::with`], the target must provide
+/// implementation of `critical-section` crate, e.g. by `cortex-m` with
+/// `critical-section-single-core` feature.
+///
+/// # Locking cost
+///
+/// Interrupts are delayed for duration of one update, which is a lookup over `N` names.
+/// `next` also holds critical section while package is sent,
+/// so encode package into memory there instead of blocking transport.
+///
+/// # Panics
+///
+/// Calls are not reentrant: `with`, `set` and `next` panic on `RefCell::borrow_mut`
+/// if called on the same cell inside closure of `with` or send function of `next`
+pub struct SvCell<const N: usize, const P: usize> {
+    pub(crate) map: Mutex<RefCell<SVMap<N, P>>>,
+}
+
+impl<const N: usize, const P: usize> SvCell<N, P> {
+    /// Create cell with empty map
+    pub const fn new() -> Self {
+        Self {
+            map: Mutex::new(RefCell::new(SVMap::new())),
+        }
+    }
+
+    /// Run `f` with exclusive access to map
+    pub fn with<R>(&self, f: impl FnOnce(&mut SVMap<N, P>) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.map.borrow(cs).borrow_mut()))
+    }
+
+    /// Update value of specified type at current time position
+    pub fn set<T: Value>(&self, name: &'static Name, value: T) -> Result<(), AddError> {
+        self.with(|map| map.set(name, value))
+    }
+
+    /// Go to next time position. `F` is send package function
+    pub fn next<F>(&self, f: F)
+    where
+        F: FnOnce(&SVMap<N, P>),
+    {
+        self.with(|map| map.next(f))
+    }
+}
+
+impl<const N: usize, const P: usize> Default for SvCell<N, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    static CELL: SvCell<2, 2> = SvCell::new();
+
+    #[test]
+    fn shared() {
+        let mut buf = Buffer::<512>::new();
+        for i in 0..2 {
+            CELL.set(&A, i).unwrap();
+            CELL.next(|m| buf.send_package(&MODULE, m).unwrap());
+        }
+        assert!(!buf.is_empty());
+        assert_eq!(CELL.with(|m| m.get(&A)), Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "already")]
+    fn reentrant() {
+        let cell = SvCell::<2, 2>::new();
+        cell.with(|_| cell.set(&A, 1)).ok();
+    }
+}
@@ -0,0 +1,269 @@
+use crate::decoder::{DecodeError, PackageView};
+use crate::WriteIter;
+
+/// Checksum algorithm
+pub trait Checksum: Default {
+    /// Algorithm identifier, sent before checksum so receiver can detect algorithm
+    const ID: u8;
+    /// Size of checksum in bytes
+    const SIZE: usize;
+    /// Process next byte
+    fn update(&mut self, byte: u8);
+    /// Checksum of processed bytes
+    fn finish(&self) -> u32;
+
+    /// Checksum of bytes
+    fn checksum(bytes: &[u8]) -> u32 {
+        let mut c = Self::default();
+        for &b in bytes {
+            c.update(b);
+        }
+        c.finish()
+    }
+}
+
+/// CRC-16/CCITT-FALSE, polynomial `0x1021`
+#[derive(Clone, Copy, Debug)]
+pub struct Crc16(u16);
+
+impl Default for Crc16 {
+    fn default() -> Self {
+        Self(0xFFFF)
+    }
+}
+
+impl Checksum for Crc16 {
+    const ID: u8 = 1;
+    const SIZE: usize = 2;
+    fn update(&mut self, byte: u8) {
+        self.0 ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            self.0 = if self.0 & 0x8000 != 0 {
+                (self.0 << 1) ^ 0x1021
+            } else {
+                self.0 << 1
+            };
+        }
+    }
+    fn finish(&self) -> u32 {
+        self.0 as u32
+    }
+}
+
+/// CRC-32 (IEEE 802.3), computed without table to keep flash usage small
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32(u32);
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self(!0)
+    }
+}
+
+impl Checksum for Crc32 {
+    const ID: u8 = 2;
+    const SIZE: usize = 4;
+    fn update(&mut self, byte: u8) {
+        self.0 ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (self.0 & 1).wrapping_neg();
+            self.0 = (self.0 >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// Fletcher-16, cheapest check for resource-constrained devices
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Fletcher16 {
+    sum1: u16,
+    sum2: u16,
+}
+
+impl Checksum for Fletcher16 {
+    const ID: u8 = 3;
+    const SIZE: usize = 2;
+    fn update(&mut self, byte: u8) {
+        self.sum1 = (self.sum1 + byte as u16) % 255;
+        self.sum2 = (self.sum2 + self.sum1) % 255;
+    }
+    fn finish(&self) -> u32 {
+        ((self.sum2 as u32) << 8) | self.sum1 as u32
+    }
+}
+
+/// Transport appending checksum trailer to each package.
+///
+/// Trailer is algorithm identifier followed by little-endian checksum
+/// of all package bytes from `=begin=` to `=end=`. As byte transport, it checks
+/// packages of any container and frames of other layers written into it.
+/// Advertise algorithm to receiver with [`Hello::with_checksum`](crate::handshake::Hello::with_checksum)
+pub struct Checked<Tx, C> {
+    tx: Tx,
+    checksum: C,
+}
+
+impl<Tx, C: Checksum> Checked<Tx, C> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self {
+            tx,
+            checksum: C::default(),
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+impl<Tx: WriteIter, C: Checksum> WriteIter for Checked<Tx, C> {
+    type Error = Tx::Error;
+
+    fn bwrite_iter<WI>(&mut self, bytes: WI) -> Result<(), Self::Error>
+    where
+        WI: Iterator<Item = u8>,
+    {
+        let checksum = &mut self.checksum;
+        self.tx.bwrite_iter(bytes.inspect(|&b| checksum.update(b)))
+    }
+
+    fn bflush(&mut self) -> Result<(), Self::Error> {
+        self.tx.bflush()
+    }
+
+    fn bend_frame(&mut self) -> Result<(), Self::Error> {
+        let sum = self.checksum.finish().to_le_bytes();
+        self.checksum = C::default();
+        self.tx
+            .bwrite_iter(core::iter::once(C::ID).chain(sum.iter().take(C::SIZE).cloned()))?;
+        self.tx.bflush()?;
+        self.tx.bend_frame()
+    }
+}
+
+/// Errors of checksum verification
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChecksumError {
+    /// More bytes are needed
+    Incomplete,
+    /// Unknown algorithm identifier
+    UnknownAlgorithm,
+    /// Checksum does not match
+    Mismatch,
+    /// Package is malformed
+    Decode(DecodeError),
+}
+
+/// Verify `package` with checksum from the start of `trailer`,
+/// algorithm is detected by its identifier.
+///
+/// Returns size of trailer
+pub fn verify(package: &[u8], trailer: &[u8]) -> Result<usize, ChecksumError> {
+    let (sum, size) = match trailer.first() {
+        None => return Err(ChecksumError::Incomplete),
+        Some(&Crc16::ID) => (Crc16::checksum(package), Crc16::SIZE),
+        Some(&Crc32::ID) => (Crc32::checksum(package), Crc32::SIZE),
+        Some(&Fletcher16::ID) => (Fletcher16::checksum(package), Fletcher16::SIZE),
+        Some(_) => return Err(ChecksumError::UnknownAlgorithm),
+    };
+    let received = trailer.get(1..1 + size).ok_or(ChecksumError::Incomplete)?;
+    if received != &sum.to_le_bytes()[..size] {
+        return Err(ChecksumError::Mismatch);
+    }
+    Ok(1 + size)
+}
+
+impl<'a> PackageView<'a> {
+    /// Parse package followed by checksum trailer of [`Checked`] transport.
+    ///
+    /// Returns package view and the rest of bytes after the trailer
+    pub fn parse_checked(
+        bytes: &'a [u8],
+        values: usize,
+    ) -> Result<(Self, &'a [u8]), ChecksumError> {
+        let (view, rest) = Self::parse(bytes, values).map_err(ChecksumError::Decode)?;
+        let trailer = verify(&bytes[..bytes.len() - rest.len()], rest)?;
+        Ok((view, &rest[trailer..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn check_values() {
+        let data = b"123456789";
+        assert_eq!(Crc16::checksum(data), 0x29B1);
+        assert_eq!(Crc32::checksum(data), 0xCBF4_3926);
+        assert_eq!(Fletcher16::checksum(data), 0x1EDE);
+    }
+
+    /// Package sent by `Checked` transport
+    fn checked<C: Checksum>() -> heapless::Vec<u8, 2048> {
+        let mut map = SVMap::<1, 2>::new();
+        map.set(&A, 3).unwrap();
+        let mut tx = Checked::<_, C>::new(Mock::<2048>::new());
+        tx.send_package(&MODULE, &map).unwrap();
+        tx.send_package(&MODULE, &map).unwrap();
+        let mut bytes = heapless::Vec::new();
+        bytes.extend_from_slice(tx.free().as_slice()).unwrap();
+        bytes
+    }
+
+    fn round_trip<C: Checksum>() {
+        let bytes = checked::<C>();
+        let (view, rest) = PackageView::parse_checked(&bytes, 2).unwrap();
+        assert_eq!(view.module_str(), Some("module"));
+        let (_, rest) = PackageView::parse_checked(rest, 2).unwrap();
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn verify_round_trip() {
+        round_trip::<Crc16>();
+        round_trip::<Crc32>();
+        round_trip::<Fletcher16>();
+    }
+
+    #[test]
+    fn corrupted_trailer() {
+        let mut bytes = checked::<Crc16>();
+        let first = bytes.len() / 2;
+        let package = &bytes[..first - 1 - Crc16::SIZE];
+        let trailer = &bytes[first - 1 - Crc16::SIZE..first];
+        assert_eq!(verify(package, trailer), Ok(1 + Crc16::SIZE));
+        assert_eq!(
+            verify(package, &trailer[..2]),
+            Err(ChecksumError::Incomplete)
+        );
+        assert_eq!(verify(package, &[]), Err(ChecksumError::Incomplete));
+        assert_eq!(
+            verify(package, &[0xAA, 0, 0]),
+            Err(ChecksumError::UnknownAlgorithm)
+        );
+
+        bytes[first - 1] ^= 1;
+        assert_eq!(
+            PackageView::parse_checked(&bytes, 2).map(|_| ()),
+            Err(ChecksumError::Mismatch)
+        );
+        // Identifier of other algorithm
+        bytes[first - 1] ^= 1;
+        bytes[first - 1 - Crc16::SIZE] = Fletcher16::ID;
+        assert_eq!(
+            PackageView::parse_checked(&bytes, 2).map(|_| ()),
+            Err(ChecksumError::Mismatch)
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use crate::WriteIter;
+
+/// Transport splitting frames into chunks of at most `size` bytes,
+/// e.g. for USB or BLE packets.
+///
+/// Transport is flushed when chunk is full and at the end of frame,
+/// flush requests in between are ignored, so chunks are filled completely
+pub struct Chunked<Tx> {
+    tx: Tx,
+    size: usize,
+    filled: usize,
+}
+
+impl<Tx: WriteIter> Chunked<Tx> {
+    /// Wrap transport, `size` 0 is treated as 1
+    pub fn new(tx: Tx, size: usize) -> Self {
+        Self {
+            tx,
+            size: size.max(1),
+            filled: 0,
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+impl<Tx: WriteIter> WriteIter for Chunked<Tx> {
+    type Error = Tx::Error;
+
+    fn bwrite_iter<WI>(&mut self, mut bytes: WI) -> Result<(), Self::Error>
+    where
+        WI: Iterator<Item = u8>,
+    {
+        loop {
+            let mut written = 0;
+            self.tx.bwrite_iter(
+                bytes
+                    .by_ref()
+                    .take(self.size - self.filled)
+                    .inspect(|_| written += 1),
+            )?;
+            self.filled += written;
+            if self.filled < self.size {
+                return Ok(());
+            }
+            self.filled = 0;
+            self.tx.bflush()?;
+        }
+    }
+
+    fn bflush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn bend_frame(&mut self) -> Result<(), Self::Error> {
+        if self.filled > 0 {
+            self.filled = 0;
+            self.tx.bflush()?;
+        }
+        self.tx.bend_frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+
+    #[test]
+    fn chunks_are_filled() {
+        let mut tx = Chunked::new(Mock::<64>::new(), 4);
+        tx.bwrite_iter(0..3).unwrap();
+        // Flush in between is ignored
+        tx.bflush().unwrap();
+        tx.bwrite_iter(3..10).unwrap();
+        assert_eq!(tx.tx.flushes(), 2);
+        tx.bend_frame().unwrap();
+        assert_eq!(tx.tx.flushes(), 3);
+        // Nothing is pending at the end of next frame
+        tx.bwrite_iter(0..4).unwrap();
+        tx.bend_frame().unwrap();
+        let mock = tx.free();
+        assert_eq!(mock.flushes(), 4);
+        assert_eq!(mock.as_slice(), &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 3]);
+
+        let mut tx = Chunked::new(Mock::<64>::new(), 0);
+        tx.bwrite_iter(0..2).unwrap();
+        assert_eq!(tx.free().flushes(), 2);
+    }
+}
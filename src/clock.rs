@@ -0,0 +1,112 @@
+use core::iter::once;
+
+use crate::decoder::PackageView;
+use crate::{write_package, Name, SVMap, SendPackage, ValueType, WriteIter};
+
+/// Name of record with package timestamp
+pub const TIME_NAME: Name = Name::new("@time");
+
+/// Source of timestamps, e.g. DWT cycle counter, RTC or monotonic timer
+pub trait Clock {
+    /// Current time in clock units
+    fn now(&mut self) -> u32;
+}
+
+impl<F: FnMut() -> u32> Clock for F {
+    fn now(&mut self) -> u32 {
+        self()
+    }
+}
+
+/// Transport adding time of sending to each package,
+/// so traces of devices with jittery tick period align on host.
+///
+/// Time is sent as `Int` record [`TIME_NAME`] with the same value at all positions.
+/// Stack it over byte level layers, e.g. `Timestamped::new(Checked::<_, Crc16>::new(uart), clock)`
+pub struct Timestamped<Tx, C> {
+    tx: Tx,
+    clock: C,
+}
+
+impl<Tx, C: Clock> Timestamped<Tx, C> {
+    /// Wrap transport
+    pub fn new(tx: Tx, clock: C) -> Self {
+        Self { tx, clock }
+    }
+
+    /// Release transport and clock
+    pub fn free(self) -> (Tx, C) {
+        (self.tx, self.clock)
+    }
+}
+
+impl<Tx, C, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for Timestamped<Tx, C>
+where
+    Tx: WriteIter,
+    C: Clock,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        let time = [self.clock.now() as i32; P];
+        write_package(
+            &mut self.tx,
+            module,
+            P,
+            once((&*TIME_NAME, ValueType::Int, &time[..])).chain(
+                values
+                    .map
+                    .iter()
+                    .map(|(&name, v)| (name, v.vtype, &v.vals[..])),
+            ),
+            &values.notes,
+        )
+    }
+}
+
+impl PackageView<'_> {
+    /// Time of sending if package is timestamped
+    pub fn timestamp(&self) -> Option<u32> {
+        self.records()
+            .find(|r| r.name() == TIME_NAME.as_bytes())
+            .and_then(|r| r.get(0))
+            .map(|t| t as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn timestamp_round_trip() {
+        let mut time = 1000;
+        let clock = move || {
+            time += 250;
+            time
+        };
+        let mut tx = Timestamped::new(Mock::<1024>::new(), clock);
+        let mut map = SVMap::<1, 2>::new();
+        for i in 0..4 {
+            map.set(&A, i).unwrap();
+            map.next(|m| tx.send_package(&MODULE, m).unwrap());
+        }
+        let (tx, _) = tx.free();
+        let (first, rest) = PackageView::parse(tx.as_slice(), 2).unwrap();
+        let (second, _) = PackageView::parse(rest, 2).unwrap();
+        assert_eq!(first.timestamp(), Some(1250));
+        assert_eq!(second.timestamp(), Some(1500));
+        assert_eq!(first.records().nth(1).unwrap().name(), b"a");
+
+        let mut plain = Mock::<1024>::new();
+        map.next(|_| {});
+        map.next(|m| plain.send_package(&MODULE, m).unwrap());
+        let (view, _) = PackageView::parse(plain.as_slice(), 2).unwrap();
+        assert_eq!(view.timestamp(), None);
+    }
+}
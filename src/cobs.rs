@@ -0,0 +1,178 @@
+use core::iter::once;
+
+use heapless::Vec;
+
+use crate::WriteIter;
+
+/// Maximum number of non-zero bytes in one COBS block
+const BLOCK: usize = 254;
+
+/// Transport encoding each frame with Consistent Overhead Byte Stuffing.
+///
+/// Encoded frame has no zero bytes and is terminated by zero,
+/// so receiver resynchronizes on next zero after byte loss.
+/// Overhead is one byte per 254 bytes and delimiter
+pub struct Cobs<Tx> {
+    tx: Tx,
+    block: Vec<u8, BLOCK>,
+}
+
+impl<Tx: WriteIter> Cobs<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self {
+            tx,
+            block: Vec::new(),
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+
+    /// Write code byte and pending block
+    fn emit(&mut self) -> Result<(), Tx::Error> {
+        let code = self.block.len() as u8 + 1;
+        self.tx
+            .bwrite_iter(once(code).chain(self.block.iter().cloned()))?;
+        self.block.clear();
+        Ok(())
+    }
+}
+
+impl<Tx: WriteIter> WriteIter for Cobs<Tx> {
+    type Error = Tx::Error;
+
+    fn bwrite_iter<WI>(&mut self, bytes: WI) -> Result<(), Self::Error>
+    where
+        WI: Iterator<Item = u8>,
+    {
+        for b in bytes {
+            if b == 0 {
+                self.emit()?;
+            } else {
+                self.block.push(b).ok();
+                if self.block.is_full() {
+                    self.emit()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn bflush(&mut self) -> Result<(), Self::Error> {
+        self.tx.bflush()
+    }
+
+    fn bend_frame(&mut self) -> Result<(), Self::Error> {
+        self.emit()?;
+        self.tx.bwrite_iter(once(0))?;
+        self.tx.bflush()?;
+        self.tx.bend_frame()
+    }
+}
+
+/// Errors of COBS decoding
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CobsError {
+    /// Frame contains zero byte or code points past its end
+    Corrupted,
+    /// Output buffer is too small
+    Overflow,
+}
+
+/// Decode COBS `frame` without delimiter into `out`.
+///
+/// Returns size of decoded frame
+pub fn decode(frame: &[u8], out: &mut [u8]) -> Result<usize, CobsError> {
+    let mut pos = 0;
+    let mut len = 0;
+    while pos < frame.len() {
+        let code = frame[pos] as usize;
+        if code == 0 {
+            return Err(CobsError::Corrupted);
+        }
+        let block = frame.get(pos + 1..pos + code).ok_or(CobsError::Corrupted)?;
+        if block.contains(&0) {
+            return Err(CobsError::Corrupted);
+        }
+        out.get_mut(len..len + block.len())
+            .ok_or(CobsError::Overflow)?
+            .copy_from_slice(block);
+        len += block.len();
+        pos += code;
+        // Block shorter than maximum is followed by zero, except the last one
+        if code <= BLOCK && pos < frame.len() {
+            *out.get_mut(len).ok_or(CobsError::Overflow)? = 0;
+            len += 1;
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+
+    fn encode(bytes: &[u8]) -> Buffer<1024> {
+        let mut cobs = Cobs::new(Buffer::new());
+        cobs.bwrite_iter(bytes.iter().cloned()).unwrap();
+        cobs.bend_frame().unwrap();
+        cobs.free()
+    }
+
+    fn round_trip(bytes: &[u8]) {
+        let frame = encode(bytes);
+        let (delimiter, frame) = frame.split_last().unwrap();
+        assert_eq!(*delimiter, 0);
+        assert!(!frame.contains(&0));
+        let mut out = [0; 1024];
+        let len = decode(frame, &mut out).unwrap();
+        assert_eq!(&out[..len], bytes);
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trip(&[]);
+        round_trip(&[0]);
+        round_trip(&[0, 0, 0]);
+        round_trip(&[1, 2, 0, 3]);
+        round_trip(b"=begin=\0\0\0\0test=end=");
+        let mut long = [0x11; 600];
+        round_trip(&long[..BLOCK]);
+        round_trip(&long[..BLOCK + 1]);
+        long[BLOCK] = 0;
+        round_trip(&long[..BLOCK + 1]);
+        round_trip(&long);
+    }
+
+    #[test]
+    fn truncated() {
+        let frame = encode(&[1, 2, 3, 0, 4, 5]);
+        // Last block is cut, code points past end of frame
+        assert_eq!(
+            decode(&frame[..frame.len() - 2], &mut [0; 16]),
+            Err(CobsError::Corrupted)
+        );
+    }
+
+    #[test]
+    fn corrupted() {
+        let mut frame = encode(&[1, 2, 3, 0, 4, 5]);
+        frame.pop();
+        frame[2] = 0;
+        assert_eq!(decode(&frame, &mut [0; 16]), Err(CobsError::Corrupted));
+        frame[0] = 0;
+        assert_eq!(decode(&frame, &mut [0; 16]), Err(CobsError::Corrupted));
+    }
+
+    #[test]
+    fn overflow() {
+        let frame = encode(&[1, 2, 3, 0, 4, 5]);
+        let frame = &frame[..frame.len() - 1];
+        assert_eq!(decode(frame, &mut [0; 5]), Err(CobsError::Overflow));
+    }
+}
@@ -0,0 +1,211 @@
+use embedded_hal::serial::Read;
+
+use crate::protocol::{Protocol, SVisual};
+use crate::{Name, SVStruct, NAME_SIZE};
+
+/// Marker of command frame
+pub const MARKER: &[u8] = b"=cmd=";
+
+/// Size of command frame
+pub const FRAME_SIZE: usize = MARKER.len() + 1 + NAME_SIZE + 4 + SVisual::END.len();
+
+/// Command received from host
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command {
+    /// Set parameter to value
+    Set(Name, i32),
+    /// Change tick period, in application units
+    Period(u32),
+    /// Start (resume) recording
+    Start,
+    /// Stop (pause) recording
+    Stop,
+}
+
+impl Command {
+    fn code(&self) -> u8 {
+        match self {
+            Self::Set(..) => 1,
+            Self::Period(_) => 2,
+            Self::Start => 3,
+            Self::Stop => 4,
+        }
+    }
+
+    /// Command frame, e.g. for sending from host
+    pub fn encode(&self) -> [u8; FRAME_SIZE] {
+        let mut frame = [SVisual::PADDING; FRAME_SIZE];
+        let (name, value) = match self {
+            Self::Set(name, value) => (name.as_bytes(), *value),
+            Self::Period(period) => (&[][..], *period as i32),
+            _ => (&[][..], 0),
+        };
+        frame[..MARKER.len()].copy_from_slice(MARKER);
+        frame[MARKER.len()] = self.code();
+        let name_start = MARKER.len() + 1;
+        frame[name_start..name_start + name.len()].copy_from_slice(name);
+        let end = FRAME_SIZE - SVisual::END.len();
+        frame[name_start + NAME_SIZE..end].copy_from_slice(&value.to_le_bytes());
+        frame[end..].copy_from_slice(SVisual::END);
+        frame
+    }
+
+    /// Parse complete command frame. `None` if frame is invalid
+    pub fn decode(frame: &[u8; FRAME_SIZE]) -> Option<Self> {
+        if !frame.starts_with(MARKER) || !frame.ends_with(SVisual::END) {
+            return None;
+        }
+        let name_start = MARKER.len() + 1;
+        let v = &frame[name_start + NAME_SIZE..];
+        let value = i32::from_le_bytes([v[0], v[1], v[2], v[3]]);
+        Some(match frame[MARKER.len()] {
+            1 => {
+                let name = &frame[name_start..name_start + NAME_SIZE];
+                let len = name.iter().position(|&b| b == SVisual::PADDING)?;
+                let name = core::str::from_utf8(&name[..len]).ok()?;
+                Self::Set(Name::try_new(name).ok()?, value)
+            }
+            2 => Self::Period(value as u32),
+            3 => Self::Start,
+            4 => Self::Stop,
+            _ => return None,
+        })
+    }
+}
+
+/// Receiver of command frames from the same link as packages.
+///
+/// Bytes before marker and invalid frames are skipped
+pub struct CommandReader {
+    frame: [u8; FRAME_SIZE],
+    len: usize,
+}
+
+impl CommandReader {
+    /// Create receiver
+    pub const fn new() -> Self {
+        Self {
+            frame: [0; FRAME_SIZE],
+            len: 0,
+        }
+    }
+
+    /// Process next received byte. Returns command if frame is complete
+    pub fn feed(&mut self, byte: u8) -> Option<Command> {
+        self.frame[self.len] = byte;
+        self.len += 1;
+        // Resynchronize on marker
+        while self.len > 0
+            && self.frame[..self.len.min(MARKER.len())] != MARKER[..self.len.min(MARKER.len())]
+        {
+            self.frame.copy_within(1..self.len, 0);
+            self.len -= 1;
+        }
+        if self.len < FRAME_SIZE {
+            return None;
+        }
+        self.len = 0;
+        Command::decode(&self.frame)
+    }
+
+    /// Read all available bytes of `rx` and dispatch received commands to `f`
+    pub fn poll<Rx, F>(&mut self, rx: &mut Rx, mut f: F) -> Result<(), Rx::Error>
+    where
+        Rx: Read<u8>,
+        F: FnMut(Command),
+    {
+        loop {
+            match rx.read() {
+                Ok(byte) => {
+                    if let Some(command) = self.feed(byte) {
+                        f(command);
+                    }
+                }
+                Err(nb::Error::WouldBlock) => return Ok(()),
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Default for CommandReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> SVStruct<M> {
+    /// Apply `Start` and `Stop` commands. Returns `false` for other commands
+    pub fn apply(&mut self, command: &Command) -> bool {
+        match command {
+            Command::Start => self.resume(),
+            Command::Stop => self.pause(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const GAIN: SVName = SVName::new("gain");
+    const COMMANDS: [Command; 4] = [
+        Command::Set(GAIN, -7),
+        Command::Period(100),
+        Command::Start,
+        Command::Stop,
+    ];
+
+    /// Receiver of bytes of slice
+    struct Bytes<'a>(&'a [u8]);
+
+    impl Read<u8> for Bytes<'_> {
+        type Error = ();
+        fn read(&mut self) -> nb::Result<u8, ()> {
+            let (&b, rest) = self.0.split_first().ok_or(nb::Error::WouldBlock)?;
+            self.0 = rest;
+            Ok(b)
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for command in COMMANDS.iter() {
+            assert_eq!(Command::decode(&command.encode()), Some(*command));
+        }
+        let mut frame = Command::Start.encode();
+        frame[MARKER.len()] = 9;
+        assert_eq!(Command::decode(&frame), None);
+    }
+
+    #[test]
+    fn reader_resynchronizes() {
+        let mut bytes = [0; 3 * FRAME_SIZE + 6];
+        bytes[..3].copy_from_slice(b"=c=");
+        bytes[3..3 + FRAME_SIZE].copy_from_slice(&Command::Set(GAIN, 5).encode());
+        // Broken frame is dropped
+        let mut broken = Command::Stop.encode();
+        broken[FRAME_SIZE - 1] = b'x';
+        bytes[3 + FRAME_SIZE..3 + 2 * FRAME_SIZE].copy_from_slice(&broken);
+        bytes[3 + 2 * FRAME_SIZE..6 + 2 * FRAME_SIZE].copy_from_slice(b"===");
+        bytes[6 + 2 * FRAME_SIZE..].copy_from_slice(&Command::Stop.encode());
+
+        let mut reader = CommandReader::new();
+        let mut received = heapless::Vec::<Command, 4>::new();
+        reader
+            .poll(&mut Bytes(&bytes), |c| received.push(c).unwrap())
+            .unwrap();
+        assert_eq!(&received[..], &[Command::Set(GAIN, 5), Command::Stop]);
+
+        let mut map = SVMap::<1, 1>::new();
+        assert!(map.apply(&Command::Stop));
+        assert!(map.is_paused());
+        assert!(map.apply(&Command::Start));
+        assert!(!map.is_paused());
+        assert!(!map.apply(&Command::Period(1)));
+    }
+}
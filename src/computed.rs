@@ -0,0 +1,130 @@
+use crate::store::{SignalStore, Static};
+use crate::{Name, SVStruct, Sample};
+
+/// Expression over values of signals at current time position
+#[derive(Clone, Copy, Debug)]
+pub enum Expr {
+    /// `a + b`
+    Add(Name, Name),
+    /// `a - b`
+    Sub(Name, Name),
+    /// `a * b`, e.g. power from voltage and current
+    Mul(Name, Name),
+    /// `a / b`
+    Div(Name, Name),
+    /// `source * scale + offset`
+    Linear {
+        /// Source signal
+        source: Name,
+        /// Multiplier
+        scale: f32,
+        /// Added offset
+        offset: f32,
+    },
+    /// User function of two signals
+    Fn(fn(f32, f32) -> f32, Name, Name),
+}
+
+/// `Float` signal computed from other signals
+#[derive(Clone, Copy, Debug)]
+pub struct Computed {
+    /// Signal name
+    pub name: Name,
+    /// Expression
+    pub expr: Expr,
+}
+
+impl<S: SignalStore> SVStruct<S> {
+    /// Set signals computed on each `next()` call from values at current time position.
+    ///
+    /// Signals are computed in order, so expression can use previous computed signals.
+    /// Signal is not updated while any of its sources is absent
+    pub fn set_computed(&mut self, computed: &'static [Computed]) {
+        self.computed = computed;
+    }
+
+    fn value(&self, name: &Name) -> Option<f32> {
+        self.map
+            .get(name)
+            .map(|vr| Sample::new(vr.vtype, self.current_value(vr)).as_f32())
+    }
+
+    /// Evaluate computed signals at current time position
+    pub(crate) fn update_computed(&mut self) {
+        let computed = self.computed;
+        for c in computed {
+            let value = match c.expr {
+                Expr::Add(a, b) => self.value(&a).zip(self.value(&b)).map(|(a, b)| a + b),
+                Expr::Sub(a, b) => self.value(&a).zip(self.value(&b)).map(|(a, b)| a - b),
+                Expr::Mul(a, b) => self.value(&a).zip(self.value(&b)).map(|(a, b)| a * b),
+                Expr::Div(a, b) => self.value(&a).zip(self.value(&b)).map(|(a, b)| a / b),
+                Expr::Linear {
+                    source,
+                    scale,
+                    offset,
+                } => self.value(&source).map(|v| v * scale + offset),
+                Expr::Fn(f, a, b) => self.value(&a).zip(self.value(&b)).map(|(a, b)| f(a, b)),
+            };
+            if let Some(value) = value {
+                // Errors are the same as of direct update and are ignored here
+                self.set(Static(&c.name), value).ok();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const U: SVName = SVName::new("u");
+    const I: SVName = SVName::new("i");
+    const POWER: SVName = SVName::new("power");
+    const SCALED: SVName = SVName::new("scaled");
+    const MISSING: SVName = SVName::new("missing");
+    const RATIO: SVName = SVName::new("ratio");
+    const HYPOT: SVName = SVName::new("hypot");
+
+    static COMPUTED: [Computed; 4] = [
+        Computed {
+            name: POWER,
+            expr: Expr::Mul(U, I),
+        },
+        // Uses computed signal above
+        Computed {
+            name: SCALED,
+            expr: Expr::Linear {
+                source: POWER,
+                scale: 2.,
+                offset: 1.,
+            },
+        },
+        Computed {
+            name: RATIO,
+            expr: Expr::Div(U, MISSING),
+        },
+        Computed {
+            name: HYPOT,
+            expr: Expr::Fn(|a, b| a.max(b), U, I),
+        },
+    ];
+
+    #[test]
+    fn evaluated_on_next() {
+        let mut map = SVMap::<6, 1>::new();
+        map.set_computed(&COMPUTED);
+        map.set(&U, 2).unwrap();
+        map.set(&I, 1.5).unwrap();
+        let mut sent = false;
+        map.next(|m| {
+            assert_eq!(m.get(&POWER), Some(3.));
+            assert_eq!(m.get(&SCALED), Some(7.));
+            assert_eq!(m.get(&HYPOT), Some(2.));
+            // Not added while source is absent
+            assert_eq!(m.get::<f32, _>(&RATIO), None);
+            sent = true;
+        });
+        assert!(sent);
+    }
+}
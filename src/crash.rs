@@ -0,0 +1,86 @@
+use crate::cell::SvCell;
+use crate::{next_slot, write_package, Name, SVMap, WriteIter};
+
+/// Errors of sending in-flight package from shared cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CrashError<E> {
+    /// Map was borrowed when crash happened, e.g. panic inside `set`
+    Busy,
+    /// Transport error
+    Transport(E),
+}
+
+impl<const N: usize, const P: usize> SVMap<N, P> {
+    /// Send current partially-filled package, e.g. from `panic_handler` or HardFault.
+    ///
+    /// Remaining time positions continue last values as on `next()`.
+    /// `tx` must be polling transport, because interrupts are usually disabled at this point
+    pub fn send_in_flight<Tx: WriteIter>(
+        &mut self,
+        tx: &mut Tx,
+        module: &Name,
+    ) -> Result<(), Tx::Error> {
+        let current = self.current;
+        for v in self.map.values_mut() {
+            let last = v.vals[current];
+            for slot in &mut v.vals[current + 1..] {
+                *slot = next_slot(v.front, last);
+            }
+        }
+        write_package(
+            tx,
+            module,
+            P,
+            self.map
+                .iter()
+                .map(|(&name, v)| (name, v.vtype, &v.vals[..])),
+            &self.notes,
+        )
+    }
+}
+
+impl<const N: usize, const P: usize> SvCell<N, P> {
+    /// Send current partially-filled package in critical section,
+    /// see [`SVMap::send_in_flight`]
+    pub fn send_in_flight<Tx: WriteIter>(
+        &self,
+        tx: &mut Tx,
+        module: &Name,
+    ) -> Result<(), CrashError<Tx::Error>> {
+        critical_section::with(|cs| match self.map.borrow(cs).try_borrow_mut() {
+            Ok(mut map) => map
+                .send_in_flight(tx, module)
+                .map_err(CrashError::Transport),
+            Err(_) => Err(CrashError::Busy),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::PackageView;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn partial_package() {
+        let cell = SvCell::<1, 4>::new();
+        let mut tx = Mock::<512>::new();
+        cell.set(&A, 1).unwrap();
+        cell.next(|_| panic!("package is not full"));
+        cell.set(&A, 2).unwrap();
+        cell.send_in_flight(&mut tx, &MODULE).unwrap();
+        let (view, rest) = PackageView::parse(tx.as_slice(), 4).unwrap();
+        assert!(rest.is_empty());
+        // Remaining positions hold last value
+        assert!(view.records().next().unwrap().values().eq([1, 2, 2, 2]));
+
+        let busy = cell.with(|_| cell.send_in_flight(&mut Mock::<512>::new(), &MODULE));
+        assert_eq!(busy, Err(CrashError::Busy));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::buffer::{Buffer, BufferOverflow};
+use crate::{Name, SendPackage, WriteIter};
+
+/// Marker of encrypted frame
+pub const MARKER: &[u8] = b"=enc=";
+/// Size of nonce
+pub const NONCE_SIZE: usize = 12;
+/// Size of authentication tag
+pub const TAG_SIZE: usize = 16;
+/// Marker, nonce and ciphertext length
+pub const HEADER_SIZE: usize = MARKER.len() + NONCE_SIZE + 4;
+
+/// Authenticated encryption with associated data.
+///
+/// Any AEAD with 96-bit nonce and 128-bit tag fits.
+/// `ChaCha20Poly1305` of RustCrypto implements it with `chacha20poly1305` feature,
+/// dependency-free fallback of the crate is enabled by `builtin-cipher` feature
+pub trait Aead {
+    /// Error type
+    type Error;
+    /// Encrypt `buffer` in place, return authentication tag
+    fn encrypt(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        buffer: &mut [u8],
+    ) -> Result<[u8; TAG_SIZE], Self::Error>;
+    /// Decrypt `buffer` in place, fail if `tag` does not match
+    fn decrypt(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        buffer: &mut [u8],
+        tag: &[u8; TAG_SIZE],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Errors of encrypted transport
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CryptoError<E, C> {
+    /// Package does not fit in buffer
+    Overflow,
+    /// Nonce counter exhausted, key must be changed
+    NonceExhausted,
+    /// Cipher error
+    Cipher(C),
+    /// Transport error
+    Transport(E),
+}
+
+/// Transport that encrypts each package. `B` is maximum package size.
+///
+/// Frame is `=enc=`, nonce, ciphertext length (`u32`), ciphertext and tag.
+/// Nonce consists of 4-byte prefix and 64-bit package counter.
+/// Prefix must differ between device runs with the same key, e.g. boot counter
+pub struct Encrypted<Tx, C, const B: usize> {
+    tx: Tx,
+    cipher: C,
+    prefix: [u8; 4],
+    counter: u64,
+    buffer: Buffer<B>,
+}
+
+impl<Tx, C, const B: usize> Encrypted<Tx, C, B> {
+    /// Wrap transport
+    pub fn new(tx: Tx, cipher: C, nonce_prefix: [u8; 4]) -> Self {
+        Self {
+            tx,
+            cipher,
+            prefix: nonce_prefix,
+            counter: 0,
+            buffer: Buffer::new(),
+        }
+    }
+
+    /// Release transport and cipher
+    pub fn free(self) -> (Tx, C) {
+        (self.tx, self.cipher)
+    }
+
+    fn nonce(&self) -> [u8; NONCE_SIZE] {
+        let mut nonce = [0; NONCE_SIZE];
+        nonce[..4].copy_from_slice(&self.prefix);
+        nonce[4..].copy_from_slice(&self.counter.to_le_bytes());
+        nonce
+    }
+}
+
+impl<Tx, C, V, const B: usize> SendPackage<V> for Encrypted<Tx, C, B>
+where
+    Tx: WriteIter,
+    C: Aead,
+    Buffer<B>: SendPackage<V, Error = BufferOverflow>,
+{
+    type Error = CryptoError<Tx::Error, C::Error>;
+
+    fn send_package(&mut self, module: &Name, values: &V) -> Result<(), Self::Error> {
+        if self.counter == u64::MAX {
+            return Err(CryptoError::NonceExhausted);
+        }
+        let nonce = self.nonce();
+        self.buffer.clear();
+        self.buffer
+            .send_package(module, values)
+            .map_err(|_| CryptoError::Overflow)?;
+        let tag = self
+            .cipher
+            .encrypt(&nonce, self.buffer.as_mut_slice())
+            .map_err(CryptoError::Cipher)?;
+        self.counter += 1;
+
+        let len = self.buffer.len() as u32;
+        self.tx
+            .bwrite_iter(
+                MARKER
+                    .iter()
+                    .chain(nonce.iter())
+                    .chain(len.to_le_bytes().iter())
+                    .chain(self.buffer.iter())
+                    .chain(tag.iter())
+                    .cloned(),
+            )
+            .map_err(CryptoError::Transport)?;
+        self.tx.bflush().map_err(CryptoError::Transport)?;
+        self.tx.bend_frame().map_err(CryptoError::Transport)
+    }
+}
+
+/// Errors of frame decryption
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OpenError<C> {
+    /// More bytes are needed to decrypt frame
+    Incomplete,
+    /// Frame does not start with `=enc=`
+    InvalidMarker,
+    /// Cipher error, e.g. authentication failure
+    Cipher(C),
+}
+
+/// Decrypt frame at the start of `frame` in place.
+///
+/// Returns plain package bytes and size of the whole frame
+pub fn open<'a, C: Aead>(
+    cipher: &mut C,
+    frame: &'a mut [u8],
+) -> Result<(&'a [u8], usize), OpenError<C::Error>> {
+    if frame.len() < HEADER_SIZE {
+        return Err(OpenError::Incomplete);
+    }
+    if &frame[..MARKER.len()] != MARKER {
+        return Err(OpenError::InvalidMarker);
+    }
+    let mut nonce = [0; NONCE_SIZE];
+    nonce.copy_from_slice(&frame[MARKER.len()..MARKER.len() + NONCE_SIZE]);
+    let mut len = [0; 4];
+    len.copy_from_slice(&frame[HEADER_SIZE - 4..HEADER_SIZE]);
+    let len = u32::from_le_bytes(len) as usize;
+    let size = HEADER_SIZE + len + TAG_SIZE;
+    if frame.len() < size {
+        return Err(OpenError::Incomplete);
+    }
+    let (body, tag) = frame[HEADER_SIZE..size].split_at_mut(len);
+    let mut t = [0; TAG_SIZE];
+    t.copy_from_slice(tag);
+    cipher
+        .decrypt(&nonce, body, &t)
+        .map_err(OpenError::Cipher)?;
+    Ok((&frame[HEADER_SIZE..HEADER_SIZE + len], size))
+}
+
+#[cfg(feature = "builtin-cipher")]
+mod builtin;
+#[cfg(feature = "builtin-cipher")]
+pub use builtin::{ChaCha20Poly1305, TagMismatch};
+
+/// ChaCha20-Poly1305 of RustCrypto
+#[cfg(feature = "chacha20poly1305")]
+impl Aead for chacha20poly1305::ChaCha20Poly1305 {
+    type Error = chacha20poly1305::aead::Error;
+
+    fn encrypt(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        buffer: &mut [u8],
+    ) -> Result<[u8; TAG_SIZE], Self::Error> {
+        use chacha20poly1305::aead::AeadInPlace;
+        let tag = self.encrypt_in_place_detached(
+            chacha20poly1305::Nonce::from_slice(nonce),
+            &[],
+            buffer,
+        )?;
+        let mut out = [0; TAG_SIZE];
+        out.copy_from_slice(&tag);
+        Ok(out)
+    }
+
+    fn decrypt(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        buffer: &mut [u8],
+        tag: &[u8; TAG_SIZE],
+    ) -> Result<(), Self::Error> {
+        use chacha20poly1305::aead::AeadInPlace;
+        self.decrypt_in_place_detached(
+            chacha20poly1305::Nonce::from_slice(nonce),
+            &[],
+            buffer,
+            chacha20poly1305::Tag::from_slice(tag),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "chacha20poly1305"))]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use chacha20poly1305::aead::KeyInit;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    fn cipher() -> chacha20poly1305::ChaCha20Poly1305 {
+        chacha20poly1305::ChaCha20Poly1305::new(&[0x42; 32].into())
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut map = SVMap::<1, 2>::new();
+        map.set(&A, 7).unwrap();
+        let mut plain = Buffer::<512>::new();
+        plain.send_package(&MODULE, &map).unwrap();
+
+        let mut tx = Encrypted::<_, _, 512>::new(Buffer::<1024>::new(), cipher(), [1; 4]);
+        tx.send_package(&MODULE, &map).unwrap();
+        tx.send_package(&MODULE, &map).unwrap();
+        let (mut frames, _) = tx.free();
+        let (package, size) = open(&mut cipher(), &mut frames).unwrap();
+        assert_eq!(package, &*plain);
+        // Second frame has other nonce, so other ciphertext
+        assert_eq!(frames.len(), 2 * size);
+        assert_ne!(frames[..size], frames[size..]);
+
+        frames[size + HEADER_SIZE] ^= 1;
+        assert!(matches!(
+            open(&mut cipher(), &mut frames[size..]),
+            Err(OpenError::Cipher(_))
+        ));
+        assert_eq!(
+            open(&mut cipher(), &mut frames[..HEADER_SIZE - 1]),
+            Err(OpenError::Incomplete)
+        );
+    }
+
+    #[cfg(feature = "builtin-cipher")]
+    #[test]
+    fn same_as_builtin() {
+        let nonce = [3; NONCE_SIZE];
+        let (mut a, mut b) = ([0x5a; 100], [0x5a; 100]);
+        let tag = cipher().encrypt(&nonce, &mut a).unwrap();
+        let builtin_tag = ChaCha20Poly1305::new(&[0x42; 32])
+            .encrypt(&nonce, &mut b)
+            .unwrap();
+        assert_eq!((a, tag), (b, builtin_tag));
+    }
+}
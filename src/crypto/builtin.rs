@@ -0,0 +1,365 @@
+use super::{Aead, NONCE_SIZE, TAG_SIZE};
+
+/// Authentication tag does not match
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TagMismatch;
+
+/// ChaCha20-Poly1305 (RFC 8439) without associated data, implemented without dependencies
+#[derive(Clone)]
+pub struct ChaCha20Poly1305 {
+    key: [u32; 8],
+}
+
+impl ChaCha20Poly1305 {
+    /// Cipher with 256-bit key
+    pub fn new(key: &[u8; 32]) -> Self {
+        let mut k = [0; 8];
+        for (w, b) in k.iter_mut().zip(key.chunks_exact(4)) {
+            *w = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        }
+        Self { key: k }
+    }
+
+    /// Key stream block at `counter`
+    fn block(&self, counter: u32, nonce: &[u8; NONCE_SIZE]) -> [u8; 64] {
+        let mut init = [0u32; 16];
+        init[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+        init[4..12].copy_from_slice(&self.key);
+        init[12] = counter;
+        for (w, b) in init[13..].iter_mut().zip(nonce.chunks_exact(4)) {
+            *w = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        }
+        let mut x = init;
+        for _ in 0..10 {
+            for &(a, b, c, d) in &[
+                (0, 4, 8, 12),
+                (1, 5, 9, 13),
+                (2, 6, 10, 14),
+                (3, 7, 11, 15),
+                (0, 5, 10, 15),
+                (1, 6, 11, 12),
+                (2, 7, 8, 13),
+                (3, 4, 9, 14),
+            ] {
+                quarter_round(&mut x, a, b, c, d);
+            }
+        }
+        let mut out = [0; 64];
+        for (i, chunk) in out.chunks_exact_mut(4).enumerate() {
+            chunk.copy_from_slice(&x[i].wrapping_add(init[i]).to_le_bytes());
+        }
+        out
+    }
+
+    /// XOR `buffer` with key stream starting from block 1
+    fn apply(&self, nonce: &[u8; NONCE_SIZE], buffer: &mut [u8]) {
+        for (i, chunk) in buffer.chunks_mut(64).enumerate() {
+            let stream = self.block(i as u32 + 1, nonce);
+            for (b, s) in chunk.iter_mut().zip(stream.iter()) {
+                *b ^= s;
+            }
+        }
+    }
+
+    /// Poly1305 tag of associated data and ciphertext with one-time key from block 0
+    fn tag(&self, nonce: &[u8; NONCE_SIZE], aad: &[u8], ciphertext: &[u8]) -> [u8; TAG_SIZE] {
+        let otk = self.block(0, nonce);
+        let mut mac = Poly1305::new(&otk[..32]);
+        for chunk in aad.chunks(16).chain(ciphertext.chunks(16)) {
+            let mut block = [0; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            mac.block(&block, FULL_BLOCK);
+        }
+        let mut lengths = [0; 16];
+        lengths[..8].copy_from_slice(&(aad.len() as u64).to_le_bytes());
+        lengths[8..].copy_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+        mac.block(&lengths, FULL_BLOCK);
+        mac.finish()
+    }
+}
+
+impl Aead for ChaCha20Poly1305 {
+    type Error = TagMismatch;
+
+    fn encrypt(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        buffer: &mut [u8],
+    ) -> Result<[u8; TAG_SIZE], Self::Error> {
+        self.apply(nonce, buffer);
+        Ok(self.tag(nonce, &[], buffer))
+    }
+
+    fn decrypt(
+        &mut self,
+        nonce: &[u8; NONCE_SIZE],
+        buffer: &mut [u8],
+        tag: &[u8; TAG_SIZE],
+    ) -> Result<(), Self::Error> {
+        let expected = self.tag(nonce, &[], buffer);
+        // Constant time comparison
+        if expected.iter().zip(tag).fold(0, |d, (a, b)| d | (a ^ b)) != 0 {
+            return Err(TagMismatch);
+        }
+        self.apply(nonce, buffer);
+        Ok(())
+    }
+}
+
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
+
+/// Poly1305 with 26-bit limbs
+struct Poly1305 {
+    r: [u32; 5],
+    h: [u32; 5],
+    pad: [u32; 4],
+}
+
+const MASK_26: u32 = 0x3ff_ffff;
+/// Bit 128 of block, set for all blocks except final partial one of raw Poly1305
+const FULL_BLOCK: u32 = 1 << 24;
+
+fn le32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+impl Poly1305 {
+    fn new(key: &[u8]) -> Self {
+        Self {
+            r: [
+                le32(&key[0..]) & 0x3ff_ffff,
+                (le32(&key[3..]) >> 2) & 0x3ff_ff03,
+                (le32(&key[6..]) >> 4) & 0x3ff_c0ff,
+                (le32(&key[9..]) >> 6) & 0x3f0_3fff,
+                (le32(&key[12..]) >> 8) & 0x00f_ffff,
+            ],
+            h: [0; 5],
+            pad: [
+                le32(&key[16..]),
+                le32(&key[20..]),
+                le32(&key[24..]),
+                le32(&key[28..]),
+            ],
+        }
+    }
+
+    fn block(&mut self, m: &[u8; 16], hibit: u32) {
+        let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+        let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+        let h = &mut self.h;
+        h[0] += le32(&m[0..]) & MASK_26;
+        h[1] += (le32(&m[3..]) >> 2) & MASK_26;
+        h[2] += (le32(&m[6..]) >> 4) & MASK_26;
+        h[3] += (le32(&m[9..]) >> 6) & MASK_26;
+        h[4] += (le32(&m[12..]) >> 8) | hibit;
+        let [h0, h1, h2, h3, h4] = h.map(u64::from);
+
+        let d0 = h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1;
+        let mut d1 = h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2;
+        let mut d2 = h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3;
+        let mut d3 = h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4;
+        let mut d4 = h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0;
+
+        d1 += d0 >> 26;
+        d2 += d1 >> 26;
+        d3 += d2 >> 26;
+        d4 += d3 >> 26;
+        let d0 = (d0 & MASK_26 as u64) + (d4 >> 26) * 5;
+        h[0] = d0 as u32 & MASK_26;
+        h[1] = (d1 as u32 & MASK_26) + (d0 >> 26) as u32;
+        h[2] = d2 as u32 & MASK_26;
+        h[3] = d3 as u32 & MASK_26;
+        h[4] = d4 as u32 & MASK_26;
+    }
+
+    fn finish(self) -> [u8; TAG_SIZE] {
+        let mut h = self.h;
+        for i in 1..5 {
+            h[i] += h[i - 1] >> 26;
+            h[i - 1] &= MASK_26;
+        }
+        h[0] += (h[4] >> 26) * 5;
+        h[4] &= MASK_26;
+        h[1] += h[0] >> 26;
+        h[0] &= MASK_26;
+
+        // h - p, selected if h >= p
+        let mut g = [0u32; 5];
+        let mut carry = 5;
+        for i in 0..5 {
+            g[i] = h[i].wrapping_add(carry);
+            carry = g[i] >> 26;
+            g[i] &= MASK_26;
+        }
+        let ge = carry.wrapping_neg();
+        for i in 0..5 {
+            h[i] = (h[i] & !ge) | (g[i] & ge);
+        }
+
+        let words = [
+            h[0] | (h[1] << 26),
+            (h[1] >> 6) | (h[2] << 20),
+            (h[2] >> 12) | (h[3] << 14),
+            (h[3] >> 18) | (h[4] << 8),
+        ];
+        let mut tag = [0; TAG_SIZE];
+        let mut f = 0u64;
+        for (i, chunk) in tag.chunks_exact_mut(4).enumerate() {
+            f = words[i] as u64 + self.pad[i] as u64 + (f >> 32);
+            chunk.copy_from_slice(&(f as u32).to_le_bytes());
+        }
+        tag
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bytes of hex string, whitespace is ignored
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        let mut digits = s
+            .bytes()
+            .filter(|b| !b.is_ascii_whitespace())
+            .map(|b| (b as char).to_digit(16).unwrap() as u8);
+        let mut out = [0; N];
+        for o in out.iter_mut() {
+            *o = digits.next().unwrap() << 4 | digits.next().unwrap();
+        }
+        assert!(digits.next().is_none());
+        out
+    }
+
+    /// Raw Poly1305 of message with partial final block
+    fn poly1305(key: &[u8; 32], msg: &[u8]) -> [u8; TAG_SIZE] {
+        let mut mac = Poly1305::new(key);
+        for chunk in msg.chunks(16) {
+            let mut block = [0; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            if chunk.len() == 16 {
+                mac.block(&block, FULL_BLOCK);
+            } else {
+                block[chunk.len()] = 1;
+                mac.block(&block, 0);
+            }
+        }
+        mac.finish()
+    }
+
+    fn key() -> [u8; 32] {
+        let mut key = [0; 32];
+        for (i, k) in key.iter_mut().enumerate() {
+            *k = i as u8;
+        }
+        key
+    }
+
+    const SUNSCREEN: &[u8; 114] = b"Ladies and Gentlemen of the class of '99: \
+        If I could offer you only one tip for the future, sunscreen would be it.";
+
+    #[test]
+    fn chacha20_block() {
+        // RFC 8439, 2.3.2
+        let cipher = ChaCha20Poly1305::new(&key());
+        let nonce = hex("000000090000004a00000000");
+        let expected: [u8; 64] = hex(
+            "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e
+             d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e",
+        );
+        assert_eq!(cipher.block(1, &nonce), expected);
+    }
+
+    #[test]
+    fn chacha20_encryption() {
+        // RFC 8439, 2.4.2
+        let cipher = ChaCha20Poly1305::new(&key());
+        let nonce = hex("000000000000004a00000000");
+        let expected: [u8; 114] = hex(
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b
+             f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8
+             07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736
+             5af90bbf74a35be6b40b8eedf2785e42874d",
+        );
+        let mut buffer = *SUNSCREEN;
+        cipher.apply(&nonce, &mut buffer);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn poly1305_tag() {
+        // RFC 8439, 2.5.2
+        let key = hex("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b");
+        let expected: [u8; TAG_SIZE] = hex("a8061dc1305136c6c22b8baf0c0127a9");
+        assert_eq!(
+            poly1305(&key, b"Cryptographic Forum Research Group"),
+            expected
+        );
+    }
+
+    #[test]
+    fn aead() {
+        // RFC 8439, 2.8.2
+        let mut key = [0; 32];
+        for (i, k) in key.iter_mut().enumerate() {
+            *k = 0x80 + i as u8;
+        }
+        let cipher = ChaCha20Poly1305::new(&key);
+        let nonce = hex("070000004041424344454647");
+        let aad: [u8; 12] = hex("50515253c0c1c2c3c4c5c6c7");
+        let expected: [u8; 114] = hex(
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6
+             3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36
+             92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc
+             3ff4def08e4b7a9de576d26586cec64b6116",
+        );
+        let expected_tag: [u8; TAG_SIZE] = hex("1ae10b594f09e26a7e902ecbd0600691");
+        let mut buffer = *SUNSCREEN;
+        cipher.apply(&nonce, &mut buffer);
+        assert_eq!(buffer, expected);
+        assert_eq!(cipher.tag(&nonce, &aad, &buffer), expected_tag);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut cipher = ChaCha20Poly1305::new(&key());
+        let nonce = [7; NONCE_SIZE];
+        let mut buffer = *SUNSCREEN;
+        let tag = cipher.encrypt(&nonce, &mut buffer).unwrap();
+        assert_ne!(&buffer, SUNSCREEN);
+        cipher.decrypt(&nonce, &mut buffer, &tag).unwrap();
+        assert_eq!(&buffer, SUNSCREEN);
+    }
+
+    #[test]
+    fn tampered_tag() {
+        let mut cipher = ChaCha20Poly1305::new(&key());
+        let nonce = [7; NONCE_SIZE];
+        let mut buffer = *SUNSCREEN;
+        let mut tag = cipher.encrypt(&nonce, &mut buffer).unwrap();
+        let ciphertext = buffer;
+        tag[0] ^= 1;
+        assert_eq!(cipher.decrypt(&nonce, &mut buffer, &tag), Err(TagMismatch));
+        // Ciphertext is not decrypted on mismatch
+        assert_eq!(buffer, ciphertext);
+    }
+
+    #[test]
+    fn tampered_ciphertext() {
+        let mut cipher = ChaCha20Poly1305::new(&key());
+        let nonce = [7; NONCE_SIZE];
+        let mut buffer = *SUNSCREEN;
+        let tag = cipher.encrypt(&nonce, &mut buffer).unwrap();
+        buffer[100] ^= 0x80;
+        assert_eq!(cipher.decrypt(&nonce, &mut buffer, &tag), Err(TagMismatch));
+    }
+}
@@ -0,0 +1,115 @@
+use core::fmt::Write;
+
+use crate::{Name, SVMap, Sample, SendPackage};
+
+/// Encoder writing packages as CSV rows into text writer, e.g. file or terminal.
+///
+/// Header `position,name1,name2,...` is written before first package and
+/// each time number of signals changes, then one row per time position.
+/// Position counts time positions from first package, annotations are not written
+pub struct Csv<W> {
+    writer: W,
+    position: u32,
+    signals: Option<usize>,
+}
+
+impl<W: Write> Csv<W> {
+    /// Wrap writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            position: 0,
+            signals: None,
+        }
+    }
+
+    /// Release writer
+    pub fn free(self) -> W {
+        self.writer
+    }
+
+    /// Write header again before next package
+    pub fn reset_header(&mut self) {
+        self.signals = None;
+    }
+}
+
+/// Write field quoted if it contains separator or quote
+fn write_field<W: Write>(w: &mut W, field: &str) -> core::fmt::Result {
+    if !field.contains([',', '"', '\n']) {
+        return w.write_str(field);
+    }
+    w.write_char('"')?;
+    for c in field.chars() {
+        if c == '"' {
+            w.write_char('"')?;
+        }
+        w.write_char(c)?;
+    }
+    w.write_char('"')
+}
+
+impl<W: Write, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for Csv<W> {
+    type Error = core::fmt::Error;
+
+    fn send_package(&mut self, _module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        let w = &mut self.writer;
+        if self.signals != Some(values.map.len()) {
+            w.write_str("position")?;
+            for name in values.map.keys() {
+                w.write_char(',')?;
+                write_field(w, name)?;
+            }
+            w.write_char('\n')?;
+            self.signals = Some(values.map.len());
+        }
+        for slot in 0..P {
+            write!(w, "{}", self.position.wrapping_add(slot as u32))?;
+            for v in values.map.values() {
+                match Sample::new(v.vtype, v.vals[slot]) {
+                    Sample::Bool(b) => write!(w, ",{}", b as u8)?,
+                    Sample::Int(i) => write!(w, ",{}", i)?,
+                    Sample::Float(f) => write!(w, ",{}", f)?,
+                }
+            }
+            w.write_char('\n')?;
+        }
+        self.position = self.position.wrapping_add(P as u32);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use heapless::String;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+    const QUOTED: SVName = SVName::new("x,\"y\"");
+
+    #[test]
+    fn rows_and_headers() {
+        let mut map = SVMap::<3, 2>::new();
+        let mut csv = Csv::new(String::<256>::new());
+        for i in 0..4 {
+            map.set(&A, i).unwrap();
+            map.set(&B, i % 2 == 0).unwrap();
+            if i >= 2 {
+                map.set(&QUOTED, 0.5).unwrap();
+            }
+            map.next(|m| csv.send_package(&MODULE, m).unwrap());
+        }
+        assert_eq!(
+            csv.free().as_str(),
+            "position,a,b\n\
+             0,0,1\n\
+             1,1,0\n\
+             position,a,b,\"x,\"\"y\"\"\"\n\
+             2,2,1,0.5\n\
+             3,3,0,0.5\n"
+        );
+    }
+}
@@ -0,0 +1,450 @@
+use core::convert::TryFrom;
+
+use crate::protocol::{Protocol, SVisual};
+use crate::{Name, Sample, ValueType, NOTE_PREFIX};
+
+/// Errors of package decoding
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecodeError {
+    /// More bytes are needed to decode package
+    Incomplete,
+    /// Package does not start with `=begin=` or does not end with `=end=`
+    InvalidMarker,
+    /// Package size does not match with number of values per signal
+    InvalidSize,
+    /// Unknown signal type
+    InvalidType,
+}
+
+/// Borrowed view of one package. Nothing is copied, values are decoded on demand
+#[derive(Clone, Copy, Debug)]
+pub struct PackageView<'a> {
+    module: &'a [u8],
+    records: &'a [u8],
+    values: usize,
+    padding: u8,
+}
+
+impl<'a> PackageView<'a> {
+    /// Parse package at the start of `bytes` with `values` values per signal.
+    ///
+    /// Returns package view and the rest of bytes after the package
+    pub fn parse(bytes: &'a [u8], values: usize) -> Result<(Self, &'a [u8]), DecodeError> {
+        Self::parse_with::<SVisual>(bytes, values)
+    }
+
+    /// Parse package with markers and padding of protocol `C`
+    pub fn parse_with<C: Protocol>(
+        bytes: &'a [u8],
+        values: usize,
+    ) -> Result<(Self, &'a [u8]), DecodeError> {
+        let (begin, end_marker) = (C::BEGIN, C::END);
+        // Marker and package size
+        let header_size = begin.len() + 4;
+        if bytes.len() < header_size {
+            return Err(if begin.starts_with(bytes) || bytes.starts_with(begin) {
+                DecodeError::Incomplete
+            } else {
+                DecodeError::InvalidMarker
+            });
+        }
+        if &bytes[..begin.len()] != begin {
+            return Err(DecodeError::InvalidMarker);
+        }
+        let mut size = [0; 4];
+        size.copy_from_slice(&bytes[begin.len()..header_size]);
+        let full_size = u32::from_le_bytes(size) as usize;
+        if full_size < Name::MAX_SIZE || (full_size - Name::MAX_SIZE) % record_size(values) != 0 {
+            return Err(DecodeError::InvalidSize);
+        }
+        let end = header_size
+            .checked_add(full_size)
+            .ok_or(DecodeError::InvalidSize)?;
+        if bytes.len() - end.min(bytes.len()) < end_marker.len() {
+            return Err(DecodeError::Incomplete);
+        }
+        if &bytes[end..end + end_marker.len()] != end_marker {
+            return Err(DecodeError::InvalidMarker);
+        }
+        let body = &bytes[header_size..end];
+        let package = Self {
+            module: &body[..Name::MAX_SIZE],
+            records: &body[Name::MAX_SIZE..],
+            values,
+            padding: C::PADDING,
+        };
+        if package.records().any(|r| r.vtype().is_none()) {
+            return Err(DecodeError::InvalidType);
+        }
+        Ok((package, &bytes[end + end_marker.len()..]))
+    }
+
+    /// Recover complete signals from package truncated by end of capture (e.g. device reset).
+    ///
+    /// Number of signals is inferred from available bytes.
+    /// Complete package is parsed as by [`parse`](Self::parse)
+    pub fn recover(bytes: &'a [u8], values: usize) -> Result<Self, DecodeError> {
+        Self::recover_with::<SVisual>(bytes, values)
+    }
+
+    /// Recover truncated package with markers and padding of protocol `C`
+    pub fn recover_with<C: Protocol>(bytes: &'a [u8], values: usize) -> Result<Self, DecodeError> {
+        match Self::parse_with::<C>(bytes, values) {
+            Err(DecodeError::Incomplete) => {}
+            other => return other.map(|(package, _)| package),
+        }
+        let begin = C::BEGIN;
+        let header_size = begin.len() + 4;
+        if bytes.len() < header_size + Name::MAX_SIZE {
+            return Err(DecodeError::Incomplete);
+        }
+        let mut size = [0; 4];
+        size.copy_from_slice(&bytes[begin.len()..header_size]);
+        let full_size = u32::from_le_bytes(size) as usize;
+        let end = header_size
+            .checked_add(full_size)
+            .ok_or(DecodeError::InvalidSize)?;
+        let body = &bytes[header_size..bytes.len().min(end)];
+        let records = &body[Name::MAX_SIZE..];
+        let complete = records.len() - records.len() % record_size(values);
+        let package = Self {
+            module: &body[..Name::MAX_SIZE],
+            records: &records[..complete],
+            values,
+            padding: C::PADDING,
+        };
+        if package.records().any(|r| r.vtype().is_none()) {
+            return Err(DecodeError::InvalidType);
+        }
+        Ok(package)
+    }
+
+    /// Module name bytes without padding
+    pub fn module(&self) -> &'a [u8] {
+        trim_with(self.module, self.padding)
+    }
+
+    /// Module name, if it is valid UTF-8
+    pub fn module_str(&self) -> Option<&'a str> {
+        core::str::from_utf8(self.module()).ok()
+    }
+
+    /// Number of signals in package
+    pub fn len(&self) -> usize {
+        self.records.len() / record_size(self.values)
+    }
+
+    /// Checks if package has no signals
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Iterator over signals of package
+    pub fn records(&self) -> impl Iterator<Item = RecordView<'a>> + 'a {
+        let (values, padding) = (self.values, self.padding);
+        self.records
+            .chunks_exact(record_size(values))
+            .map(move |bytes| RecordView {
+                bytes,
+                values,
+                padding,
+            })
+    }
+}
+
+/// Borrowed view of one signal in package
+#[derive(Clone, Copy, Debug)]
+pub struct RecordView<'a> {
+    bytes: &'a [u8],
+    values: usize,
+    padding: u8,
+}
+
+impl<'a> RecordView<'a> {
+    /// Signal name bytes without padding
+    pub fn name(&self) -> &'a [u8] {
+        trim_with(&self.bytes[..Name::MAX_SIZE], self.padding)
+    }
+
+    /// Signal name, if it is valid UTF-8
+    pub fn name_str(&self) -> Option<&'a str> {
+        core::str::from_utf8(self.name()).ok()
+    }
+
+    /// Text of annotation if record is annotation instead of signal.
+    /// Annotation values show impulses at marked time positions
+    pub fn note(&self) -> Option<&'a [u8]> {
+        match self.name() {
+            [NOTE_PREFIX, text @ ..] => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Time positions marked by annotation, empty for ordinary signals.
+    ///
+    /// Position is index of value in package, so markers align with signal values exactly
+    pub fn marks(&self) -> impl Iterator<Item = usize> + 'a {
+        let note = self.note().is_some();
+        self.values()
+            .enumerate()
+            .filter(move |&(_, v)| note && v != 0)
+            .map(|(slot, _)| slot)
+    }
+
+    /// Signal type
+    pub fn vtype(&self) -> Option<ValueType> {
+        let vtype = read_i32(&self.bytes[Name::MAX_SIZE..]);
+        u8::try_from(vtype)
+            .ok()
+            .and_then(|t| ValueType::try_from(t).ok())
+    }
+
+    /// Raw little-endian bytes of values
+    pub fn raw(&self) -> &'a [u8] {
+        &self.bytes[Name::MAX_SIZE + 4..]
+    }
+
+    /// Number of values
+    pub fn len(&self) -> usize {
+        self.values
+    }
+
+    /// Checks if signal has no values
+    pub fn is_empty(&self) -> bool {
+        self.values == 0
+    }
+
+    /// Value at specified time position in `i32` representation
+    pub fn get(&self, idx: usize) -> Option<i32> {
+        if idx < self.values {
+            Some(read_i32(&self.raw()[idx * 4..]))
+        } else {
+            None
+        }
+    }
+
+    /// Decoded value at specified time position
+    pub fn sample(&self, idx: usize) -> Option<Sample> {
+        Some(Sample::new(self.vtype()?, self.get(idx)?))
+    }
+
+    /// Iterator over values in `i32` representation
+    pub fn values(&self) -> impl Iterator<Item = i32> + 'a {
+        self.raw().chunks_exact(4).map(read_i32)
+    }
+}
+
+/// Size of one signal record with `values` values
+pub const fn record_size(values: usize) -> usize {
+    Name::MAX_SIZE + 4 + values * 4
+}
+
+fn read_i32(bytes: &[u8]) -> i32 {
+    let mut val = [0; 4];
+    val.copy_from_slice(&bytes[..4]);
+    i32::from_le_bytes(val)
+}
+
+pub(crate) fn trim(name: &[u8]) -> &[u8] {
+    trim_with(name, SVisual::PADDING)
+}
+
+fn trim_with(name: &[u8], padding: u8) -> &[u8] {
+    let len = name
+        .iter()
+        .position(|&b| b == padding)
+        .unwrap_or(name.len());
+    &name[..len]
+}
+
+/// Owned decoded package
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Package {
+    /// Module name
+    pub module: alloc::string::String,
+    /// Signals of package
+    pub signals: alloc::vec::Vec<Signal>,
+}
+
+/// Owned decoded signal
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signal {
+    /// Signal name
+    pub name: alloc::string::String,
+    /// Signal type
+    pub vtype: ValueType,
+    /// Values in time order
+    pub samples: alloc::vec::Vec<Sample>,
+}
+
+#[cfg(feature = "alloc")]
+impl PackageView<'_> {
+    /// Copy package into owned form. Names which are not UTF-8 are converted lossy
+    pub fn to_package(&self) -> Package {
+        Package {
+            module: alloc::string::String::from_utf8_lossy(self.module()).into_owned(),
+            signals: self
+                .records()
+                .filter_map(|r| {
+                    let vtype = r.vtype()?;
+                    Some(Signal {
+                        name: alloc::string::String::from_utf8_lossy(r.name()).into_owned(),
+                        vtype,
+                        samples: r.values().map(|v| Sample::new(vtype, v)).collect(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Decode all complete packages from the start of `bytes` with `values` values per signal.
+///
+/// Returns packages and the rest of bytes starting with incomplete package
+#[cfg(feature = "alloc")]
+pub fn decode_all(
+    mut bytes: &[u8],
+    values: usize,
+) -> Result<(alloc::vec::Vec<Package>, &[u8]), DecodeError> {
+    let mut packages = alloc::vec::Vec::new();
+    while !bytes.is_empty() {
+        match PackageView::parse(bytes, values) {
+            Ok((package, rest)) => {
+                packages.push(package.to_package());
+                bytes = rest;
+            }
+            Err(DecodeError::Incomplete) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok((packages, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const INT: SVName = SVName::new("int");
+    const FLOAT: SVName = SVName::new("float");
+
+    fn package() -> Buffer<512> {
+        let mut map = SVMap::<2, 4>::new();
+        let mut buf = Buffer::new();
+        for i in 0..4 {
+            map.set(&INT, i * 10).unwrap();
+            map.set(&FLOAT, i as f32 / 2.).unwrap();
+            map.next(|m| buf.send_package(&MODULE, m).unwrap());
+        }
+        buf
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut bytes = package();
+        let extra = [1, 2, 3];
+        bytes.extend_from_slice(&extra).unwrap();
+        let (package, rest) = PackageView::parse(&bytes, 4).unwrap();
+        assert_eq!(rest, extra);
+        assert_eq!(package.module_str(), Some("module"));
+        assert_eq!(package.len(), 2);
+        let int = package.records().find(|r| r.name() == b"int").unwrap();
+        assert_eq!(int.vtype(), Some(ValueType::Int));
+        assert!(int.values().eq([0, 10, 20, 30]));
+        let float = package.records().find(|r| r.name() == b"float").unwrap();
+        assert_eq!(float.vtype(), Some(ValueType::Float));
+        assert_eq!(float.sample(3), Some(Sample::Float(1.5)));
+        assert_eq!(float.get(4), None);
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = package();
+        for len in [0, 3, SVisual::BEGIN.len() + 2, bytes.len() - 1] {
+            assert_eq!(
+                PackageView::parse(&bytes[..len], 4).err(),
+                Some(DecodeError::Incomplete)
+            );
+        }
+    }
+
+    #[test]
+    fn corrupted() {
+        let mut bytes = package();
+        bytes[1] = b'x';
+        assert_eq!(
+            PackageView::parse(&bytes, 4).err(),
+            Some(DecodeError::InvalidMarker)
+        );
+
+        let mut bytes = package();
+        let last = bytes.len() - 1;
+        bytes[last] = b'x';
+        assert_eq!(
+            PackageView::parse(&bytes, 4).err(),
+            Some(DecodeError::InvalidMarker)
+        );
+
+        let bytes = package();
+        assert_eq!(
+            PackageView::parse(&bytes, 3).err(),
+            Some(DecodeError::InvalidSize)
+        );
+
+        let mut bytes = package();
+        let vtype = SVisual::BEGIN.len() + 4 + Name::MAX_SIZE + Name::MAX_SIZE;
+        bytes[vtype] = 0x7f;
+        assert_eq!(
+            PackageView::parse(&bytes, 4).err(),
+            Some(DecodeError::InvalidType)
+        );
+
+        let mut bytes = package();
+        let size = SVisual::BEGIN.len();
+        bytes[size..size + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(PackageView::parse(&bytes, 4).is_err());
+    }
+
+    #[test]
+    fn recover() {
+        let bytes = package();
+        let full = PackageView::recover(&bytes, 4).unwrap();
+        assert_eq!(full.len(), 2);
+        // Second record is cut in the middle
+        let cut = bytes.len() - SVisual::END.len() - 5;
+        let package = PackageView::recover(&bytes[..cut], 4).unwrap();
+        assert_eq!(package.module(), b"module");
+        assert_eq!(package.len(), 1);
+        assert_eq!(
+            PackageView::recover(&bytes[..SVisual::BEGIN.len() + 4], 4).err(),
+            Some(DecodeError::Incomplete)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn decode_all() {
+        let mut bytes = package();
+        bytes.extend_from_slice(&package()).unwrap();
+        let cut = bytes.len() - 3;
+        let (packages, rest) = super::decode_all(&bytes[..cut], 4).unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(rest.len(), package().len() - 3);
+        let package = &packages[0];
+        assert_eq!(package.module, "module");
+        let int = package.signals.iter().find(|s| s.name == "int").unwrap();
+        assert_eq!(int.vtype, ValueType::Int);
+        assert_eq!(int.samples[2], Sample::Int(20));
+
+        bytes[1] = b'x';
+        assert_eq!(
+            super::decode_all(&bytes, 4).err(),
+            Some(DecodeError::InvalidMarker)
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use crate::gorilla::{put, DecompressError};
+use crate::protocol::{padded, write_header, write_trailer, Protocol, SVisual};
+use crate::{Name, SVMap, SendPackage, ValueType, WriteIter, NAME_SIZE};
+
+/// Marker of delta encoded package
+pub const MARKER: &[u8] = b"=delta=";
+
+/// Transport sending first value of each signal and differences
+/// between neighbour values in variable-length form.
+///
+/// Zigzag encoded difference takes 1 byte for changes up to ±63,
+/// so slowly changing signals shrink up to 4 times.
+/// `Float` signals are encoded as differences of their bit representation.
+/// Receiver restores ordinary package with [`decode`]
+pub struct DeltaEncoded<Tx> {
+    tx: Tx,
+}
+
+impl<Tx> DeltaEncoded<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self { tx }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+/// Encode values: first value, then differences with previous one,
+/// each zigzag encoded as LEB128 varint. Returns number of written bytes
+fn encode<E>(vals: &[i32], mut out: impl FnMut(u8) -> Result<(), E>) -> Result<usize, E> {
+    let mut written = 0;
+    let mut prev = 0i32;
+    for &v in vals {
+        let delta = v.wrapping_sub(prev);
+        prev = v;
+        let mut zigzag = ((delta << 1) ^ (delta >> 31)) as u32;
+        loop {
+            let byte = (zigzag & 0x7F) as u8;
+            zigzag >>= 7;
+            written += 1;
+            if zigzag == 0 {
+                out(byte)?;
+                break;
+            }
+            out(byte | 0x80)?;
+        }
+    }
+    Ok(written)
+}
+
+/// Size of encoded values in bytes
+fn encoded_size(vals: &[i32]) -> usize {
+    encode::<()>(vals, |_| Ok(())).unwrap_or(0)
+}
+
+/// Decode `count` values from `bytes` writing them in little-endian order to `out`
+fn expand(bytes: &[u8], count: usize, out: &mut [u8]) -> Option<()> {
+    let mut bytes = bytes.iter();
+    let mut prev = 0i32;
+    for chunk in out.chunks_exact_mut(4).take(count) {
+        let mut zigzag = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = *bytes.next()?;
+            zigzag |= ((byte & 0x7F) as u32).checked_shl(shift)?;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        let delta = (zigzag >> 1) as i32 ^ -((zigzag & 1) as i32);
+        prev = prev.wrapping_add(delta);
+        chunk.copy_from_slice(&prev.to_le_bytes());
+    }
+    Some(())
+}
+
+impl<Tx, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for DeltaEncoded<Tx>
+where
+    Tx: WriteIter,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        // Annotation impulses take 1 byte per value
+        let notes = values.unique_notes().count();
+        let size = NAME_SIZE
+            + values
+                .map
+                .iter()
+                .map(|(_, v)| NAME_SIZE + 4 + 2 + encoded_size(&v.vals))
+                .sum::<usize>()
+            + notes * (NAME_SIZE + 4 + 2 + P);
+
+        let tx = &mut self.tx;
+        write_header::<SVisual, _>(tx, MARKER, size, module)?;
+        for (&name, v) in values.map.iter() {
+            let len = encoded_size(&v.vals) as u16;
+            tx.bwrite_iter(
+                padded::<SVisual, _>(name.bytes())
+                    .chain((v.vtype as i32).to_le_bytes())
+                    .chain(len.to_le_bytes()),
+            )?;
+            encode(&v.vals, |b| tx.bwrite_iter(core::iter::once(b)))?;
+            tx.bflush()?;
+        }
+        for note in values.unique_notes() {
+            let mut marks = [0; P];
+            for (slot, m) in marks.iter_mut().enumerate() {
+                *m = values.is_noted(&note.text, slot) as i32;
+            }
+            let len = encoded_size(&marks) as u16;
+            tx.bwrite_iter(
+                padded::<SVisual, _>(note.name())
+                    .chain((ValueType::Bool as i32).to_le_bytes())
+                    .chain(len.to_le_bytes()),
+            )?;
+            encode(&marks, |b| tx.bwrite_iter(core::iter::once(b)))?;
+            tx.bflush()?;
+        }
+        write_trailer::<SVisual, _>(tx)
+    }
+}
+
+/// Restore ordinary package with `values` values per signal from delta encoded `frame` into `out`.
+///
+/// Returns sizes of consumed frame and restored package
+pub fn decode(
+    frame: &[u8],
+    values: usize,
+    out: &mut [u8],
+) -> Result<(usize, usize), DecompressError> {
+    use DecompressError::*;
+    let head = MARKER.len() + 4;
+    if frame.len() < head {
+        return Err(Incomplete);
+    }
+    if !frame.starts_with(MARKER) {
+        return Err(InvalidMarker);
+    }
+    let s = &frame[MARKER.len()..head];
+    let size = u32::from_le_bytes([s[0], s[1], s[2], s[3]]) as usize;
+    let end = head + size;
+    let body = frame.get(head..end).ok_or(Incomplete)?;
+    if frame.get(end..end + SVisual::END.len()).ok_or(Incomplete)? != SVisual::END {
+        return Err(InvalidMarker);
+    }
+    let module = body.get(..NAME_SIZE).ok_or(Corrupted)?;
+
+    let record = NAME_SIZE + 4 + values * 4;
+    let mut pos = put(out, 0, SVisual::BEGIN)?;
+    pos = put(out, pos, &[0; 4])?;
+    pos = put(out, pos, module)?;
+
+    let mut rest = &body[NAME_SIZE..];
+    let mut records = 0;
+    while !rest.is_empty() {
+        let header = rest.get(..NAME_SIZE + 4).ok_or(Corrupted)?;
+        if !matches!(header[NAME_SIZE..], [0..=2, 0, 0, 0]) {
+            return Err(Corrupted);
+        }
+        pos = put(out, pos, header)?;
+        let len = rest.get(NAME_SIZE + 4..NAME_SIZE + 6).ok_or(Corrupted)?;
+        let len = u16::from_le_bytes([len[0], len[1]]) as usize;
+        rest = &rest[NAME_SIZE + 6..];
+        let bytes = rest.get(..len).ok_or(Corrupted)?;
+        let vals = out.get_mut(pos..pos + values * 4).ok_or(Overflow)?;
+        expand(bytes, values, vals).ok_or(Corrupted)?;
+        rest = &rest[len..];
+        pos += values * 4;
+        records += 1;
+    }
+    let full_size = (NAME_SIZE + records * record) as u32;
+    let size_at = SVisual::BEGIN.len();
+    out[size_at..size_at + 4].copy_from_slice(&full_size.to_le_bytes());
+    pos = put(out, pos, SVisual::END)?;
+    Ok((end + SVisual::END.len(), pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const INT: SVName = SVName::new("int");
+    const FLOAT: SVName = SVName::new("float");
+
+    fn map() -> SVMap<2, 8> {
+        let mut map = SVMap::new();
+        for i in 0..8 {
+            map.set(&INT, [0, 1, -1, 100, -100, i32::MAX, i32::MIN, 0][i])
+                .unwrap();
+            map.set(&FLOAT, i as f32 * 0.25).unwrap();
+            if i < 7 {
+                map.next(|_| {});
+            }
+        }
+        map
+    }
+
+    fn frames() -> (Buffer<512>, Buffer<512>) {
+        let map = map();
+        let mut plain = Buffer::new();
+        plain.send_package(&MODULE, &map).unwrap();
+        let mut delta = DeltaEncoded::new(Buffer::new());
+        delta.send_package(&MODULE, &map).unwrap();
+        (plain, delta.free())
+    }
+
+    #[test]
+    fn varint_round_trip() {
+        let vals = [0, 63, -64, 64, -65, i32::MAX, i32::MIN, 1, 1];
+        let mut bytes = Buffer::<64>::new();
+        let written = encode::<()>(&vals, |b| bytes.push(b).map_err(drop)).unwrap();
+        assert_eq!(written, bytes.len());
+        assert_eq!(encoded_size(&vals), written);
+        let mut out = [0; 36];
+        expand(&bytes, vals.len(), &mut out).unwrap();
+        let restored = out
+            .chunks_exact(4)
+            .map(|c| i32::from_le_bytes([c[0], c[1], c[2], c[3]]));
+        assert!(restored.eq(vals.iter().cloned()));
+        assert_eq!(expand(&bytes[..written - 1], vals.len(), &mut out), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let (plain, delta) = frames();
+        assert!(delta.len() < plain.len());
+        let mut out = [0; 512];
+        let (consumed, len) = decode(&delta, 8, &mut out).unwrap();
+        assert_eq!(consumed, delta.len());
+        assert_eq!(&out[..len], &plain[..]);
+    }
+
+    #[test]
+    fn truncated() {
+        let (_, delta) = frames();
+        for len in [0, MARKER.len() + 2, MARKER.len() + 6, delta.len() - 1] {
+            assert_eq!(
+                decode(&delta[..len], 8, &mut [0; 512]),
+                Err(DecompressError::Incomplete)
+            );
+        }
+    }
+
+    #[test]
+    fn corrupted() {
+        let (_, mut delta) = frames();
+        delta[0] = b'x';
+        assert_eq!(
+            decode(&delta, 8, &mut [0; 512]),
+            Err(DecompressError::InvalidMarker)
+        );
+
+        // Length of values of the first signal points past package end
+        let (_, mut delta) = frames();
+        let len = MARKER.len() + 4 + NAME_SIZE + NAME_SIZE + 4;
+        delta[len..len + 2].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert_eq!(
+            decode(&delta, 8, &mut [0; 512]),
+            Err(DecompressError::Corrupted)
+        );
+
+        // Unknown signal type
+        let (_, mut delta) = frames();
+        delta[len - 4] = 0x7f;
+        assert_eq!(
+            decode(&delta, 8, &mut [0; 512]),
+            Err(DecompressError::Corrupted)
+        );
+
+        let (_, delta) = frames();
+        assert_eq!(
+            decode(&delta, 8, &mut [0; 64]),
+            Err(DecompressError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use heapless::Vec;
+
+use crate::buffer::Buffer;
+use crate::decoder::{DecodeError, PackageView};
+use crate::Name;
+
+/// Receiver of raw packages, e.g. file, TCP forward or callback
+pub trait PackageSink {
+    /// Error type
+    type Error;
+    /// Accept whole package. `WouldBlock` keeps package pending for next `poll`
+    fn accept(&mut self, package: &[u8]) -> nb::Result<(), Self::Error>;
+}
+
+/// Sink of one module with own pending package
+struct Route<S, const B: usize> {
+    module: Name,
+    sink: S,
+    pending: Buffer<B>,
+    stats: RouteStats,
+}
+
+/// Statistics of one route
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RouteStats {
+    /// Delivered packages
+    pub delivered: u32,
+    /// Packages dropped because sink did not accept previous one yet
+    pub dropped: u32,
+    /// Sink errors
+    pub errors: u32,
+}
+
+/// Result of routing package
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Routed {
+    /// Package is delivered to route with index
+    Delivered(usize),
+    /// Sink is busy, package is kept pending
+    Pending(usize),
+    /// Previous package is still pending, new one is dropped
+    Dropped(usize),
+    /// Sink returned error
+    Failed(usize),
+    /// No route for module
+    Unknown,
+}
+
+/// Demultiplexer of packages from many modules on one bus into separate sinks.
+///
+/// Each sink has own pending package of maximum size `B`,
+/// so slow sink does not block others. `K` is maximum number of routes
+pub struct Demux<S, const K: usize, const B: usize> {
+    routes: Vec<Route<S, B>, K>,
+    values: usize,
+}
+
+impl<S: PackageSink, const K: usize, const B: usize> Demux<S, K, B> {
+    /// Create demultiplexer of packages with `values` values per signal
+    pub const fn new(values: usize) -> Self {
+        Self {
+            routes: Vec::new(),
+            values,
+        }
+    }
+
+    /// Route packages of `module` to `sink`. Returns sink back if there is no space
+    pub fn add_route(&mut self, module: Name, sink: S) -> Result<usize, S> {
+        self.routes
+            .push(Route {
+                module,
+                sink,
+                pending: Buffer::new(),
+                stats: RouteStats::default(),
+            })
+            .map_err(|r| r.sink)?;
+        Ok(self.routes.len() - 1)
+    }
+
+    /// Sink of route
+    pub fn sink(&mut self, route: usize) -> Option<&mut S> {
+        self.routes.get_mut(route).map(|r| &mut r.sink)
+    }
+
+    /// Statistics of route
+    pub fn stats(&self, route: usize) -> Option<RouteStats> {
+        self.routes.get(route).map(|r| r.stats)
+    }
+
+    /// Deliver one complete package to sink of its module
+    pub fn route(&mut self, package: &[u8]) -> Result<Routed, DecodeError> {
+        let (view, _) = PackageView::parse(package, self.values)?;
+        let idx = match self
+            .routes
+            .iter()
+            .position(|r| r.module.as_bytes() == view.module())
+        {
+            Some(idx) => idx,
+            None => return Ok(Routed::Unknown),
+        };
+        let route = &mut self.routes[idx];
+        if !route.pending.is_empty() {
+            route.stats.dropped = route.stats.dropped.wrapping_add(1);
+            return Ok(Routed::Dropped(idx));
+        }
+        Ok(match route.sink.accept(package) {
+            Ok(()) => {
+                route.stats.delivered = route.stats.delivered.wrapping_add(1);
+                Routed::Delivered(idx)
+            }
+            Err(nb::Error::WouldBlock) => {
+                if route.pending.extend_from_slice(package).is_ok() {
+                    Routed::Pending(idx)
+                } else {
+                    route.stats.dropped = route.stats.dropped.wrapping_add(1);
+                    Routed::Dropped(idx)
+                }
+            }
+            Err(nb::Error::Other(_)) => {
+                route.stats.errors = route.stats.errors.wrapping_add(1);
+                Routed::Failed(idx)
+            }
+        })
+    }
+
+    /// Retry delivery of pending packages. Returns number of still pending packages
+    pub fn poll(&mut self) -> usize {
+        let mut pending = 0;
+        for route in self.routes.iter_mut().filter(|r| !r.pending.is_empty()) {
+            match route.sink.accept(route.pending.as_slice()) {
+                Ok(()) => {
+                    route.stats.delivered = route.stats.delivered.wrapping_add(1);
+                    route.pending.clear();
+                }
+                Err(nb::Error::WouldBlock) => pending += 1,
+                Err(nb::Error::Other(_)) => {
+                    route.stats.errors = route.stats.errors.wrapping_add(1);
+                    route.pending.clear();
+                }
+            }
+        }
+        pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const LEFT: SVName = SVName::new("left");
+    const RIGHT: SVName = SVName::new("right");
+    const OTHER: SVName = SVName::new("other");
+    const A: SVName = SVName::new("a");
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Sink {
+        busy: bool,
+        fail: bool,
+        received: usize,
+    }
+
+    impl PackageSink for Sink {
+        type Error = ();
+        fn accept(&mut self, _package: &[u8]) -> nb::Result<(), ()> {
+            if self.fail {
+                return Err(nb::Error::Other(()));
+            }
+            if self.busy {
+                return Err(nb::Error::WouldBlock);
+            }
+            self.received += 1;
+            Ok(())
+        }
+    }
+
+    fn package(module: &Name) -> Buffer<512> {
+        let mut map = SVMap::<1, 2>::new();
+        let mut buf = Buffer::new();
+        map.set(&A, 1).unwrap();
+        map.next(|_| {});
+        map.next(|m| buf.send_package(module, m).unwrap());
+        buf
+    }
+
+    #[test]
+    fn routes_by_module() {
+        let mut demux = Demux::<Sink, 2, 512>::new(2);
+        assert_eq!(demux.add_route(LEFT, Sink::default()), Ok(0));
+        let busy = Sink {
+            busy: true,
+            ..Sink::default()
+        };
+        assert_eq!(demux.add_route(RIGHT, busy), Ok(1));
+        assert!(demux.add_route(OTHER, Sink::default()).is_err());
+
+        let (left, right) = (package(&LEFT), package(&RIGHT));
+        assert_eq!(demux.route(&left), Ok(Routed::Delivered(0)));
+        assert_eq!(demux.route(&package(&OTHER)), Ok(Routed::Unknown));
+        assert_eq!(demux.route(&left[..8]), Err(DecodeError::Incomplete));
+
+        // Busy sink keeps one package pending and does not block other sink
+        assert_eq!(demux.route(&right), Ok(Routed::Pending(1)));
+        assert_eq!(demux.route(&right), Ok(Routed::Dropped(1)));
+        assert_eq!(demux.route(&left), Ok(Routed::Delivered(0)));
+        assert_eq!(demux.poll(), 1);
+        demux.sink(1).unwrap().busy = false;
+        assert_eq!(demux.poll(), 0);
+        assert_eq!(demux.sink(1).unwrap().received, 1);
+
+        demux.sink(1).unwrap().fail = true;
+        assert_eq!(demux.route(&right), Ok(Routed::Failed(1)));
+        assert_eq!(
+            demux.stats(0),
+            Some(RouteStats {
+                delivered: 2,
+                dropped: 0,
+                errors: 0,
+            })
+        );
+        assert_eq!(
+            demux.stats(1),
+            Some(RouteStats {
+                delivered: 1,
+                dropped: 1,
+                errors: 1,
+            })
+        );
+        assert_eq!(demux.stats(2), None);
+    }
+}
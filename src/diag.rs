@@ -0,0 +1,170 @@
+use crate::seq::DROPPED_NAME;
+use crate::{write_package, AddError, Name, SVMap, SendPackage, ValueType, WriteIter};
+
+/// Name of record with number of transport errors
+pub const ERRORS_NAME: Name = Name::new("@errors");
+
+/// Name of record with number of failed updates because of full map
+pub const OVERFLOW_NAME: Name = Name::new("@overflow");
+
+/// Name of record with number of missed time positions
+pub const OVERRUN_NAME: Name = Name::new("@overrun");
+
+/// Counters of link health
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Counters {
+    /// Packages that did not reach transport
+    pub dropped: u32,
+    /// Transport errors
+    pub errors: u32,
+    /// Updates failed with `MapOverflow` or `NotesOverflow`
+    pub overflows: u32,
+    /// Time positions missed by application loop
+    pub overruns: u32,
+}
+
+/// Transport appending diagnostic signals to each package.
+///
+/// Counters are sent as `Int` records [`DROPPED_NAME`], [`ERRORS_NAME`],
+/// [`OVERFLOW_NAME`] and [`OVERRUN_NAME`], so link health is plotted with other signals.
+/// Transport errors are counted automatically, other events are reported by application
+pub struct Diagnosed<Tx> {
+    tx: Tx,
+    counters: Counters,
+}
+
+impl<Tx> Diagnosed<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self {
+            tx,
+            counters: Counters::default(),
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+
+    /// Current counters
+    pub fn counters(&self) -> Counters {
+        self.counters
+    }
+
+    /// Count package dropped before transport, e.g. by full queue
+    pub fn dropped(&mut self) {
+        self.counters.dropped = self.counters.dropped.wrapping_add(1);
+    }
+
+    /// Count missed time positions, e.g. timer expired several times before poll
+    pub fn overrun(&mut self, positions: u32) {
+        self.counters.overruns = self.counters.overruns.wrapping_add(positions);
+    }
+
+    /// Count overflow of update result, e.g. `diag.check(map.set(&NAME, value))`
+    pub fn check<T>(&mut self, result: Result<T, AddError>) -> Result<T, AddError> {
+        if let Err(AddError::MapOverflow | AddError::NotesOverflow) = result {
+            self.counters.overflows = self.counters.overflows.wrapping_add(1);
+        }
+        result
+    }
+}
+
+impl<Tx, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for Diagnosed<Tx>
+where
+    Tx: WriteIter,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        let c = self.counters;
+        let counters = [
+            [c.dropped as i32; P],
+            [c.errors as i32; P],
+            [c.overflows as i32; P],
+            [c.overruns as i32; P],
+        ];
+        let names = [
+            &*DROPPED_NAME,
+            &*ERRORS_NAME,
+            &*OVERFLOW_NAME,
+            &*OVERRUN_NAME,
+        ];
+        write_package(
+            &mut self.tx,
+            module,
+            P,
+            values
+                .map
+                .iter()
+                .map(|(&name, v)| (name, v.vtype, &v.vals[..]))
+                .chain(
+                    names
+                        .iter()
+                        .zip(counters.iter())
+                        .map(|(&name, vals)| (name, ValueType::Int, &vals[..])),
+                ),
+            &values.notes,
+        )
+        .map_err(|e| {
+            self.counters.errors = self.counters.errors.wrapping_add(1);
+            self.counters.dropped = self.counters.dropped.wrapping_add(1);
+            e
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::PackageView;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn counters_as_signals() {
+        let mut map = SVMap::<1, 1>::new();
+        let mut diag = Diagnosed::new(Mock::<512>::new());
+        diag.dropped();
+        diag.overrun(2);
+        assert_eq!(
+            diag.check(Err::<(), _>(AddError::MapOverflow)),
+            Err(AddError::MapOverflow)
+        );
+        diag.check(Err::<(), _>(AddError::NotRegistered)).ok();
+        diag.check(map.set(&A, 5)).unwrap();
+        diag.tx.fail_after(0);
+        map.next(|m| assert!(diag.send_package(&MODULE, m).is_err()));
+        assert_eq!(
+            diag.counters(),
+            Counters {
+                dropped: 2,
+                errors: 1,
+                overflows: 1,
+                overruns: 2,
+            }
+        );
+
+        diag.tx.recover();
+        diag.tx.clear();
+        map.next(|m| diag.send_package(&MODULE, m).unwrap());
+        let tx = diag.free();
+        let (view, _) = PackageView::parse(tx.as_slice(), 1).unwrap();
+        let expected: [(&str, i32); 5] = [
+            ("a", 5),
+            (&DROPPED_NAME, 2),
+            (&ERRORS_NAME, 1),
+            (&OVERFLOW_NAME, 1),
+            (&OVERRUN_NAME, 2),
+        ];
+        assert_eq!(view.len(), expected.len());
+        for (record, &(name, val)) in view.records().zip(expected.iter()) {
+            assert_eq!(record.name_str(), Some(name));
+            assert_eq!(record.get(0), Some(val));
+        }
+    }
+}
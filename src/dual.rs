@@ -0,0 +1,101 @@
+use crate::{NextValue, SVMap, ValueRec};
+
+/// Map with second, decimated package stream.
+///
+/// Every time position goes to the full rate sink, every `factor`-th
+/// goes to the slow sink, e.g. full rate to SD logger and 1/10 rate over UART
+pub struct DualRate<const N: usize, const P: usize> {
+    fast: SVMap<N, P>,
+    slow: SVMap<N, P>,
+    factor: u32,
+    tick: u32,
+}
+
+impl<const N: usize, const P: usize> DualRate<N, P> {
+    /// Create new instance with decimation `factor` of slow stream
+    pub const fn new(factor: u32) -> Self {
+        Self {
+            fast: SVMap::new(),
+            slow: SVMap::new(),
+            factor: if factor == 0 { 1 } else { factor },
+            tick: 0,
+        }
+    }
+
+    /// Go to next time position.
+    /// `fast` and `slow` are send package functions of each stream
+    pub fn next<F, S>(&mut self, fast: F, slow: S)
+    where
+        F: FnOnce(&SVMap<N, P>),
+        S: FnOnce(&SVMap<N, P>),
+    {
+        if self.tick == 0 {
+            let current = self.fast.current;
+            for (&name, v) in self.fast.map.iter() {
+                if !self.slow.map.contains_key(&name) {
+                    if self.slow.map.insert(name, ValueRec::new(v.vtype)).is_err() {
+                        continue;
+                    }
+                    self.slow.layout_changed();
+                }
+                let rec = self.slow.map.get_mut(name).unwrap();
+                rec.vals[self.slow.current] = v.vals[current];
+            }
+            self.slow.next(slow);
+        }
+        self.tick = (self.tick + 1) % self.factor;
+        self.fast.next(fast);
+    }
+}
+
+impl<const N: usize, const P: usize> core::ops::Deref for DualRate<N, P> {
+    type Target = SVMap<N, P>;
+    fn deref(&self) -> &Self::Target {
+        &self.fast
+    }
+}
+
+impl<const N: usize, const P: usize> core::ops::DerefMut for DualRate<N, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.fast
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::Sample;
+
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn slow_stream_decimated() {
+        let mut dual = DualRate::<2, 4>::new(2);
+        let (mut fast, mut slow) = (0, 0);
+        for i in 0..8 {
+            dual.set(&A, i).unwrap();
+            dual.next(
+                |m| {
+                    let first = fast as f32 * 4.;
+                    assert!(m.samples(&A).unwrap().map(Sample::as_f32).eq([
+                        first,
+                        first + 1.,
+                        first + 2.,
+                        first + 3.
+                    ]));
+                    fast += 1;
+                },
+                |m| {
+                    assert!(m
+                        .samples(&A)
+                        .unwrap()
+                        .map(Sample::as_f32)
+                        .eq([0., 2., 4., 6.]));
+                    slow += 1;
+                },
+            );
+        }
+        assert_eq!((fast, slow), (2, 1));
+    }
+}
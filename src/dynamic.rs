@@ -0,0 +1,126 @@
+use heapless::{LinearMap, String};
+
+use crate::store::{NewSignal, SignalStore};
+use crate::{AddError, Name, SVStruct, ValueRec, ValueType, NAME_SIZE};
+
+/// Store of signals with names copied into it
+pub type DynStore<const N: usize, const P: usize> =
+    LinearMap<String<NAME_SIZE>, ValueRec<[i32; P]>, N>;
+
+/// Map of signals with names generated at runtime,
+/// e.g. `cell_07` for each of variable number of battery cells
+pub type DynMap<const N: usize, const P: usize> = SVStruct<DynStore<N, P>>;
+
+impl<const N: usize, const P: usize> DynMap<N, P> {
+    /// Create new instance
+    pub const fn new() -> Self {
+        Self::with_map(LinearMap::new())
+    }
+}
+
+/// Map key of signal name
+fn key(name: &str) -> String<NAME_SIZE> {
+    let mut key = String::new();
+    // Name is always shorter than key capacity
+    key.push_str(name).ok();
+    key
+}
+
+impl<const N: usize, const P: usize> SignalStore for DynStore<N, P> {
+    type Values = [i32; P];
+    fn package(&self) -> usize {
+        P
+    }
+    fn len(&self) -> usize {
+        LinearMap::len(self)
+    }
+    fn get(&self, name: &str) -> Option<&ValueRec<[i32; P]>> {
+        LinearMap::get(self, &key(name))
+    }
+    fn get_mut(&mut self, name: &str) -> Option<&mut ValueRec<[i32; P]>> {
+        LinearMap::get_mut(self, &key(name))
+    }
+    fn position(&self, name: &str) -> Option<usize> {
+        self.keys().position(|k| k == name)
+    }
+    fn get_index_mut(&mut self, idx: usize) -> Option<(&str, &mut ValueRec<[i32; P]>)> {
+        self.iter_mut().nth(idx).map(|(k, vr)| (k.as_str(), vr))
+    }
+    fn insert(&mut self, name: &'static str, vtype: ValueType) -> Result<(), AddError> {
+        self.insert_copied(name, vtype)
+    }
+    fn insert_copied(&mut self, name: &str, vtype: ValueType) -> Result<(), AddError> {
+        LinearMap::insert(self, key(name), ValueRec::new(vtype))
+            .map(|_| ())
+            .map_err(|_| AddError::MapOverflow)
+    }
+    fn remove(&mut self, name: &str) -> bool {
+        LinearMap::remove(self, &key(name)).is_some()
+    }
+    fn clear(&mut self) {
+        LinearMap::clear(self)
+    }
+    fn try_for_each<E, F>(&self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&str, &ValueRec<[i32; P]>) -> Result<(), E>,
+    {
+        self.iter().try_for_each(|(k, vr)| f(k, vr))
+    }
+    fn for_each_mut<F: FnMut(&mut ValueRec<[i32; P]>)>(&mut self, f: F) {
+        self.values_mut().for_each(f)
+    }
+}
+
+/// Name is copied into map on first update
+impl<const N: usize, const P: usize> NewSignal<DynStore<N, P>> for &Name {
+    fn insert(&self, store: &mut DynStore<N, P>, vtype: ValueType) -> Result<(), AddError> {
+        store.insert_copied(self, vtype)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+    use crate::SendPackage;
+    use core::fmt::Write;
+
+    const MODULE: SVName = SVName::new("battery");
+
+    #[test]
+    fn runtime_names() {
+        let mut map = DynMap::<3, 1>::new();
+        let names: heapless::Vec<Name, 4> = (0..4)
+            .map(|i| {
+                let mut s = String::<NAME_SIZE>::new();
+                write!(s, "cell_{:02}", i).unwrap();
+                Name::try_new(&s).unwrap()
+            })
+            .collect();
+        for (i, name) in names[..3].iter().enumerate() {
+            map.set(name, i as f32).unwrap();
+        }
+        assert_eq!(map.set(&names[3], 3.), Err(AddError::MapOverflow));
+        assert_eq!(map.get(&Name::new("cell_01")), Some(1.));
+
+        // Same package as map with static names
+        let mut fixed = SVMap::<3, 1>::new();
+        const CELLS: [SVName; 3] = [
+            SVName::new("cell_00"),
+            SVName::new("cell_01"),
+            SVName::new("cell_02"),
+        ];
+        for (i, name) in CELLS.iter().enumerate() {
+            fixed.set(name, i as f32).unwrap();
+        }
+        let (mut a, mut b) = (Buffer::<512>::new(), Buffer::<512>::new());
+        map.next(|m| a.send_package(&MODULE, m).unwrap());
+        fixed.next(|m| b.send_package(&MODULE, m).unwrap());
+        assert_eq!(a.as_slice(), b.as_slice());
+
+        assert!(map.remove(&names[1]));
+        map.set(&names[3], 3.).unwrap();
+        assert_eq!(map.get::<f32, _>(&names[1]), None);
+    }
+}
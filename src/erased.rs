@@ -0,0 +1,99 @@
+use crate::buffer::{Buffer, BufferOverflow};
+use crate::{Name, SendPackage, WriteIter};
+
+/// Errors of erased sender
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ErasedError {
+    /// Package does not fit in buffer
+    Overflow,
+    /// Transport error, details are erased with transport type
+    Transport,
+}
+
+/// Object-safe sender of encoded packages and frames,
+/// so output transport can be selected at runtime as `&mut dyn ErasedSender`.
+///
+/// Implemented for every [`WriteIter`] transport
+pub trait ErasedSender {
+    /// Send whole encoded frame
+    fn send_frame(&mut self, frame: &[u8]) -> Result<(), ErasedError>;
+}
+
+impl<Tx: WriteIter> ErasedSender for Tx {
+    fn send_frame(&mut self, frame: &[u8]) -> Result<(), ErasedError> {
+        self.bwrite_iter(frame.iter().cloned())
+            .and_then(|_| self.bflush())
+            .and_then(|_| self.bend_frame())
+            .map_err(|_| ErasedError::Transport)
+    }
+}
+
+/// Transport encoding packages of up to `B` bytes for erased sender
+pub struct Erased<'a, const B: usize> {
+    sender: &'a mut dyn ErasedSender,
+    buffer: Buffer<B>,
+}
+
+impl<'a, const B: usize> Erased<'a, B> {
+    /// Wrap erased sender
+    pub fn new(sender: &'a mut dyn ErasedSender) -> Self {
+        Self {
+            sender,
+            buffer: Buffer::new(),
+        }
+    }
+}
+
+impl<V, const B: usize> SendPackage<V> for Erased<'_, B>
+where
+    Buffer<B>: SendPackage<V, Error = BufferOverflow>,
+{
+    type Error = ErasedError;
+
+    fn send_package(&mut self, module: &Name, values: &V) -> Result<(), Self::Error> {
+        self.buffer.clear();
+        self.buffer
+            .send_package(module, values)
+            .map_err(|_| ErasedError::Overflow)?;
+        self.sender.send_frame(self.buffer.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn runtime_selected_sender() {
+        let (mut first, mut second) = (Mock::<512>::new(), Mock::<512>::new());
+        let mut expected = Mock::<512>::new();
+        let mut map = SVMap::<1, 1>::new();
+        map.set(&A, 1).unwrap();
+        for use_second in [false, true] {
+            let sender: &mut dyn ErasedSender = if use_second { &mut second } else { &mut first };
+            let mut tx = Erased::<512>::new(sender);
+            map.next(|m| tx.send_package(&MODULE, m).unwrap());
+        }
+        map.next(|m| expected.send_package(&MODULE, m).unwrap());
+        assert_eq!(first.as_slice(), expected.as_slice());
+        assert_eq!(second.as_slice(), expected.as_slice());
+
+        first.fail_after(0);
+        map.next(|m| {
+            assert_eq!(
+                Erased::<512>::new(&mut first).send_package(&MODULE, m),
+                Err(ErasedError::Transport)
+            );
+            assert_eq!(
+                Erased::<8>::new(&mut second).send_package(&MODULE, m),
+                Err(ErasedError::Overflow)
+            );
+        });
+    }
+}
@@ -0,0 +1,142 @@
+use core::iter::once;
+
+use crate::WriteIter;
+
+/// Frame delimiter, never present inside escaped frame
+pub const DELIMITER: u8 = b'=';
+
+/// Escape byte, followed by escaped byte XOR `0x20`
+pub const ESC: u8 = 0x7D;
+
+/// Transport escaping all `=` bytes, so payload never aliases `=begin=` and `=end=` markers.
+///
+/// Each frame is followed by single [`DELIMITER`]
+pub struct Escaped<Tx> {
+    tx: Tx,
+}
+
+impl<Tx: WriteIter> Escaped<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self { tx }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+impl<Tx: WriteIter> WriteIter for Escaped<Tx> {
+    type Error = Tx::Error;
+
+    fn bwrite_iter<WI>(&mut self, bytes: WI) -> Result<(), Self::Error>
+    where
+        WI: Iterator<Item = u8>,
+    {
+        self.tx.bwrite_iter(bytes.flat_map(|b| {
+            let escaped = b == DELIMITER || b == ESC;
+            once(if escaped { ESC } else { b })
+                .chain(once(b ^ 0x20))
+                .take(if escaped { 2 } else { 1 })
+        }))
+    }
+
+    fn bflush(&mut self) -> Result<(), Self::Error> {
+        self.tx.bflush()
+    }
+
+    fn bend_frame(&mut self) -> Result<(), Self::Error> {
+        self.tx.bwrite_iter(once(DELIMITER))?;
+        self.tx.bflush()?;
+        self.tx.bend_frame()
+    }
+}
+
+/// Errors of unescaping
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum UnescapeError {
+    /// Frame contains delimiter or ends with escape byte
+    Corrupted,
+    /// Output buffer is too small
+    Overflow,
+}
+
+/// Restore escaped `frame` without delimiter into `out`.
+///
+/// Returns size of restored frame
+pub fn unescape(frame: &[u8], out: &mut [u8]) -> Result<usize, UnescapeError> {
+    let mut len = 0;
+    let mut bytes = frame.iter();
+    while let Some(&b) = bytes.next() {
+        let b = match b {
+            DELIMITER => return Err(UnescapeError::Corrupted),
+            ESC => bytes.next().ok_or(UnescapeError::Corrupted)? ^ 0x20,
+            b => b,
+        };
+        *out.get_mut(len).ok_or(UnescapeError::Overflow)? = b;
+        len += 1;
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+
+    fn escape(bytes: &[u8]) -> Buffer<256> {
+        let mut escaped = Escaped::new(Buffer::new());
+        escaped.bwrite_iter(bytes.iter().cloned()).unwrap();
+        escaped.bend_frame().unwrap();
+        escaped.free()
+    }
+
+    #[test]
+    fn round_trip() {
+        for bytes in [
+            &b""[..],
+            b"plain",
+            b"=begin=\x10\0\0\0=end=",
+            &[ESC, DELIMITER, ESC ^ 0x20, DELIMITER ^ 0x20],
+        ] {
+            let frame = escape(bytes);
+            let (delimiter, frame) = frame.split_last().unwrap();
+            assert_eq!(*delimiter, DELIMITER);
+            assert!(!frame.contains(&DELIMITER));
+            let mut out = [0; 256];
+            let len = unescape(frame, &mut out).unwrap();
+            assert_eq!(&out[..len], bytes);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let frame = escape(b"a=b");
+        // Frame ends with escape byte
+        assert_eq!(
+            unescape(&frame[..2], &mut [0; 16]),
+            Err(UnescapeError::Corrupted)
+        );
+    }
+
+    #[test]
+    fn corrupted() {
+        let mut frame = escape(b"a=b");
+        frame[1] = DELIMITER;
+        assert_eq!(
+            unescape(&frame[..frame.len() - 1], &mut [0; 16]),
+            Err(UnescapeError::Corrupted)
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let frame = escape(b"abc");
+        assert_eq!(
+            unescape(&frame[..frame.len() - 1], &mut [0; 2]),
+            Err(UnescapeError::Overflow)
+        );
+    }
+}
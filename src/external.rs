@@ -0,0 +1,216 @@
+use crate::store::{SignalStore, StaticNames};
+use crate::{AddError, SVStruct, ValueRec, ValueType};
+
+/// Signal entry of map with external storage
+pub struct Entry<'a> {
+    name: &'static str,
+    /// Record with values of signal, `None` if there are no values for entry
+    rec: Option<ValueRec<&'a mut [i32]>>,
+}
+
+impl Entry<'_> {
+    /// Unused entry, e.g. for `[Entry::EMPTY; 16]` storage
+    pub const EMPTY: Self = Self {
+        name: "",
+        rec: None,
+    };
+}
+
+/// Signal store in caller-provided storage
+pub struct RefStore<'a> {
+    entries: &'a mut [Entry<'a>],
+    len: usize,
+    package: usize,
+}
+
+/// Map of signals in caller-provided storage,
+/// so signal count and package size can be chosen at runtime, e.g. from config in flash.
+///
+/// Values of signal `i` are `values[i * package..(i + 1) * package]`
+/// until signals are removed
+pub type SVMapRef<'a> = SVStruct<RefStore<'a>>;
+
+impl<'a> SVMapRef<'a> {
+    /// Create map with `package` values in package.
+    /// Returns `None` if `package` is 0 or there is no space for one signal
+    pub fn new(
+        entries: &'a mut [Entry<'a>],
+        values: &'a mut [i32],
+        package: usize,
+    ) -> Option<Self> {
+        if package == 0 || values.len() < package || entries.is_empty() {
+            return None;
+        }
+        let capacity = entries.len().min(values.len() / package);
+        for (entry, vals) in entries.iter_mut().zip(values.chunks_exact_mut(package)) {
+            *entry = Entry {
+                name: "",
+                rec: Some(ValueRec::with_values(ValueType::Int, vals)),
+            };
+        }
+        Some(Self::with_map(RefStore {
+            entries: &mut entries[..capacity],
+            len: 0,
+            package,
+        }))
+    }
+}
+
+impl RefStore<'_> {
+    /// Maximum number of signals
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Number of values in package
+    pub fn package(&self) -> usize {
+        self.package
+    }
+
+    /// Number of signals
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if map has no signals
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<'a> SignalStore for RefStore<'a> {
+    type Values = &'a mut [i32];
+    fn package(&self) -> usize {
+        self.package
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn get(&self, name: &str) -> Option<&ValueRec<&'a mut [i32]>> {
+        let idx = self.position(name)?;
+        self.entries[idx].rec.as_ref()
+    }
+    fn get_mut(&mut self, name: &str) -> Option<&mut ValueRec<&'a mut [i32]>> {
+        let idx = self.position(name)?;
+        self.entries[idx].rec.as_mut()
+    }
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries[..self.len].iter().position(|e| e.name == name)
+    }
+    fn get_index_mut(&mut self, idx: usize) -> Option<(&str, &mut ValueRec<&'a mut [i32]>)> {
+        let entry = self.entries[..self.len].get_mut(idx)?;
+        let name = entry.name;
+        entry.rec.as_mut().map(|vr| (name, vr))
+    }
+    fn insert(&mut self, name: &'static str, vtype: ValueType) -> Result<(), AddError> {
+        let entry = self
+            .entries
+            .get_mut(self.len)
+            .ok_or(AddError::MapOverflow)?;
+        // Values of removed signal are reused
+        let vals = entry.rec.take().ok_or(AddError::MapOverflow)?.vals;
+        let mut vr = ValueRec::with_values(vtype, vals);
+        vr.reset();
+        *entry = Entry {
+            name,
+            rec: Some(vr),
+        };
+        self.len += 1;
+        Ok(())
+    }
+    fn remove(&mut self, name: &str) -> bool {
+        let idx = match self.position(name) {
+            Some(idx) => idx,
+            None => return false,
+        };
+        // Move last signal in place of removed one
+        self.entries.swap(idx, self.len - 1);
+        self.len -= 1;
+        true
+    }
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+    fn try_for_each<E, F>(&self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&str, &ValueRec<&'a mut [i32]>) -> Result<(), E>,
+    {
+        self.entries[..self.len]
+            .iter()
+            .filter_map(|e| e.rec.as_ref().map(|vr| (e.name, vr)))
+            .try_for_each(|(name, vr)| f(name, vr))
+    }
+    fn for_each_mut<F: FnMut(&mut ValueRec<&'a mut [i32]>)>(&mut self, f: F) {
+        self.entries[..self.len]
+            .iter_mut()
+            .filter_map(|e| e.rec.as_mut())
+            .for_each(f)
+    }
+}
+
+impl StaticNames for RefStore<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+    use crate::OnlyFront;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+    const C: SVName = SVName::new("c");
+    const NOTE: SVName = SVName::new("note");
+
+    #[test]
+    fn same_as_svmap() {
+        let mut entries = [Entry::EMPTY; 4];
+        let mut values = [0; 12];
+        let mut ext = SVMapRef::new(&mut entries, &mut values, 3).unwrap();
+        let mut map = SVMap::<4, 3>::new();
+        let (mut a, mut b) = (Buffer::<2048>::new(), Buffer::<2048>::new());
+        ext.set_decimation(&A, 1).unwrap_err();
+        for i in 0..6 {
+            ext.set(&A, i).unwrap();
+            map.set(&A, i).unwrap();
+            ext.set(&B, OnlyFront(i == 4)).unwrap();
+            map.set(&B, OnlyFront(i == 4)).unwrap();
+            if i == 1 {
+                ext.annotate(&NOTE).unwrap();
+                map.annotate(&NOTE).unwrap();
+                ext.set_decimation(&A, 2).unwrap();
+                map.set_decimation(&A, 2).unwrap();
+            }
+            ext.next(|m| a.send_package(&MODULE, m).unwrap());
+            map.next(|m| b.send_package(&MODULE, m).unwrap());
+        }
+        assert!(!a.is_empty());
+        assert_eq!(&*a, &*b);
+    }
+
+    #[test]
+    fn remove_reuses_values() {
+        let mut entries = [Entry::EMPTY; 4];
+        let mut values = [0; 4];
+        assert!(SVMapRef::new(&mut [], &mut [0; 2], 2).is_none());
+        let mut map = SVMapRef::new(&mut entries, &mut values, 2).unwrap();
+        assert_eq!(map.capacity(), 2);
+        map.set(&A, 1).unwrap();
+        map.set(&B, 2.).unwrap();
+        assert_eq!(map.set(&C, 3), Err(AddError::MapOverflow));
+
+        assert!(map.remove(&A));
+        assert!(!map.remove(&A));
+        assert_eq!(map.len(), 1);
+        map.set(&C, true).unwrap();
+        assert_eq!(map.get(&B), Some(2.));
+        assert_eq!(map.get(&C), Some(true));
+        assert_eq!(map.get::<i32, _>(&A), None);
+
+        map.pause();
+        map.next(|_| panic!("paused map must not send"));
+        map.clear();
+        assert!(map.is_empty());
+    }
+}
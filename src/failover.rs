@@ -0,0 +1,145 @@
+use crate::{Name, SendPackage};
+
+/// Errors of failover sender
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FailoverError<A, B> {
+    /// Primary transport failed
+    Primary(A),
+    /// Backup transport failed
+    Backup(B),
+}
+
+/// Sender switching to backup transport after `threshold` consecutive failures
+/// of primary one, e.g. for field devices with flaky main link.
+///
+/// On backup, primary is retried every `retry` packages,
+/// package of failed retry is sent to backup
+pub struct Failover<A, B> {
+    primary: A,
+    backup: B,
+    threshold: u32,
+    retry: u32,
+    failures: u32,
+    on_backup: bool,
+    skipped: u32,
+}
+
+impl<A, B> Failover<A, B> {
+    /// Combine transports
+    pub fn new(primary: A, backup: B, threshold: u32, retry: u32) -> Self {
+        Self {
+            primary,
+            backup,
+            threshold,
+            retry,
+            failures: 0,
+            on_backup: false,
+            skipped: 0,
+        }
+    }
+
+    /// Release transports
+    pub fn free(self) -> (A, B) {
+        (self.primary, self.backup)
+    }
+
+    /// Checks if packages go to backup transport
+    pub fn is_on_backup(&self) -> bool {
+        self.on_backup
+    }
+
+    /// Switch back to primary transport
+    pub fn reset(&mut self) {
+        self.failures = 0;
+        self.on_backup = false;
+    }
+}
+
+impl<A, B, V> SendPackage<V> for Failover<A, B>
+where
+    A: SendPackage<V>,
+    B: SendPackage<V>,
+{
+    type Error = FailoverError<A::Error, B::Error>;
+
+    fn send_package(&mut self, module: &Name, values: &V) -> Result<(), Self::Error> {
+        if !self.on_backup {
+            return match self.primary.send_package(module, values) {
+                Ok(()) => {
+                    self.failures = 0;
+                    Ok(())
+                }
+                Err(e) => {
+                    self.failures = self.failures.saturating_add(1);
+                    if self.failures >= self.threshold {
+                        self.on_backup = true;
+                        self.skipped = 0;
+                    }
+                    Err(FailoverError::Primary(e))
+                }
+            };
+        }
+        self.skipped = self.skipped.saturating_add(1);
+        if self.skipped >= self.retry {
+            self.skipped = 0;
+            if self.primary.send_package(module, values).is_ok() {
+                self.reset();
+                return Ok(());
+            }
+        }
+        self.backup
+            .send_package(module, values)
+            .map_err(FailoverError::Backup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Mock, MockError};
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn switch_and_retry() {
+        let mut map = SVMap::<1, 2>::new();
+        map.set(&A, 1).unwrap();
+        let mut size = Mock::<2048>::new();
+        size.send_package(&MODULE, &map).unwrap();
+        let size = size.as_slice().len();
+
+        let mut failover = Failover::new(Mock::<2048>::new(), Mock::<2048>::new(), 2, 3);
+        failover.primary.fail_after(0);
+        for _ in 0..2 {
+            assert_eq!(
+                failover.send_package(&MODULE, &map),
+                Err(FailoverError::Primary(MockError::Injected))
+            );
+        }
+        assert!(failover.is_on_backup());
+        // Primary is retried on third package, failed one goes to backup
+        for _ in 0..5 {
+            failover.send_package(&MODULE, &map).unwrap();
+        }
+        assert!(failover.is_on_backup());
+        assert_eq!(failover.backup.as_slice().len(), 5 * size);
+
+        failover.primary.recover();
+        failover.send_package(&MODULE, &map).unwrap();
+        assert!(!failover.is_on_backup());
+        assert_eq!(failover.backup.as_slice().len(), 5 * size);
+        assert_eq!(failover.primary.as_slice().len(), size);
+
+        // Single failure below threshold stays on primary
+        failover.primary.fail_after(0);
+        failover.send_package(&MODULE, &map).unwrap_err();
+        failover.primary.recover();
+        failover.send_package(&MODULE, &map).unwrap();
+        failover.primary.fail_after(0);
+        failover.send_package(&MODULE, &map).unwrap_err();
+        assert!(!failover.is_on_backup());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{Name, SendPackage};
+
+/// Handling of branch errors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FanoutPolicy {
+    /// Stop on first error, second branch is skipped if first one fails
+    FailFast,
+    /// Send to both branches and report errors of each
+    BestEffort,
+}
+
+/// Errors of fan-out sender
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FanoutError<A, B> {
+    /// First branch failed
+    First(A),
+    /// Second branch failed
+    Second(B),
+    /// Both branches failed
+    Both(A, B),
+}
+
+/// Sender of each package to two transports, e.g. UART for the bench and flash logger.
+///
+/// Nest fan-outs for more transports: `Fanout<A, Fanout<B, C>>`
+pub struct Fanout<A, B> {
+    first: A,
+    second: B,
+    policy: FanoutPolicy,
+    errors: (u32, u32),
+}
+
+impl<A, B> Fanout<A, B> {
+    /// Combine transports
+    pub fn new(first: A, second: B, policy: FanoutPolicy) -> Self {
+        Self {
+            first,
+            second,
+            policy,
+            errors: (0, 0),
+        }
+    }
+
+    /// Release transports
+    pub fn free(self) -> (A, B) {
+        (self.first, self.second)
+    }
+
+    /// First transport
+    pub fn first(&mut self) -> &mut A {
+        &mut self.first
+    }
+
+    /// Second transport
+    pub fn second(&mut self) -> &mut B {
+        &mut self.second
+    }
+
+    /// Number of errors of each branch
+    pub fn errors(&self) -> (u32, u32) {
+        self.errors
+    }
+}
+
+impl<A, B, V> SendPackage<V> for Fanout<A, B>
+where
+    A: SendPackage<V>,
+    B: SendPackage<V>,
+{
+    type Error = FanoutError<A::Error, B::Error>;
+
+    fn send_package(&mut self, module: &Name, values: &V) -> Result<(), Self::Error> {
+        let first = self.first.send_package(module, values);
+        if first.is_err() {
+            self.errors.0 = self.errors.0.wrapping_add(1);
+        }
+        let first = match (first, self.policy) {
+            (Err(e), FanoutPolicy::FailFast) => return Err(FanoutError::First(e)),
+            (first, _) => first,
+        };
+        let second = self.second.send_package(module, values);
+        if second.is_err() {
+            self.errors.1 = self.errors.1.wrapping_add(1);
+        }
+        match (first, second) {
+            (Ok(()), Ok(())) => Ok(()),
+            (Err(a), Ok(())) => Err(FanoutError::First(a)),
+            (Ok(()), Err(b)) => Err(FanoutError::Second(b)),
+            (Err(a), Err(b)) => Err(FanoutError::Both(a, b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Mock, MockError};
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    fn fanout(policy: FanoutPolicy) -> (Fanout<Mock<512>, Mock<512>>, SVMap<1, 2>) {
+        let mut map = SVMap::new();
+        map.set(&A, 1).unwrap();
+        (Fanout::new(Mock::new(), Mock::new(), policy), map)
+    }
+
+    #[test]
+    fn fail_fast() {
+        let (mut fanout, map) = fanout(FanoutPolicy::FailFast);
+        fanout.send_package(&MODULE, &map).unwrap();
+        assert!(!fanout.second().as_slice().is_empty());
+        fanout.second().clear();
+
+        fanout.first().fail_after(0);
+        assert_eq!(
+            fanout.send_package(&MODULE, &map),
+            Err(FanoutError::First(MockError::Injected))
+        );
+        // Second branch is skipped
+        assert!(fanout.second().as_slice().is_empty());
+        assert_eq!(fanout.errors(), (1, 0));
+    }
+
+    #[test]
+    fn best_effort() {
+        let (mut fanout, map) = fanout(FanoutPolicy::BestEffort);
+        fanout.first().fail_after(0);
+        assert_eq!(
+            fanout.send_package(&MODULE, &map),
+            Err(FanoutError::First(MockError::Injected))
+        );
+        assert!(!fanout.second().as_slice().is_empty());
+
+        fanout.second().fail_after(0);
+        assert_eq!(
+            fanout.send_package(&MODULE, &map),
+            Err(FanoutError::Both(MockError::Injected, MockError::Injected))
+        );
+        fanout.first().recover();
+        assert_eq!(
+            fanout.send_package(&MODULE, &map),
+            Err(FanoutError::Second(MockError::Injected))
+        );
+        assert_eq!(fanout.errors(), (2, 2));
+    }
+}
@@ -0,0 +1,232 @@
+use core::marker::PhantomData;
+
+use crate::store::{NewSignal, SignalKey, SignalStore, StaticNames};
+use crate::{AddError, Name, SVStruct, SignalDef, ValueRec, ValueType};
+
+/// Enum of statically known signals, usually generated with [`signals!`](crate::signals!)
+pub trait SignalEnum: Copy {
+    /// Signals in order of variants
+    const SIGNALS: &'static [SignalDef];
+    /// Index of variant in [`SIGNALS`](Self::SIGNALS)
+    fn index(self) -> usize;
+}
+
+/// Records of `K` signals listed by enum `E`
+#[derive(Clone)]
+pub struct Slots<E, const K: usize, const P: usize> {
+    recs: [ValueRec<[i32; P]>; K],
+    _signals: PhantomData<E>,
+}
+
+/// Map of fixed signal set indexed by enum, `set` is array store without name lookup
+pub type FixedMap<E, const K: usize, const P: usize> = SVStruct<Slots<E, K, P>>;
+
+impl<E: SignalEnum, const K: usize, const P: usize> FixedMap<E, K, P> {
+    /// Compile-time checks of map parameters
+    const VALID: () = {
+        assert!(
+            E::SIGNALS.len() == K,
+            "K must be equal to number of signals"
+        );
+        assert!(P > 0, "package size P must be greater than 0");
+        assert!(SignalDef::all_unique(E::SIGNALS), "duplicate signal name");
+    };
+
+    /// Create new instance with all signals registered
+    pub fn new() -> Self {
+        let () = Self::VALID;
+        let mut idx = 0;
+        let recs = [(); K].map(|_| {
+            let s = &E::SIGNALS[idx];
+            idx += 1;
+            let mut vr = ValueRec::new(s.vtype);
+            vr.front = s.front;
+            vr
+        });
+        let mut map = Self::with_map(Slots {
+            recs,
+            _signals: PhantomData,
+        });
+        map.sealed = true;
+        map
+    }
+}
+
+impl<E: SignalEnum, const K: usize, const P: usize> Default for FixedMap<E, K, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: SignalEnum, const K: usize, const P: usize> Slots<E, K, P> {
+    fn index(name: &str) -> Option<usize> {
+        E::SIGNALS.iter().position(|s| &*s.name == name)
+    }
+}
+
+/// Signal set is fixed, so signals are never added or removed
+impl<E: SignalEnum, const K: usize, const P: usize> SignalStore for Slots<E, K, P> {
+    type Values = [i32; P];
+    fn package(&self) -> usize {
+        P
+    }
+    fn len(&self) -> usize {
+        K
+    }
+    fn get(&self, name: &str) -> Option<&ValueRec<[i32; P]>> {
+        Self::index(name).map(|i| &self.recs[i])
+    }
+    fn get_mut(&mut self, name: &str) -> Option<&mut ValueRec<[i32; P]>> {
+        Self::index(name).map(move |i| &mut self.recs[i])
+    }
+    fn position(&self, name: &str) -> Option<usize> {
+        Self::index(name)
+    }
+    fn get_index_mut(&mut self, idx: usize) -> Option<(&str, &mut ValueRec<[i32; P]>)> {
+        let name: &'static str = &E::SIGNALS.get(idx)?.name;
+        self.recs.get_mut(idx).map(|vr| (name, vr))
+    }
+    fn insert(&mut self, _name: &'static str, _vtype: ValueType) -> Result<(), AddError> {
+        Err(AddError::NotRegistered)
+    }
+    fn remove(&mut self, _name: &str) -> bool {
+        false
+    }
+    fn clear(&mut self) {}
+    fn try_for_each<Err, F>(&self, mut f: F) -> Result<(), Err>
+    where
+        F: FnMut(&str, &ValueRec<[i32; P]>) -> Result<(), Err>,
+    {
+        E::SIGNALS
+            .iter()
+            .zip(self.recs.iter())
+            .try_for_each(|(s, vr)| f(&s.name, vr))
+    }
+    fn for_each_mut<F: FnMut(&mut ValueRec<[i32; P]>)>(&mut self, f: F) {
+        self.recs.iter_mut().for_each(f)
+    }
+}
+
+impl<E: SignalEnum, const K: usize, const P: usize> StaticNames for Slots<E, K, P> {}
+
+/// Update by variant is array store without name lookup
+impl<E: SignalEnum, const K: usize, const P: usize> SignalKey<Slots<E, K, P>> for E {
+    fn name(&self) -> &Name {
+        &E::SIGNALS[self.index()].name
+    }
+    fn get<'s>(&self, store: &'s Slots<E, K, P>) -> Option<&'s ValueRec<[i32; P]>> {
+        store.recs.get(self.index())
+    }
+    fn get_mut<'s>(&self, store: &'s mut Slots<E, K, P>) -> Option<&'s mut ValueRec<[i32; P]>> {
+        store.recs.get_mut(self.index())
+    }
+}
+
+impl<E: SignalEnum, const K: usize, const P: usize> NewSignal<Slots<E, K, P>> for E {
+    fn insert(&self, _store: &mut Slots<E, K, P>, _vtype: ValueType) -> Result<(), AddError> {
+        Err(AddError::NotRegistered)
+    }
+}
+
+/// Enum of fixed signal set and [`FixedMap`] alias for it,
+/// e.g. `signals!{ pub enum Motor: MotorMap { Current = "current": f32, On = "on": bool } }`
+/// for `motor.set(Motor::Current, 1.5)` on `MotorMap<10>` with 10 values in package.
+///
+/// Name length and duplicates are checked at compile time
+#[macro_export]
+macro_rules! signals {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $enum:ident : $map:ident {
+            $($variant:ident = $name:literal : $t:ty),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        $vis enum $enum {
+            $(
+                #[doc = $name]
+                $variant,
+            )+
+        }
+
+        impl $crate::fixed::SignalEnum for $enum {
+            const SIGNALS: &'static [$crate::SignalDef] = &[$($crate::SignalDef {
+                name: $crate::Name::new($name),
+                vtype: <$t as $crate::Value>::TYPE,
+                front: <$t as $crate::Value>::FRONT,
+            }),+];
+            fn index(self) -> usize {
+                self as usize
+            }
+        }
+
+        #[doc = concat!("Map of [`", stringify!($enum), "`] signals")]
+        $vis type $map<const P: usize> =
+            $crate::fixed::FixedMap<$enum, { [$(stringify!($variant)),+].len() }, P>;
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+    use crate::AddError;
+
+    crate::signals! {
+        enum Motor: MotorMap {
+            Current = "current": f32,
+            On = "on": bool,
+        }
+    }
+
+    const MODULE: SVName = SVName::new("motor");
+    const NOTE: SVName = SVName::new("start");
+    const CURRENT: SVName = SVName::new("current");
+    const ON: SVName = SVName::new("on");
+    const OTHER: SVName = SVName::new("other");
+
+    #[test]
+    fn same_as_svmap() {
+        let mut fixed = MotorMap::<4>::new();
+        let mut map = SVMap::<2, 4>::new();
+        let (mut a, mut b) = (Buffer::<2048>::new(), Buffer::<2048>::new());
+        for i in 0..8 {
+            fixed.set(Motor::Current, i as f32).unwrap();
+            fixed.set(Motor::On, i > 2).unwrap();
+            map.set(&CURRENT, i as f32).unwrap();
+            map.set(&ON, i > 2).unwrap();
+            if i == 5 {
+                fixed.annotate(&NOTE).unwrap();
+                map.annotate(&NOTE).unwrap();
+            }
+            fixed.next(|m| a.send_package(&MODULE, m).unwrap());
+            map.next(|m| b.send_package(&MODULE, m).unwrap());
+        }
+        assert!(!a.is_empty());
+        assert_eq!(&*a, &*b);
+    }
+
+    #[test]
+    fn features_of_core() {
+        let mut map = MotorMap::<4>::new();
+        map.set_decimation(Motor::Current, 2).unwrap();
+        map.set(Motor::Current, 1.).unwrap();
+        assert_eq!(map.get(Motor::Current), Some(1.));
+        assert_eq!(map.get::<bool, _>(Motor::Current), None);
+        map.next(|_| {});
+        // Skipped by decimation, previous value is held
+        map.set(Motor::Current, 2.).unwrap();
+        assert_eq!(map.get(Motor::Current), Some(1.));
+
+        map.pause();
+        map.next(|_| panic!("paused map must not send"));
+        assert!(map.is_paused());
+
+        // Signal set is fixed
+        assert_eq!(map.set(&OTHER, 1), Err(AddError::NotRegistered));
+        #[cfg(not(feature = "unchecked"))]
+        assert_eq!(map.set(Motor::On, 1), Err(AddError::TypeMismatch));
+        assert!(!map.remove(&ON));
+    }
+}
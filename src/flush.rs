@@ -0,0 +1,119 @@
+use crate::WriteIter;
+
+/// When buffered bytes are flushed to transport
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FlushPolicy {
+    /// On each flush request: after header and every signal
+    Always,
+    /// Once at the end of each frame
+    PerFrame,
+    /// On flush request if at least this number of bytes is written since last flush,
+    /// and at the end of each frame
+    EveryBytes(usize),
+    /// Never, transport sends bytes by itself, e.g. DMA ring
+    Never,
+}
+
+/// Transport with configurable flushing.
+///
+/// `bflush` of some HALs waits for shift register to become empty,
+/// so flushing after every signal slows sending down
+pub struct FlushControl<Tx> {
+    tx: Tx,
+    policy: FlushPolicy,
+    unflushed: usize,
+}
+
+impl<Tx: WriteIter> FlushControl<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx, policy: FlushPolicy) -> Self {
+        Self {
+            tx,
+            policy,
+            unflushed: 0,
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+
+    /// Change flush policy
+    pub fn set_policy(&mut self, policy: FlushPolicy) {
+        self.policy = policy;
+    }
+
+    fn flush(&mut self) -> Result<(), Tx::Error> {
+        self.unflushed = 0;
+        self.tx.bflush()
+    }
+}
+
+impl<Tx: WriteIter> WriteIter for FlushControl<Tx> {
+    type Error = Tx::Error;
+
+    fn bwrite_iter<WI>(&mut self, bytes: WI) -> Result<(), Self::Error>
+    where
+        WI: Iterator<Item = u8>,
+    {
+        let unflushed = &mut self.unflushed;
+        self.tx.bwrite_iter(bytes.inspect(|_| *unflushed += 1))
+    }
+
+    fn bflush(&mut self) -> Result<(), Self::Error> {
+        match self.policy {
+            FlushPolicy::Always => self.flush(),
+            FlushPolicy::EveryBytes(n) if self.unflushed >= n => self.flush(),
+            _ => Ok(()),
+        }
+    }
+
+    fn bend_frame(&mut self) -> Result<(), Self::Error> {
+        if self.policy != FlushPolicy::Never && self.unflushed > 0 {
+            self.flush()?;
+        }
+        self.tx.bend_frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+
+    fn send<Tx: WriteIter>(tx: &mut Tx) {
+        let mut map = SVMap::<2, 1>::new();
+        map.set(&A, 1).unwrap();
+        map.set(&B, 2.).unwrap();
+        map.next(|m| tx.send_package(&MODULE, m).ok().unwrap());
+    }
+
+    #[test]
+    fn policies() {
+        let mut plain = Mock::<1024>::new();
+        send(&mut plain);
+        assert!(plain.flushes() > 2);
+
+        let counts = [
+            (FlushPolicy::Always, plain.flushes()),
+            (FlushPolicy::EveryBytes(1), plain.flushes()),
+            (FlushPolicy::EveryBytes(usize::MAX), 1),
+            (FlushPolicy::PerFrame, 1),
+            (FlushPolicy::Never, 0),
+        ];
+        for &(policy, flushes) in &counts {
+            let mut tx = FlushControl::new(Mock::<1024>::new(), policy);
+            send(&mut tx);
+            let tx = tx.free();
+            assert_eq!(tx.flushes(), flushes, "{:?}", policy);
+            assert_eq!(tx.as_slice(), plain.as_slice());
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::decoder::{DecodeError, PackageView, RecordView};
+use crate::{abs, ValueType};
+
+/// Difference between live and golden value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Deviation<'a> {
+    /// Index of package from start of run
+    pub package: usize,
+    /// Signal name
+    pub signal: &'a [u8],
+    /// Time position in package
+    pub slot: usize,
+    /// Value from golden capture, `None` if signal is absent in it
+    pub expected: Option<i32>,
+    /// Live value, `None` if signal is absent in live package
+    pub actual: Option<i32>,
+}
+
+/// Comparison of live packages with previously recorded golden capture.
+///
+/// Golden capture is a sequence of raw packages, e.g. captured on host or stored in flash
+pub struct GoldenRun<'g> {
+    golden: &'g [u8],
+    values: usize,
+    tolerance: f32,
+    package: usize,
+}
+
+impl<'g> GoldenRun<'g> {
+    /// Create comparator of packages with `values` values per signal.
+    /// Float values may differ no more than `tolerance`, int and bool values must be equal
+    pub const fn new(golden: &'g [u8], values: usize, tolerance: f32) -> Self {
+        Self {
+            golden,
+            values,
+            tolerance,
+            package: 0,
+        }
+    }
+
+    /// Number of compared packages
+    pub fn packages(&self) -> usize {
+        self.package
+    }
+
+    /// Compare `live` package with next golden package calling `f` on each deviation.
+    ///
+    /// Returns number of deviations in package
+    pub fn compare<'a, F>(&mut self, live: &PackageView<'a>, mut f: F) -> Result<usize, DecodeError>
+    where
+        'g: 'a,
+        F: FnMut(Deviation<'a>),
+    {
+        let (golden, rest) = PackageView::parse(self.golden, self.values)?;
+        self.golden = rest;
+        let package = self.package;
+        self.package += 1;
+
+        let mut count = 0;
+        let mut report = |signal, slot, expected, actual| {
+            count += 1;
+            f(Deviation {
+                package,
+                signal,
+                slot,
+                expected,
+                actual,
+            })
+        };
+        for g in golden.records() {
+            match live.records().find(|l| l.name() == g.name()) {
+                Some(l) => {
+                    for (slot, (e, a)) in g.values().zip(l.values()).enumerate() {
+                        if !self.matches(&g, e, a) {
+                            report(g.name(), slot, Some(e), Some(a));
+                        }
+                    }
+                }
+                None => report(g.name(), 0, g.get(0), None),
+            }
+        }
+        for l in live.records() {
+            if !golden.records().any(|g| g.name() == l.name()) {
+                report(l.name(), 0, None, l.get(0));
+            }
+        }
+        Ok(count)
+    }
+
+    fn matches(&self, record: &RecordView, expected: i32, actual: i32) -> bool {
+        match record.vtype() {
+            Some(ValueType::Float) => {
+                let (e, a) = (
+                    f32::from_bits(expected as u32),
+                    f32::from_bits(actual as u32),
+                );
+                (e.is_nan() && a.is_nan()) || e == a || abs(e - a) <= self.tolerance
+            }
+            _ => expected == actual,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+    const C: SVName = SVName::new("c");
+
+    fn package(a: [i32; 2], b: Option<f32>, c: bool) -> Buffer<512> {
+        let mut map = SVMap::<3, 2>::new();
+        let mut buf = Buffer::new();
+        for &a in &a {
+            map.set(&A, a).unwrap();
+            if let Some(b) = b {
+                map.set(&B, b).unwrap();
+            }
+            if c {
+                map.set(&C, true).unwrap();
+            }
+            map.next(|m| buf.send_package(&MODULE, m).unwrap());
+        }
+        buf
+    }
+
+    #[test]
+    fn deviations() {
+        let mut golden = Buffer::<1024>::new();
+        golden
+            .extend_from_slice(&package([1, 2], Some(0.5), false))
+            .unwrap();
+        golden
+            .extend_from_slice(&package([3, 4], Some(0.5), false))
+            .unwrap();
+        let mut run = GoldenRun::new(&golden, 2, 0.01);
+
+        // Float values within tolerance match
+        let live = package([1, 2], Some(0.505), false);
+        let (view, _) = PackageView::parse(&live, 2).unwrap();
+        assert_eq!(run.compare(&view, |d| panic!("{:?}", d)), Ok(0));
+
+        let live = package([3, 5], None, true);
+        let (view, _) = PackageView::parse(&live, 2).unwrap();
+        let mut found = [None; 3];
+        let mut i = 0;
+        assert_eq!(
+            run.compare(&view, |d| {
+                found[i] = Some(d);
+                i += 1;
+            }),
+            Ok(3)
+        );
+        let deviation = |signal, slot, expected, actual| {
+            Some(Deviation {
+                package: 1,
+                signal,
+                slot,
+                expected,
+                actual,
+            })
+        };
+        assert_eq!(
+            found,
+            [
+                deviation(b"a", 1, Some(4), Some(5)),
+                deviation(b"b", 0, Some(0.5f32.to_bits() as i32), None),
+                deviation(b"c", 0, None, Some(1)),
+            ]
+        );
+        assert_eq!(run.packages(), 2);
+        assert_eq!(run.compare(&view, |_| {}), Err(DecodeError::Incomplete));
+    }
+}
@@ -0,0 +1,439 @@
+use core::iter::once;
+
+use crate::protocol::{padded, write_header, write_trailer, Protocol, SVisual};
+
+use crate::{Name, SVMap, SendPackage, ValueType, WriteIter, NAME_SIZE};
+
+/// Marker of compressed package
+pub const MARKER: &[u8] = b"=xor=";
+
+/// Transport compressing `Float` signals of each package
+/// with XOR of neighbour values (Gorilla encoding).
+///
+/// Slowly changing analog signals shrink 2-4 times, other signals are sent as is.
+/// Receiver restores ordinary package with [`decompress`]
+pub struct Compressed<Tx> {
+    tx: Tx,
+}
+
+impl<Tx> Compressed<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self { tx }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+/// Sink of bits, most significant first
+struct BitWriter<F> {
+    out: F,
+    acc: u8,
+    bits: u32,
+    written: usize,
+}
+
+impl<E, F: FnMut(u8) -> Result<(), E>> BitWriter<F> {
+    fn new(out: F) -> Self {
+        Self {
+            out,
+            acc: 0,
+            bits: 0,
+            written: 0,
+        }
+    }
+
+    fn put(&mut self, value: u32, bits: u32) -> Result<(), E> {
+        for i in (0..bits).rev() {
+            self.acc = (self.acc << 1) | ((value >> i) & 1) as u8;
+            self.bits += 1;
+            if self.bits == 8 {
+                (self.out)(self.acc)?;
+                self.written += 1;
+                self.acc = 0;
+                self.bits = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pad last byte with zeros. Returns number of written bytes
+    fn finish(mut self) -> Result<usize, E> {
+        if self.bits > 0 {
+            (self.out)(self.acc << (8 - self.bits))?;
+            self.written += 1;
+        }
+        Ok(self.written)
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl BitReader<'_> {
+    fn get(&mut self, bits: u32) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = *self.bytes.get(self.pos / 8)?;
+            value = (value << 1) | ((byte >> (7 - self.pos % 8)) & 1) as u32;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+}
+
+/// Encode values: first value as is, then XOR with previous one.
+///
+/// Zero XOR is one `0` bit. Otherwise `1` is followed by `0` and meaningful bits
+/// if they fit in previous window, or by `1`, 5 bits of leading zeros count,
+/// 5 bits of meaningful bits count minus one and meaningful bits.
+/// Returns number of written bytes
+fn compress<E>(vals: &[i32], out: impl FnMut(u8) -> Result<(), E>) -> Result<usize, E> {
+    let mut w = BitWriter::new(out);
+    let mut prev = match vals.first() {
+        Some(&v) => v as u32,
+        None => return w.finish(),
+    };
+    w.put(prev, 32)?;
+    // Leading and trailing zeros of previous window
+    let mut window: Option<(u32, u32)> = None;
+    for &v in &vals[1..] {
+        let xor = v as u32 ^ prev;
+        prev = v as u32;
+        if xor == 0 {
+            w.put(0, 1)?;
+            continue;
+        }
+        let (lead, trail) = (xor.leading_zeros(), xor.trailing_zeros());
+        match window {
+            Some((wl, wt)) if lead >= wl && trail >= wt => {
+                w.put(0b10, 2)?;
+                w.put(xor >> wt, 32 - wl - wt)?;
+            }
+            _ => {
+                let len = 32 - lead - trail;
+                w.put(0b11, 2)?;
+                w.put(lead, 5)?;
+                w.put(len - 1, 5)?;
+                w.put(xor >> trail, len)?;
+                window = Some((lead, trail));
+            }
+        }
+    }
+    w.finish()
+}
+
+/// Size of compressed values in bytes
+fn compressed_size(vals: &[i32]) -> usize {
+    compress::<()>(vals, |_| Ok(())).unwrap_or(0)
+}
+
+/// Decode `count` values from `bytes` writing them in little-endian order to `out`
+fn expand(bytes: &[u8], count: usize, out: &mut [u8]) -> Option<()> {
+    let mut r = BitReader { bytes, pos: 0 };
+    let mut window = None;
+    let mut prev = 0;
+    for (i, chunk) in out.chunks_exact_mut(4).take(count).enumerate() {
+        let value = if i == 0 {
+            r.get(32)?
+        } else if r.get(1)? == 0 {
+            prev
+        } else {
+            if r.get(1)? == 1 {
+                let lead = r.get(5)?;
+                let len = r.get(5)? + 1;
+                if lead + len > 32 {
+                    return None;
+                }
+                window = Some((lead, 32 - lead - len));
+            }
+            let (wl, wt) = window?;
+            prev ^ (r.get(32 - wl - wt)? << wt)
+        };
+        chunk.copy_from_slice(&value.to_le_bytes());
+        prev = value;
+    }
+    Some(())
+}
+
+impl<Tx, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for Compressed<Tx>
+where
+    Tx: WriteIter,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        let payload = |vtype: ValueType, vals: &[i32; P]| match vtype {
+            ValueType::Float => 2 + compressed_size(vals),
+            _ => P * 4,
+        };
+        let notes = values.unique_notes().count();
+        let size = NAME_SIZE
+            + values
+                .map
+                .iter()
+                .map(|(_, v)| NAME_SIZE + 4 + payload(v.vtype, &v.vals))
+                .sum::<usize>()
+            + notes * (NAME_SIZE + 4 + P * 4);
+
+        let tx = &mut self.tx;
+        write_header::<SVisual, _>(tx, MARKER, size, module)?;
+        for (&name, v) in values.map.iter() {
+            tx.bwrite_iter(
+                padded::<SVisual, _>(name.bytes()).chain((v.vtype as i32).to_le_bytes()),
+            )?;
+            if v.vtype == ValueType::Float {
+                let len = compressed_size(&v.vals) as u16;
+                tx.bwrite_iter(len.to_le_bytes().iter().cloned())?;
+                compress(&v.vals, |b| tx.bwrite_iter(once(b)))?;
+            } else {
+                tx.bwrite_iter(v.vals.iter().flat_map(|val| val.to_le_bytes()))?;
+            }
+            tx.bflush()?;
+        }
+        for note in values.unique_notes() {
+            tx.bwrite_iter(
+                padded::<SVisual, _>(note.name())
+                    .chain((ValueType::Bool as i32).to_le_bytes())
+                    .chain(
+                        (0..P).flat_map(|slot| {
+                            (values.is_noted(&note.text, slot) as i32).to_le_bytes()
+                        }),
+                    ),
+            )?;
+        }
+        write_trailer::<SVisual, _>(tx)
+    }
+}
+
+/// Errors of package decompression
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecompressError {
+    /// More bytes are needed
+    Incomplete,
+    /// Package does not start with compressed package marker or does not end with `=end=`
+    InvalidMarker,
+    /// Invalid signal type or compressed values
+    Corrupted,
+    /// Output buffer is too small
+    Overflow,
+}
+
+/// Restore ordinary package with `values` values per signal from compressed `frame` into `out`.
+///
+/// Returns sizes of consumed frame and restored package
+pub fn decompress(
+    frame: &[u8],
+    values: usize,
+    out: &mut [u8],
+) -> Result<(usize, usize), DecompressError> {
+    use DecompressError::*;
+    let head = MARKER.len() + 4;
+    if frame.len() < head {
+        return Err(Incomplete);
+    }
+    if !frame.starts_with(MARKER) {
+        return Err(InvalidMarker);
+    }
+    let size = u32::from_le_bytes([frame[5], frame[6], frame[7], frame[8]]) as usize;
+    let end = head + size;
+    let body = frame.get(head..end).ok_or(Incomplete)?;
+    if frame.get(end..end + SVisual::END.len()).ok_or(Incomplete)? != SVisual::END {
+        return Err(InvalidMarker);
+    }
+    let module = body.get(..NAME_SIZE).ok_or(Corrupted)?;
+
+    let record = NAME_SIZE + 4 + values * 4;
+    let mut pos = put(out, 0, SVisual::BEGIN)?;
+    pos = put(out, pos, &[0; 4])?;
+    pos = put(out, pos, module)?;
+
+    let mut rest = &body[NAME_SIZE..];
+    let mut records = 0;
+    while !rest.is_empty() {
+        let header = rest.get(..NAME_SIZE + 4).ok_or(Corrupted)?;
+        let vtype = i32::from_le_bytes([
+            header[NAME_SIZE],
+            header[NAME_SIZE + 1],
+            header[NAME_SIZE + 2],
+            header[NAME_SIZE + 3],
+        ]);
+        let start = pos;
+        pos = put(out, pos, header)?;
+        rest = &rest[NAME_SIZE + 4..];
+        let vals = out.get_mut(pos..pos + values * 4).ok_or(Overflow)?;
+        rest = match vtype {
+            2 => {
+                let len = rest.get(..2).ok_or(Corrupted)?;
+                let len = u16::from_le_bytes([len[0], len[1]]) as usize;
+                let bytes = rest.get(2..2 + len).ok_or(Corrupted)?;
+                expand(bytes, values, vals).ok_or(Corrupted)?;
+                &rest[2 + len..]
+            }
+            0 | 1 => {
+                vals.copy_from_slice(rest.get(..values * 4).ok_or(Corrupted)?);
+                &rest[values * 4..]
+            }
+            _ => return Err(Corrupted),
+        };
+        pos = start + record;
+        records += 1;
+    }
+    let full_size = (NAME_SIZE + records * record) as u32;
+    let size_at = SVisual::BEGIN.len();
+    out[size_at..size_at + 4].copy_from_slice(&full_size.to_le_bytes());
+    pos = put(out, pos, SVisual::END)?;
+    Ok((end + SVisual::END.len(), pos))
+}
+
+/// Copy `bytes` to `out` at `pos`. Returns position after them
+pub(crate) fn put(out: &mut [u8], pos: usize, bytes: &[u8]) -> Result<usize, DecompressError> {
+    out.get_mut(pos..pos + bytes.len())
+        .ok_or(DecompressError::Overflow)?
+        .copy_from_slice(bytes);
+    Ok(pos + bytes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const INT: SVName = SVName::new("int");
+    const FLOAT: SVName = SVName::new("float");
+    const EVENT: SVName = SVName::new("event");
+
+    fn round_trip(vals: &[i32]) -> usize {
+        let mut bytes = Buffer::<256>::new();
+        let written = compress::<()>(vals, |b| bytes.push(b).map_err(drop)).unwrap();
+        assert_eq!(written, bytes.len());
+        assert_eq!(compressed_size(vals), written);
+        let mut out = [0; 256];
+        expand(&bytes, vals.len(), &mut out).unwrap();
+        let restored = out
+            .chunks_exact(4)
+            .take(vals.len())
+            .map(|c| i32::from_le_bytes([c[0], c[1], c[2], c[3]]));
+        assert!(restored.eq(vals.iter().cloned()));
+        written
+    }
+
+    #[test]
+    fn round_trips() {
+        assert_eq!(round_trip(&[]), 0);
+        assert_eq!(round_trip(&[-1]), 4);
+        // Every meaningful bit differs
+        round_trip(&[0, -1, 0, i32::MIN | 1]);
+        let vals = [
+            0.0f32,
+            0.1,
+            0.2,
+            0.3,
+            -1e10,
+            f32::MAX,
+            f32::MIN_POSITIVE,
+            0.3,
+        ];
+        round_trip(&vals.map(f32::to_bits).map(|b| b as i32));
+    }
+
+    #[test]
+    fn zero_xor() {
+        // First value and one bit for each repeated value
+        assert_eq!(round_trip(&[0x1234_5678; 8]), 5);
+        assert_eq!(round_trip(&[0x1234_5678; 33]), 8);
+    }
+
+    #[test]
+    fn window_reuse() {
+        // Second XOR fits window of the first one: 32 + (2+5+5+1) + (2+1) bits
+        assert_eq!(round_trip(&[0, 1, 0]), 6);
+        // XOR outside of window opens new one: 32 + (2+5+5+1) + (2+5+5+1) bits
+        assert_eq!(round_trip(&[0, 1, 3]), 8);
+        // Narrower XOR inside window reuses it: 32 + (2+5+5+4) + (2+4) bits
+        assert_eq!(round_trip(&[0, 0b1001, 0b1011]), 7);
+    }
+
+    fn frames() -> (Buffer<512>, Buffer<512>) {
+        let mut map = SVMap::<3, 8>::new();
+        for i in 0..8 {
+            map.set(&INT, i * 3).unwrap();
+            map.set(&FLOAT, 20. + (i / 2) as f32 * 0.5).unwrap();
+            if i == 5 {
+                map.annotate(&EVENT).unwrap();
+            }
+            if i < 7 {
+                map.next(|_| {});
+            }
+        }
+        let mut plain = Buffer::new();
+        plain.send_package(&MODULE, &map).unwrap();
+        let mut compressed = Compressed::new(Buffer::new());
+        compressed.send_package(&MODULE, &map).unwrap();
+        (plain, compressed.free())
+    }
+
+    #[test]
+    fn package() {
+        let (plain, compressed) = frames();
+        assert!(compressed.len() < plain.len());
+        let mut out = [0; 512];
+        let (consumed, len) = decompress(&compressed, 8, &mut out).unwrap();
+        assert_eq!(consumed, compressed.len());
+        assert_eq!(&out[..len], &plain[..]);
+    }
+
+    #[test]
+    fn truncated() {
+        let (_, compressed) = frames();
+        for len in [0, MARKER.len() + 3, MARKER.len() + 10, compressed.len() - 1] {
+            assert_eq!(
+                decompress(&compressed[..len], 8, &mut [0; 512]),
+                Err(DecompressError::Incomplete)
+            );
+        }
+        // Compressed values end before all values are restored
+        assert_eq!(expand(&[0; 4], 2, &mut [0; 8]), None);
+    }
+
+    #[test]
+    fn corrupted() {
+        let (_, mut compressed) = frames();
+        compressed[0] = b'x';
+        assert_eq!(
+            decompress(&compressed, 8, &mut [0; 512]),
+            Err(DecompressError::InvalidMarker)
+        );
+
+        // Unknown signal type of the first record
+        let (_, mut compressed) = frames();
+        let vtype = MARKER.len() + 4 + NAME_SIZE + NAME_SIZE;
+        compressed[vtype] = 0x7f;
+        assert_eq!(
+            decompress(&compressed, 8, &mut [0; 512]),
+            Err(DecompressError::Corrupted)
+        );
+
+        // Window does not fit in 32 bits: leading zeros 31, 2 meaningful bits
+        assert_eq!(
+            expand(&[0, 0, 0, 0, 0b1111_1110, 0b0100_0000], 2, &mut [0; 8]),
+            None
+        );
+        // Reuse of window before it is opened
+        assert_eq!(expand(&[0, 0, 0, 0, 0b1000_0000], 2, &mut [0; 8]), None);
+
+        let (_, compressed) = frames();
+        assert_eq!(
+            decompress(&compressed, 8, &mut [0; 64]),
+            Err(DecompressError::Overflow)
+        );
+    }
+}
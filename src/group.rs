@@ -0,0 +1,69 @@
+use crate::store::{Copied, SignalStore};
+use crate::{AddError, Name, SVStruct, Value};
+
+/// Signals of one subsystem, e.g. `motor/current` and `motor/speed`.
+///
+/// Joined names are made at runtime. [`DynMap`](crate::dynamic::DynMap) copies them
+/// on first update, maps with `&'static` names like [`SVMap`](crate::SVMap) update only
+/// signals registered before, e.g. by `svmap!` or with [`Name::join`] constants,
+/// and return `NotRegistered` for others
+pub struct Group<'a, S> {
+    map: &'a mut SVStruct<S>,
+    group: Name,
+}
+
+impl<S: SignalStore> SVStruct<S> {
+    /// Scoped setter prefixing signal names with `group/`
+    pub fn group(&mut self, group: Name) -> Group<'_, S> {
+        Group { map: self, group }
+    }
+}
+
+impl<S: SignalStore> Group<'_, S> {
+    /// Update value of signal `group/name` at current time position
+    pub fn set<T: Value>(&mut self, name: &Name, value: T) -> Result<(), AddError> {
+        self.map
+            .set(Copied(&Name::try_join(&self.group, name)?), value)
+    }
+
+    /// Nested group `group/subgroup`
+    pub fn group(&mut self, subgroup: &Name) -> Result<Group<'_, S>, AddError> {
+        Ok(Group {
+            group: Name::try_join(&self.group, subgroup)?,
+            map: self.map,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const MOTOR: Name = Name::new("motor");
+    const CURRENT: Name = Name::new("current");
+    const SPEED: Name = Name::new("speed");
+    const MOTOR_CURRENT: Name = Name::join(&MOTOR, &CURRENT);
+
+    #[test]
+    fn static_names() {
+        let mut map = SVMap::<2, 4>::new();
+        map.register::<f32>(&MOTOR_CURRENT).unwrap();
+        let mut motor = map.group(MOTOR);
+        motor.set(&CURRENT, 1.5).unwrap();
+        assert_eq!(motor.set(&SPEED, 1), Err(AddError::NotRegistered));
+        assert_eq!(map.get(&MOTOR_CURRENT), Some(1.5));
+    }
+
+    #[cfg(feature = "dynamic")]
+    #[test]
+    fn copied_names() {
+        let mut map = crate::dynamic::DynMap::<2, 4>::new();
+        map.group(MOTOR)
+            .group(&Name::new("left"))
+            .unwrap()
+            .set(&SPEED, 3)
+            .unwrap();
+        assert_eq!(map.get(&Name::new("motor/left/speed")), Some(3));
+    }
+}
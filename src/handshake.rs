@@ -0,0 +1,209 @@
+use core::iter::once;
+
+use crate::decoder::{trim, DecodeError};
+use crate::protocol::{padded, write_trailer, Protocol, SVisual};
+use crate::{Name, WriteIter, NAME_SIZE};
+
+/// Marker of handshake frame
+pub const MARKER: &[u8] = b"=hello=";
+
+/// Version of protocol with extensions of this crate
+pub const PROTOCOL_VERSION: u16 = 2;
+
+/// Size of handshake frame
+pub const FRAME_SIZE: usize = MARKER.len() + NAME_SIZE + 11 + SVisual::END.len();
+
+/// Set of protocol extensions used by client
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities(pub u32);
+
+impl Capabilities {
+    /// Plain packages only
+    pub const NONE: Self = Self(0);
+    /// Checksum trailer after each package
+    pub const CHECKSUM: Self = Self(1);
+    /// Timestamp record in each package
+    pub const TIMESTAMP: Self = Self(1 << 1);
+    /// Sequence number records in each package
+    pub const SEQUENCE: Self = Self(1 << 2);
+    /// Compressed packages
+    pub const COMPRESSION: Self = Self(1 << 3);
+    /// Encrypted packages
+    pub const ENCRYPTION: Self = Self(1 << 4);
+    /// COBS or escaping framing
+    pub const FRAMING: Self = Self(1 << 5);
+    /// Alarm frames
+    pub const ALARMS: Self = Self(1 << 6);
+    /// Annotation records
+    pub const NOTES: Self = Self(1 << 7);
+    /// Client accepts command frames
+    pub const COMMANDS: Self = Self(1 << 8);
+
+    /// Checks if all extensions of `other` are used
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for Capabilities {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Startup message advertising protocol version and used extensions,
+/// so receiver can adapt instead of misparsing extended packages
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hello {
+    /// Number of values per signal in package
+    pub values: u16,
+    /// Used extensions
+    pub capabilities: Capabilities,
+    /// Identifier of checksum algorithm of package trailers, 0 if packages are not checked
+    pub checksum: u8,
+}
+
+impl Hello {
+    /// Handshake of client sending `values` values per signal
+    pub const fn new(values: u16, capabilities: Capabilities) -> Self {
+        Self {
+            values,
+            capabilities,
+            checksum: 0,
+        }
+    }
+
+    /// Advertise checksum trailers of algorithm `id`, e.g. [`Crc32::ID`](crate::checksum::Crc32)
+    pub const fn with_checksum(mut self, id: u8) -> Self {
+        self.capabilities = Capabilities(self.capabilities.0 | Capabilities::CHECKSUM.0);
+        self.checksum = id;
+        self
+    }
+
+    /// Send handshake frame of `module`
+    pub fn send<Tx: WriteIter + ?Sized>(
+        &self,
+        tx: &mut Tx,
+        module: &Name,
+    ) -> Result<(), Tx::Error> {
+        tx.bwrite_iter(
+            MARKER
+                .iter()
+                .cloned()
+                .chain(padded::<SVisual, _>(module.bytes()))
+                .chain(PROTOCOL_VERSION.to_le_bytes())
+                .chain((NAME_SIZE as u16).to_le_bytes())
+                .chain(self.values.to_le_bytes())
+                .chain(self.capabilities.0.to_le_bytes())
+                .chain(once(self.checksum)),
+        )?;
+        write_trailer::<SVisual, _>(tx)
+    }
+}
+
+/// Borrowed view of handshake frame
+#[derive(Clone, Copy, Debug)]
+pub struct HelloView<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> HelloView<'a> {
+    /// Parse handshake frame at the start of `bytes`.
+    ///
+    /// Returns frame view and the rest of bytes after the frame
+    pub fn parse(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), DecodeError> {
+        if bytes.len() < FRAME_SIZE {
+            return Err(if bytes.starts_with(MARKER) || MARKER.starts_with(bytes) {
+                DecodeError::Incomplete
+            } else {
+                DecodeError::InvalidMarker
+            });
+        }
+        if !bytes.starts_with(MARKER) || !bytes[..FRAME_SIZE].ends_with(SVisual::END) {
+            return Err(DecodeError::InvalidMarker);
+        }
+        Ok((
+            Self {
+                bytes: &bytes[..FRAME_SIZE],
+            },
+            &bytes[FRAME_SIZE..],
+        ))
+    }
+
+    fn field(&self, offset: usize) -> &'a [u8] {
+        &self.bytes[MARKER.len() + NAME_SIZE + offset..]
+    }
+
+    fn u16(&self, offset: usize) -> u16 {
+        let b = self.field(offset);
+        u16::from_le_bytes([b[0], b[1]])
+    }
+
+    /// Module name bytes without padding
+    pub fn module(&self) -> &'a [u8] {
+        trim(&self.bytes[MARKER.len()..MARKER.len() + NAME_SIZE])
+    }
+
+    /// Protocol version of client
+    pub fn version(&self) -> u16 {
+        self.u16(0)
+    }
+
+    /// Size of name field in packages
+    pub fn name_size(&self) -> u16 {
+        self.u16(2)
+    }
+
+    /// Handshake data
+    pub fn hello(&self) -> Hello {
+        let c = self.field(6);
+        Hello {
+            values: self.u16(4),
+            capabilities: Capabilities(u32::from_le_bytes([c[0], c[1], c[2], c[3]])),
+            checksum: c[4],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::checksum::{Checksum, Crc32};
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+
+    #[test]
+    fn round_trip() {
+        let hello =
+            Hello::new(10, Capabilities::SEQUENCE | Capabilities::NOTES).with_checksum(Crc32::ID);
+        assert!(hello.capabilities.contains(Capabilities::CHECKSUM));
+        assert!(!hello.capabilities.contains(Capabilities::ALARMS));
+        let mut buf = Buffer::<256>::new();
+        hello.send(&mut buf, &MODULE).unwrap();
+        buf.extend_from_slice(b"=begin=").unwrap();
+        assert_eq!(buf.len(), FRAME_SIZE + 7);
+
+        let (view, rest) = HelloView::parse(&buf).unwrap();
+        assert_eq!(view.module(), b"module");
+        assert_eq!(view.version(), PROTOCOL_VERSION);
+        assert_eq!(view.name_size() as usize, NAME_SIZE);
+        assert_eq!(view.hello(), hello);
+        assert_eq!(rest, b"=begin=");
+
+        assert_eq!(
+            HelloView::parse(&buf[..FRAME_SIZE - 1]).map(|_| ()),
+            Err(DecodeError::Incomplete)
+        );
+        assert_eq!(
+            HelloView::parse(&buf[..3]).map(|_| ()),
+            Err(DecodeError::Incomplete)
+        );
+        assert_eq!(
+            HelloView::parse(rest).map(|_| ()),
+            Err(DecodeError::InvalidMarker)
+        );
+    }
+}
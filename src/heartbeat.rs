@@ -0,0 +1,82 @@
+use crate::{write_package, Name, ValueType, WriteIter};
+
+/// Send keepalive package: package of `module` without signals
+pub fn send_keepalive<Tx: WriteIter + ?Sized>(tx: &mut Tx, module: &Name) -> Result<(), Tx::Error> {
+    write_package(
+        tx,
+        module,
+        0,
+        core::iter::empty::<(&str, ValueType, &[i32])>(),
+        &[],
+    )
+}
+
+/// Keepalive sender for change-triggered sending, so receiver does not treat
+/// silent module as disconnected.
+///
+/// Keepalive is sent when no package was sent for `interval` ticks
+#[derive(Clone, Copy, Debug)]
+pub struct Heartbeat {
+    interval: u32,
+    idle: u32,
+}
+
+impl Heartbeat {
+    /// Create sender with interval in ticks (calls of `next`)
+    pub const fn new(interval: u32) -> Self {
+        Self { interval, idle: 0 }
+    }
+
+    /// Number of ticks since last package or keepalive
+    pub fn idle(&self) -> u32 {
+        self.idle
+    }
+
+    /// Call on each tick after `next`, `sent` is `true` if package was sent on this tick.
+    ///
+    /// Returns `true` if keepalive was sent
+    pub fn poll<Tx: WriteIter + ?Sized>(
+        &mut self,
+        tx: &mut Tx,
+        module: &Name,
+        sent: bool,
+    ) -> Result<bool, Tx::Error> {
+        if sent {
+            self.idle = 0;
+            return Ok(false);
+        }
+        self.idle += 1;
+        if self.idle < self.interval {
+            return Ok(false);
+        }
+        self.idle = 0;
+        send_keepalive(tx, module)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+
+    #[test]
+    fn keepalive_after_silence() {
+        let mut heartbeat = Heartbeat::new(3);
+        let mut tx = Mock::<256>::new();
+        let mut sent = [false; 8];
+        for (i, s) in sent.iter_mut().enumerate() {
+            *s = heartbeat.poll(&mut tx, &MODULE, i == 3).unwrap();
+        }
+        assert_eq!(sent, [false, false, true, false, false, false, true, false]);
+        assert_eq!(heartbeat.idle(), 1);
+
+        let mut expected = Mock::<256>::new();
+        send_keepalive(&mut expected, &MODULE).unwrap();
+        send_keepalive(&mut expected, &MODULE).unwrap();
+        assert_eq!(tx.as_slice(), expected.as_slice());
+    }
+}
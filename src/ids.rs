@@ -0,0 +1,300 @@
+use heapless::Vec;
+
+use crate::gorilla::{put, DecompressError};
+use crate::protocol::{padded, write_header, write_trailer, Protocol, SVisual};
+use crate::{write_package_with, Name, SVMap, SendPackage, ValueType, WriteIter, NAME_SIZE};
+
+/// Marker of dictionary frame
+pub const DICT_MARKER: &[u8] = b"=dict=";
+/// Marker of package with numeric signal identifiers
+pub const MARKER: &[u8] = b"=ids=";
+/// Identifier of annotation record, followed by its name
+pub const NOTE_ID: u16 = 0xFFFF;
+
+/// Compile-time check that `N` signals get identifiers other than [`NOTE_ID`]
+struct Ids<const N: usize>;
+
+impl<const N: usize> Ids<N> {
+    const VALID: () = assert!(N < NOTE_ID as usize, "N must be less than NOTE_ID");
+}
+
+/// Dictionary frame is package without values with own marker
+struct Dict;
+
+impl Protocol for Dict {
+    const BEGIN: &'static [u8] = DICT_MARKER;
+    const END: &'static [u8] = SVisual::END;
+    const PADDING: u8 = SVisual::PADDING;
+}
+
+/// Transport referring to signals by 2-byte identifiers instead of names.
+///
+/// Names and types are sent once in dictionary frame: package without values
+/// with `=dict=` marker, identifier is position of signal in it.
+/// Package is `=ids=`, size (`u32`), module name and records of identifier (`u16`)
+/// and values. Dictionary is resent when signals are added or removed or on [`refresh`](Self::refresh).
+/// Receiver restores ordinary packages with [`Dictionary`]
+pub struct Indexed<Tx> {
+    tx: Tx,
+    /// Generation of signal set sent in last dictionary
+    layout: Option<u32>,
+}
+
+impl<Tx> Indexed<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self { tx, layout: None }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+
+    /// Resend dictionary with next package, e.g. when receiver reconnects
+    /// or map was changed directly through `DerefMut`
+    pub fn refresh(&mut self) {
+        self.layout = None;
+    }
+}
+
+impl<Tx, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for Indexed<Tx>
+where
+    Tx: WriteIter,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        #[allow(clippy::let_unit_value)]
+        let () = Ids::<N>::VALID;
+        let tx = &mut self.tx;
+        if self.layout != Some(values.layout) {
+            write_package_with::<Dict, _, _>(
+                tx,
+                module,
+                0,
+                values.map.iter().map(|(&name, v)| (name, v.vtype, &[][..])),
+                &[],
+            )?;
+            self.layout = Some(values.layout);
+        }
+
+        let notes = values.unique_notes().count();
+        let size = NAME_SIZE + values.map.len() * (2 + P * 4) + notes * (2 + NAME_SIZE + P * 4);
+        write_header::<SVisual, _>(tx, MARKER, size, module)?;
+        for (id, (_, v)) in values.map.iter().enumerate() {
+            tx.bwrite_iter(
+                (id as u16)
+                    .to_le_bytes()
+                    .iter()
+                    .cloned()
+                    .chain(v.vals.iter().flat_map(|val| val.to_le_bytes())),
+            )?;
+            tx.bflush()?;
+        }
+        for note in values.unique_notes() {
+            tx.bwrite_iter(
+                NOTE_ID
+                    .to_le_bytes()
+                    .iter()
+                    .cloned()
+                    .chain(padded::<SVisual, _>(note.name()))
+                    .chain(
+                        (0..P).flat_map(|slot| {
+                            (values.is_noted(&note.text, slot) as i32).to_le_bytes()
+                        }),
+                    ),
+            )?;
+        }
+        write_trailer::<SVisual, _>(tx)
+    }
+}
+
+/// Receiver side dictionary of up to `N` signals of one module
+#[derive(Clone, Debug, Default)]
+pub struct Dictionary<const N: usize> {
+    signals: Vec<([u8; NAME_SIZE], ValueType), N>,
+}
+
+impl<const N: usize> Dictionary<N> {
+    /// Create empty dictionary
+    pub const fn new() -> Self {
+        Self {
+            signals: Vec::new(),
+        }
+    }
+
+    /// Number of known signals
+    pub fn len(&self) -> usize {
+        self.signals.len()
+    }
+
+    /// Checks if dictionary is not received yet
+    pub fn is_empty(&self) -> bool {
+        self.signals.is_empty()
+    }
+
+    /// Replace dictionary with dictionary frame at the start of `frame`.
+    ///
+    /// Returns size of frame
+    pub fn update(&mut self, frame: &[u8]) -> Result<usize, DecompressError> {
+        use crate::decoder::{DecodeError, PackageView};
+        let (view, rest) = PackageView::parse_with::<Dict>(frame, 0).map_err(|e| match e {
+            DecodeError::Incomplete => DecompressError::Incomplete,
+            DecodeError::InvalidMarker => DecompressError::InvalidMarker,
+            _ => DecompressError::Corrupted,
+        })?;
+        self.signals.clear();
+        for record in view.records() {
+            let mut name = [0; NAME_SIZE];
+            name[..record.name().len()].copy_from_slice(record.name());
+            let vtype = record.vtype().ok_or(DecompressError::Corrupted)?;
+            self.signals
+                .push((name, vtype))
+                .map_err(|_| DecompressError::Overflow)?;
+        }
+        Ok(frame.len() - rest.len())
+    }
+
+    /// Restore ordinary package with `values` values per signal from `frame` into `out`.
+    ///
+    /// Returns sizes of consumed frame and restored package
+    pub fn expand(
+        &self,
+        frame: &[u8],
+        values: usize,
+        out: &mut [u8],
+    ) -> Result<(usize, usize), DecompressError> {
+        use DecompressError::*;
+        let head = MARKER.len() + 4;
+        if frame.len() < head {
+            return Err(Incomplete);
+        }
+        if !frame.starts_with(MARKER) {
+            return Err(InvalidMarker);
+        }
+        let s = &frame[MARKER.len()..head];
+        let size = u32::from_le_bytes([s[0], s[1], s[2], s[3]]) as usize;
+        let end = head + size;
+        let body = frame.get(head..end).ok_or(Incomplete)?;
+        if frame.get(end..end + SVisual::END.len()).ok_or(Incomplete)? != SVisual::END {
+            return Err(InvalidMarker);
+        }
+        let module = body.get(..NAME_SIZE).ok_or(Corrupted)?;
+
+        let record = NAME_SIZE + 4 + values * 4;
+        let mut pos = put(out, 0, SVisual::BEGIN)?;
+        pos = put(out, pos, &[0; 4])?;
+        pos = put(out, pos, module)?;
+
+        let mut rest = &body[NAME_SIZE..];
+        let mut records = 0;
+        while !rest.is_empty() {
+            let id = rest.get(..2).ok_or(Corrupted)?;
+            let id = u16::from_le_bytes([id[0], id[1]]);
+            rest = &rest[2..];
+            if id == NOTE_ID {
+                pos = put(out, pos, rest.get(..NAME_SIZE).ok_or(Corrupted)?)?;
+                pos = put(out, pos, &(ValueType::Bool as i32).to_le_bytes())?;
+                rest = &rest[NAME_SIZE..];
+            } else {
+                let (name, vtype) = self.signals.get(id as usize).ok_or(Corrupted)?;
+                pos = put(out, pos, name)?;
+                pos = put(out, pos, &(*vtype as i32).to_le_bytes())?;
+            }
+            pos = put(out, pos, rest.get(..values * 4).ok_or(Corrupted)?)?;
+            rest = &rest[values * 4..];
+            records += 1;
+        }
+        let full_size = (NAME_SIZE + records * record) as u32;
+        let size_at = SVisual::BEGIN.len();
+        out[size_at..size_at + 4].copy_from_slice(&full_size.to_le_bytes());
+        pos = put(out, pos, SVisual::END)?;
+        Ok((end + SVisual::END.len(), pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+    const NOTE: SVName = SVName::new("note");
+
+    #[test]
+    fn round_trip() {
+        let mut map = SVMap::<4, 2>::new();
+        map.set(&A, 1).unwrap();
+        map.set(&B, 2.5).unwrap();
+        map.annotate(&NOTE).unwrap();
+        let mut tx = Indexed::new(Buffer::<2048>::new());
+        tx.send_package(&MODULE, &map).unwrap();
+        // Dictionary is sent once
+        tx.send_package(&MODULE, &map).unwrap();
+        let frames = tx.free();
+        let mut plain = Buffer::<2048>::new();
+        plain.send_package(&MODULE, &map).unwrap();
+
+        let mut dict = Dictionary::<4>::new();
+        let mut out = [0; 2048];
+        assert_eq!(
+            dict.expand(&frames, 2, &mut out),
+            Err(DecompressError::InvalidMarker)
+        );
+        let size = dict.update(&frames).unwrap();
+        assert_eq!(dict.len(), 2);
+        let mut rest = &frames[size..];
+        for _ in 0..2 {
+            let (consumed, len) = dict.expand(rest, 2, &mut out).unwrap();
+            assert_eq!(&out[..len], &plain[..]);
+            rest = &rest[consumed..];
+        }
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn dictionary_resent_on_layout_change() {
+        let mut map = SVMap::<4, 2>::new();
+        map.set(&A, 1).unwrap();
+        let mut tx = Indexed::new(Buffer::<2048>::new());
+        tx.send_package(&MODULE, &map).unwrap();
+        let first = tx.free();
+        let mut tx = Indexed::new(Buffer::<2048>::new());
+        tx.send_package(&MODULE, &map).unwrap();
+        tx.send_package(&MODULE, &map).unwrap();
+        map.set(&B, true).unwrap();
+        tx.send_package(&MODULE, &map).unwrap();
+        tx.refresh();
+        tx.send_package(&MODULE, &map).unwrap();
+        let frames = tx.free();
+
+        let count = |bytes: &[u8]| {
+            bytes
+                .windows(DICT_MARKER.len())
+                .filter(|w| *w == DICT_MARKER)
+                .count()
+        };
+        assert_eq!(count(&first), 1);
+        assert_eq!(count(&frames), 3);
+
+        // Dictionary without signal `b`
+        let mut dict = Dictionary::<4>::new();
+        dict.update(&first).unwrap();
+        let mut tx = Indexed::new(Buffer::<2048>::new());
+        tx.send_package(&MODULE, &map).unwrap();
+        let both = tx.free();
+        let size = Dictionary::<4>::new().update(&both).unwrap();
+        assert_eq!(
+            dict.expand(&both[size..], 2, &mut [0; 2048]),
+            Err(DecompressError::Corrupted)
+        );
+        assert_eq!(
+            Dictionary::<1>::new().update(&both),
+            Err(DecompressError::Overflow)
+        );
+    }
+}
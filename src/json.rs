@@ -0,0 +1,116 @@
+use core::fmt::Write;
+
+use crate::{Name, SVMap, Sample, SendPackage, ValueType};
+
+/// Encoder writing each package as one line of JSON into text writer,
+/// e.g. terminal or web dashboard next to binary stream:
+/// `{"module":"m","signals":[{"name":"a","type":"int","values":[1,2]}],"notes":[]}`.
+///
+/// Non-finite float values are written as `null`
+pub struct Json<W> {
+    writer: W,
+}
+
+impl<W: Write> Json<W> {
+    /// Wrap writer
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Release writer
+    pub fn free(self) -> W {
+        self.writer
+    }
+}
+
+/// Write JSON string with escaped characters
+fn write_str<W: Write>(w: &mut W, s: &str) -> core::fmt::Result {
+    w.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            c if (c as u32) < 0x20 => write!(w, "\\u{:04x}", c as u32)?,
+            c => w.write_char(c)?,
+        }
+    }
+    w.write_char('"')
+}
+
+impl<W: Write, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for Json<W> {
+    type Error = core::fmt::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        let w = &mut self.writer;
+        w.write_str("{\"module\":")?;
+        write_str(w, module)?;
+        w.write_str(",\"signals\":[")?;
+        for (i, (name, v)) in values.map.iter().enumerate() {
+            if i != 0 {
+                w.write_char(',')?;
+            }
+            w.write_str("{\"name\":")?;
+            write_str(w, name)?;
+            w.write_str(match v.vtype {
+                ValueType::Bool => ",\"type\":\"bool\",\"values\":[",
+                ValueType::Int => ",\"type\":\"int\",\"values\":[",
+                ValueType::Float => ",\"type\":\"float\",\"values\":[",
+            })?;
+            for (slot, s) in v.samples().enumerate() {
+                if slot != 0 {
+                    w.write_char(',')?;
+                }
+                match s {
+                    Sample::Bool(b) => write!(w, "{}", b)?,
+                    Sample::Int(i) => write!(w, "{}", i)?,
+                    Sample::Float(f) if f.is_finite() => write!(w, "{}", f)?,
+                    Sample::Float(_) => w.write_str("null")?,
+                }
+            }
+            w.write_str("]}")?;
+        }
+        w.write_str("],\"notes\":[")?;
+        for (i, note) in values.notes.iter().flatten().enumerate() {
+            if i != 0 {
+                w.write_char(',')?;
+            }
+            w.write_str("{\"text\":")?;
+            write_str(w, &note.text)?;
+            write!(w, ",\"slot\":{}}}", note.slot)?;
+        }
+        w.write_str("]}\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use heapless::String;
+
+    const MODULE: SVName = SVName::new("mod\"ule");
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+    const NOTE: SVName = SVName::new("start");
+
+    #[test]
+    fn package_line() {
+        let mut map = SVMap::<2, 2>::new();
+        let mut json = Json::new(String::<256>::new());
+        map.set(&A, 1.5).unwrap();
+        map.set(&B, true).unwrap();
+        map.next(|_| {});
+        map.set(&A, f32::NAN).unwrap();
+        map.set(&B, false).unwrap();
+        map.annotate(&NOTE).unwrap();
+        map.next(|m| json.send_package(&MODULE, m).unwrap());
+        assert_eq!(
+            json.free().as_str(),
+            "{\"module\":\"mod\\\"ule\",\"signals\":[\
+             {\"name\":\"a\",\"type\":\"float\",\"values\":[1.5,null]},\
+             {\"name\":\"b\",\"type\":\"bool\",\"values\":[true,false]}],\
+             \"notes\":[{\"text\":\"start\",\"slot\":1}]}\n"
+        );
+    }
+}
@@ -1,22 +1,313 @@
 //! Embedded client of [SVisual](https://github.com/Tyill/SVisual/) monitor
 //!
-//! Requires Rust 1.51
+//! Requires Rust 1.59
 
 #![no_std]
 #![deny(missing_docs)]
+#![cfg_attr(
+    feature = "nightly",
+    feature(generic_const_exprs),
+    allow(incomplete_features)
+)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+/// Acknowledged delivery of packages
+pub mod ack;
+/// Alarms on signal limits
+pub mod alarm;
+/// In-memory transport
+pub mod buffer;
+/// Map shared with interrupt handlers
+pub mod cell;
+/// Package checksums
+pub mod checksum;
+/// Splitting of frames into fixed size chunks
+pub mod chunk;
+/// Package timestamps
+#[cfg(feature = "timestamp")]
+pub mod clock;
+/// Consistent Overhead Byte Stuffing framing
+pub mod cobs;
+/// Commands from host
+pub mod command;
+/// Signals computed from other signals
+pub mod computed;
+/// Sending of in-flight package on crash
+pub mod crash;
+/// Authenticated encryption of packages
+#[cfg(feature = "crypto")]
+pub mod crypto;
+/// CSV export of packages
+pub mod csv;
+/// Zero-copy package decoder
+pub mod decoder;
+/// Delta encoding of signal values
+pub mod delta;
+/// Routing of packages by module name
+pub mod demux;
+/// Diagnostic signals of link health
+pub mod diag;
+/// Full rate and decimated streams of one map
+pub mod dual;
+/// Maps with signal names generated at runtime
+#[cfg(feature = "dynamic")]
+pub mod dynamic;
+/// Object-safe sender for transport selected at runtime
+pub mod erased;
+/// Escaping of framing markers
+pub mod escape;
+/// Maps with caller-provided storage
+pub mod external;
+/// Switching to backup transport
+pub mod failover;
+/// Sending packages to several transports
+pub mod fanout;
+/// Fixed signal sets indexed by enum
+pub mod fixed;
+/// Configurable flushing of transport
+pub mod flush;
+/// Comparison with golden capture for regression testing
+pub mod golden;
+/// XOR compression of float signals
+pub mod gorilla;
+/// Signals of subsystem with common name prefix
+pub mod group;
+/// Protocol version handshake
+pub mod handshake;
+/// Keepalive packages
+pub mod heartbeat;
+/// Numeric signal identifiers instead of names
+pub mod ids;
+/// JSON encoder of packages for debugging
+#[cfg(feature = "json")]
+pub mod json;
+/// Link quality analysis
+pub mod link;
+/// `log` adapter recording key-values as signals
+#[cfg(feature = "log")]
+pub mod logger;
+/// Self test of package encoding
+pub mod loopback;
+/// LZ compression of whole packages
+#[cfg(feature = "lz")]
+pub mod lz;
+/// Message authentication of packages
+#[cfg(feature = "crypto")]
+pub mod mac;
+/// In-memory transport with error injection for unit tests
+pub mod mock;
+/// Several logical modules in one client
+pub mod modules;
+/// Containers bound to module name
+pub mod named;
+/// Postcard encoding of packages
+pub mod postcard;
 /// Prelude module for easy import
 pub mod prelude;
+/// Packages over defmt channel
+#[cfg(feature = "defmt")]
+pub mod probe;
+/// Signal transformation profiles
+pub mod profile;
+/// Wire format constants for forks of SVisual server
+pub mod protocol;
+/// Queue of updates from interrupt handlers
+pub mod queue;
+/// Signal groups with own tick rates
+pub mod rate;
+/// Recording of packages into files and replay
+pub mod record;
+/// LFSR whitening of transmitted bytes
+pub mod scrambler;
+/// Package sequence numbers
+pub mod seq;
+/// Packages of changed signals only
+pub mod sparse;
+/// Offline saving of packages into storage
+pub mod spool;
+/// Detection of signals that stopped updating
+pub mod stale;
+/// Derived statistics of signals
+pub mod stats;
+/// Persistence of signal set
+pub mod storage;
+/// Pluggable storage of signal records
+pub mod store;
+/// Thresholds with automatic flag signals
+pub mod threshold;
+/// Timer driven advance of time positions
+pub mod timer;
+/// Composition of transport layers
+pub mod transport;
+/// Capture of packages around trigger event
+pub mod trigger;
+/// Conditions over signals
+pub mod watch;
+
+#[cfg(feature = "derive")]
+pub use svisual_derive::SvSignals;
 
+use core::marker::PhantomData;
 use embedded_hal::serial::Write;
 use heapless::LinearMap;
-use nb;
+use store::{Indexed, NewSignal, SignalKey, SignalStore, Static};
 
 /// Boolean signal that shows only positive front impulses
 pub struct OnlyFront(pub bool);
 
+/// Boolean signal that shows only negative front impulses
+pub struct OnlyFall(pub bool);
+
+/// Boolean signal that shows impulse on each change of level
+pub struct BothEdges(pub bool);
+
+/// Level of boolean input which rising edges are counted per time position.
+///
+/// Captures pulses that are too fast to be seen by sampling once per time position
+pub struct PulseCount(pub bool);
+
+/// Thresholds of analog value of type `T`, e.g. `f32` or raw `u16` ADC reading,
+/// converted to boolean with hysteresis
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoolFromAnalog<T = f32> {
+    /// Level becomes `true` when value reaches this threshold
+    pub rising: T,
+    /// Level becomes `false` when value drops to this threshold
+    pub falling: T,
+}
+
+impl<T: Copy + PartialOrd> BoolFromAnalog<T> {
+    /// New level from previous `level` and analog `value`
+    pub fn level(&self, level: bool, value: T) -> bool {
+        if level {
+            value > self.falling
+        } else {
+            value >= self.rising
+        }
+    }
+}
+
+/// Condition of sending full package
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SendMode {
+    /// Send every package
+    Always,
+    /// Send package only if any signal changed since previous package or it has annotations.
+    /// Unchanged package is still sent after `max_silence` skipped ones, 0 is no limit
+    OnChange {
+        /// Maximum number of skipped packages in a row
+        max_silence: u32,
+    },
+}
+
+/// Band around recorded value where changes are treated as noise
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Deadband {
+    /// No filtering
+    Off,
+    /// Absolute difference
+    Absolute(f32),
+    /// Difference in percents of recorded value
+    Percent(f32),
+}
+
+impl Deadband {
+    /// Checks if `new` value differs from recorded `old` value more than band
+    pub fn exceeded(&self, old: f32, new: f32) -> bool {
+        let band = match *self {
+            Self::Off => return true,
+            Self::Absolute(band) => band,
+            Self::Percent(percent) => abs(old) * percent / 100.,
+        };
+        abs(new - old) > band
+    }
+}
+
+fn abs(x: f32) -> f32 {
+    if x < 0. {
+        -x
+    } else {
+        x
+    }
+}
+
+/// Combination of several updates of signal at one time position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Aggregate {
+    /// Keep last value
+    Last,
+    /// Keep minimal value, `false` if any update is `false` for bool signal
+    Min,
+    /// Keep maximal value, `true` if any update is `true` for bool signal,
+    /// so spikes between time positions are not lost
+    Max,
+    /// Keep mean value, last value for bool signal
+    Mean,
+}
+
+/// Handling of NaN and infinite `f32` values.
+///
+/// Policy is applied before alarm limits, thresholds and profile, so they see replaced value.
+/// Held value is not checked again
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NonFinite {
+    /// Send value as is. NaN and infinity are outside of any alarm limit
+    Pass,
+    /// Replace infinity with `f32::MAX`/`f32::MIN` and NaN with 0
+    Clamp,
+    /// Keep previous value
+    Hold,
+    /// Keep previous value and show impulse on specified bool signal
+    Flag(&'static Name),
+}
+
+/// Behavior of signal on next time position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Front {
+    /// Hold previous value
+    Hold = 0,
+    /// Reset to 0, so only positive fronts are shown
+    Rise = 1,
+    /// Reset to 1, so only negative fronts are shown
+    Fall = 2,
+    /// Reset to 0, impulse is shown on each level change
+    Both = 3,
+    /// Reset to 0, rising edges are counted
+    Count = 4,
+}
+
+impl core::convert::TryFrom<u8> for Front {
+    type Error = ();
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Hold),
+            1 => Ok(Self::Rise),
+            2 => Ok(Self::Fall),
+            3 => Ok(Self::Both),
+            4 => Ok(Self::Count),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Raw value scaled as `raw * NUM / DEN + OFFSET` before transmission.
+///
+/// `Scaled<i32, ..>` uses integer math and is sent as `Int`,
+/// `Scaled<f32, ..>` is sent as `Float`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scaled<T, const NUM: i32, const DEN: i32, const OFFSET: i32 = 0>(pub T);
+
 /// Types supported by SVisual
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[repr(u8)]
 pub enum ValueType {
     /// Boolean value
@@ -27,30 +318,331 @@ pub enum ValueType {
     Float = 2,
 }
 
-/// Value Record. Contents values of 1 signal. `P` is package size
+impl core::convert::TryFrom<u8> for ValueType {
+    type Error = ();
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Bool),
+            1 => Ok(Self::Int),
+            2 => Ok(Self::Float),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Decoded value of signal
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Sample {
+    /// Boolean value
+    Bool(bool),
+    /// `i32` value
+    Int(i32),
+    /// `f32` value
+    Float(f32),
+}
+
+impl Sample {
+    /// Decode `i32` representation of value of type `vtype`
+    pub fn new(vtype: ValueType, raw: i32) -> Self {
+        match vtype {
+            ValueType::Bool => Self::Bool(raw != 0),
+            ValueType::Int => Self::Int(raw),
+            ValueType::Float => Self::Float(f32::from_bits(raw as u32)),
+        }
+    }
+
+    /// Value as `f32`, bool is 0 or 1
+    pub fn as_f32(self) -> f32 {
+        match self {
+            Self::Bool(b) => b as i32 as f32,
+            Self::Int(i) => i as f32,
+            Self::Float(f) => f,
+        }
+    }
+}
+
+/// Value Record. Contents values of 1 signal, e.g. `[i32; P]` where `P` is package size
 #[derive(Clone)]
-pub struct ValueRec<const P: usize> {
-    /// Only positive front
-    is_only_front: bool,
+pub struct ValueRec<V> {
+    front: Front,
+    /// Last level of `Front::Both` and `Front::Count` signal
+    level: bool,
     vtype: ValueType,
-    vals: [i32; P],
+    vals: V,
+    /// Last value of last sent package
+    sent: i32,
+    /// Signal is recorded every `decimation` time positions
+    decimation: u16,
+    /// Time positions since last recorded one
+    phase: u16,
+    aggregate: Aggregate,
+    /// Number of updates at current time position
+    updates: u16,
+    deadband: Deadband,
+    /// Signal was set since start of current package
+    fresh: bool,
 }
 
-impl<const P: usize> ValueRec<P> {
+impl<const P: usize> ValueRec<[i32; P]> {
     /// Create empty Value Record
     pub const fn new(vtype: ValueType) -> Self {
+        Self::with_values(vtype, [0; P])
+    }
+}
+
+impl<V> ValueRec<V> {
+    /// Create Value Record keeping values in `vals`, e.g. borrowed slice
+    pub const fn with_values(vtype: ValueType, vals: V) -> Self {
         Self {
-            is_only_front: false,
+            front: Front::Hold,
+            level: false,
             vtype,
-            vals: [0; P],
+            vals,
+            sent: 0,
+            decimation: 1,
+            phase: 0,
+            aggregate: Aggregate::Last,
+            updates: 0,
+            deadband: Deadband::Off,
+            fresh: false,
         }
     }
 }
 
+impl<V: AsRef<[i32]> + AsMut<[i32]>> ValueRec<V> {
+    /// Signal type
+    pub fn vtype(&self) -> ValueType {
+        self.vtype
+    }
+
+    /// Raw values at all time positions of package
+    pub fn values(&self) -> &[i32] {
+        self.vals.as_ref()
+    }
+
+    /// Values at all time positions of package
+    pub fn samples(&self) -> impl Iterator<Item = Sample> + '_ {
+        self.values()
+            .iter()
+            .map(move |&v| Sample::new(self.vtype, v))
+    }
+
+    /// Values paired with time of their positions,
+    /// where package started at `start` and positions follow every `period`
+    pub fn timed(&self, start: u32, period: u32) -> impl Iterator<Item = (u32, Sample)> + '_ {
+        self.samples()
+            .enumerate()
+            .map(move |(slot, v)| (start.wrapping_add(slot as u32 * period), v))
+    }
+
+    fn write(&mut self, current: usize, val: i32, front: Front) {
+        self.fresh = true;
+        if self.phase != 0 {
+            return;
+        }
+        if matches!(front, Front::Hold) && !self.exceeds_deadband(self.values()[current], val) {
+            return;
+        }
+        let val = match front {
+            Front::Hold | Front::Rise | Front::Fall if self.updates > 0 => aggregate(
+                self.aggregate,
+                self.vtype,
+                self.values()[current],
+                val,
+                self.updates,
+            ),
+            _ => val,
+        };
+        self.updates = self.updates.saturating_add(1);
+        write_slot(
+            &mut self.vals.as_mut()[current],
+            &mut self.level,
+            val,
+            front,
+        );
+        self.front = front;
+    }
+
+    fn exceeds_deadband(&self, old: i32, new: i32) -> bool {
+        match self.vtype {
+            ValueType::Bool => true,
+            ValueType::Int => self.deadband.exceeded(old as f32, new as f32),
+            ValueType::Float => self
+                .deadband
+                .exceeded(f32::from_bits(old as u32), f32::from_bits(new as u32)),
+        }
+    }
+
+    /// Drop values and update state, keep signal settings
+    fn reset(&mut self) {
+        self.vals.as_mut().iter_mut().for_each(|v| *v = 0);
+        self.level = false;
+        self.sent = 0;
+        self.phase = 0;
+        self.updates = 0;
+        self.fresh = false;
+    }
+
+    /// Checks if any value differs from last value of previous package
+    fn changed(&self) -> bool {
+        self.values().iter().any(|&v| v != self.sent)
+    }
+
+    /// Fill next time position from `previous` one
+    fn advance(&mut self, previous: usize, current: usize, sent: bool) {
+        let vals = self.vals.as_mut();
+        if sent {
+            self.sent = vals[previous];
+            self.fresh = false;
+        }
+        vals[current] = next_slot(self.front, vals[previous]);
+        self.phase = (self.phase + 1) % self.decimation;
+        self.updates = 0;
+    }
+}
+
+/// Combine `old` result of `updates` updates with `new` value
+fn aggregate(mode: Aggregate, vtype: ValueType, old: i32, new: i32, updates: u16) -> i32 {
+    match (mode, vtype) {
+        (Aggregate::Last, _) | (Aggregate::Mean, ValueType::Bool) => new,
+        (Aggregate::Min, ValueType::Float) => f32::from_bits(old as u32)
+            .min(f32::from_bits(new as u32))
+            .to_i32(),
+        (Aggregate::Max, ValueType::Float) => f32::from_bits(old as u32)
+            .max(f32::from_bits(new as u32))
+            .to_i32(),
+        (Aggregate::Mean, ValueType::Float) => {
+            let (old, new) = (f32::from_bits(old as u32), f32::from_bits(new as u32));
+            (old + (new - old) / (updates as f32 + 1.)).to_i32()
+        }
+        (Aggregate::Min, _) => old.min(new),
+        (Aggregate::Max, _) => old.max(new),
+        (Aggregate::Mean, ValueType::Int) => {
+            (old as i64 + (new as i64 - old as i64) / (updates as i64 + 1)) as i32
+        }
+    }
+}
+
+/// Write value to time position taking edge tracking of `front` into account
+fn write_slot(slot: &mut i32, level: &mut bool, val: i32, front: Front) {
+    match front {
+        Front::Both => {
+            let new = val != 0;
+            if new != *level {
+                *slot = 1;
+                *level = new;
+            }
+        }
+        Front::Count => {
+            let new = val != 0;
+            if new && !*level {
+                *slot += 1;
+            }
+            *level = new;
+        }
+        _ => *slot = val,
+    }
+}
+
+/// Initial value of next time position
+const fn next_slot(front: Front, previous: i32) -> i32 {
+    match front {
+        Front::Hold => previous,
+        Front::Rise | Front::Both | Front::Count => 0,
+        Front::Fall => 1,
+    }
+}
+
+/// Registered signal of type `T`.
+///
+/// Update skips name comparisons, but still steps over `idx` map entries,
+/// so it is O(N) as `set`, only with smaller constant.
+/// Use [`FixedMap`](crate::fixed::FixedMap) for O(1) updates
+pub struct SignalHandle<T> {
+    idx: usize,
+    name: &'static Name,
+    _type: PhantomData<T>,
+}
+
+impl<T> Clone for SignalHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SignalHandle<T> {}
+
+impl<T> SignalHandle<T> {
+    /// Signal name
+    pub fn name(&self) -> &'static Name {
+        self.name
+    }
+}
+
+/// Tuple of `(SignalHandle<T>, T)` pairs updated at once
+pub trait HandleSet<S> {
+    /// Update all signals, return mask of failed entries
+    fn set_all(self, map: &mut SVStruct<S>) -> u32;
+}
+
+macro_rules! impl_handle_set {
+    ($($T:ident $i:tt),+) => {
+        impl<$($T: Value),+, S: SignalStore> HandleSet<S>
+            for ($((SignalHandle<$T>, $T),)+)
+        {
+            fn set_all(self, map: &mut SVStruct<S>) -> u32 {
+                let mut failed = 0;
+                $(
+                    if map.set_handle((self.$i).0, (self.$i).1).is_err() {
+                        failed |= 1 << $i;
+                    }
+                )+
+                failed
+            }
+        }
+    };
+}
+
+impl_handle_set!(A 0);
+impl_handle_set!(A 0, B 1);
+impl_handle_set!(A 0, B 1, C 2);
+impl_handle_set!(A 0, B 1, C 2, D 3);
+impl_handle_set!(A 0, B 1, C 2, D 3, E 4);
+impl_handle_set!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_handle_set!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_handle_set!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
 /// Errors of adding values to container
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum AddError {
     /// Overflow of container
     MapOverflow,
+    /// Too many annotations in one package
+    NotesOverflow,
+    /// Signal was not registered before map was sealed
+    NotRegistered,
+    /// Signal was added with other type
+    TypeMismatch,
+    /// Name is not valid signal name
+    InvalidName,
+    /// Module with the same name is already added
+    DuplicateModule,
+}
+
+impl core::fmt::Display for AddError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::MapOverflow => "signal map is full",
+            Self::NotesOverflow => "too many annotations in package",
+            Self::NotRegistered => "signal is not registered in sealed map",
+            Self::TypeMismatch => "signal was added with other type",
+            Self::InvalidName => "invalid signal name",
+            Self::DuplicateModule => "module is already added",
+        })
+    }
 }
 
 /// Go to next sendable value position
@@ -67,6 +659,81 @@ pub trait NextValue {
 pub struct SVStruct<M> {
     current: usize,
     map: M,
+    test: Option<TestSignal>,
+    notes: [Option<Note>; MAX_NOTES],
+    non_finite: NonFinite,
+    sealed: bool,
+    profile: &'static [profile::ProfileRule],
+    limits: &'static [alarm::Limit],
+    /// Limits that are violated
+    alarm_active: alarm::Active,
+    alarms: [Option<alarm::Alarm>; alarm::MAX_ALARMS],
+    thresholds: &'static [threshold::Threshold],
+    /// Thresholds that are crossed
+    threshold_active: alarm::Active,
+    computed: &'static [computed::Computed],
+    send_mode: SendMode,
+    /// Packages skipped in a row
+    silent: u32,
+    paused: bool,
+    /// Generation of signal set, changed on every insert and remove
+    layout: u32,
+}
+
+/// Maximum number of annotations in one package
+pub const MAX_NOTES: usize = 4;
+
+/// First byte of annotation record name
+pub(crate) const NOTE_PREFIX: u8 = b'~';
+
+/// Text marker at time position
+#[derive(Clone, Copy)]
+struct Note {
+    slot: usize,
+    text: Name,
+}
+
+impl Note {
+    /// Annotation record name: prefix and text truncated to fit in name
+    fn name(&self) -> impl Iterator<Item = u8> + Clone + '_ {
+        core::iter::once(NOTE_PREFIX).chain(self.text.bytes().take(Name::MAX_SIZE - 2))
+    }
+}
+
+/// Pattern of test signal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TestPattern {
+    /// `Bool` signal high during first half of period
+    Square,
+    /// `Int` signal counting time positions from 0 to period - 1
+    Ramp,
+}
+
+/// Known pattern generated by the crate for link verification
+#[derive(Clone, Copy)]
+struct TestSignal {
+    name: &'static Name,
+    pattern: TestPattern,
+    /// Period in time positions
+    period: u32,
+    tick: u32,
+}
+
+impl TestSignal {
+    fn vtype(&self) -> ValueType {
+        match self.pattern {
+            TestPattern::Square => ValueType::Bool,
+            TestPattern::Ramp => ValueType::Int,
+        }
+    }
+
+    fn value(&self) -> i32 {
+        match self.pattern {
+            TestPattern::Square => (self.tick < self.period - self.period / 2) as i32,
+            TestPattern::Ramp => self.tick as i32,
+        }
+    }
 }
 
 impl<M> core::ops::Deref for SVStruct<M> {
@@ -82,18 +749,147 @@ impl<M> core::ops::DerefMut for SVStruct<M> {
     }
 }
 
+impl<M> SVStruct<M> {
+    /// Container of signals in `map` with default settings
+    pub(crate) const fn with_map(map: M) -> Self {
+        Self {
+            current: 0,
+            map,
+            test: None,
+            notes: [None; MAX_NOTES],
+            non_finite: NonFinite::Pass,
+            sealed: false,
+            profile: &[],
+            limits: &[],
+            alarm_active: alarm::Active::NONE,
+            alarms: [None; alarm::MAX_ALARMS],
+            thresholds: &[],
+            threshold_active: alarm::Active::NONE,
+            computed: &[],
+            send_mode: SendMode::Always,
+            silent: 0,
+            paused: false,
+            layout: 0,
+        }
+    }
+
+    /// Mark change of signal set, so positions of signals may change
+    pub(crate) fn layout_changed(&mut self) {
+        self.layout = self.layout.wrapping_add(1);
+    }
+
+    /// Attach text marker to current time position, e.g. state transition or error.
+    ///
+    /// Annotations are sent with package as bool signals named `~text`
+    /// and showing impulses at marked positions. Text is truncated to `NAME_SIZE - 2` bytes
+    pub fn annotate(&mut self, text: &Name) -> Result<(), AddError> {
+        let note = self
+            .notes
+            .iter_mut()
+            .find(|n| n.is_none())
+            .ok_or(AddError::NotesOverflow)?;
+        *note = Some(Note {
+            slot: self.current,
+            text: *text,
+        });
+        Ok(())
+    }
+
+    /// Stop recording, e.g. by button or command. `next()` does not advance and send packages
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Continue recording from current time position
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Checks if recording is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Annotations of current package with unique text
+    fn unique_notes(&self) -> impl Iterator<Item = &Note> + '_ {
+        unique_notes(&self.notes)
+    }
+
+    /// Checks if any annotation with `text` is placed at time position `slot`
+    fn is_noted(&self, text: &Name, slot: usize) -> bool {
+        is_noted(&self.notes, text, slot)
+    }
+}
+
+/// Annotations with unique text
+fn unique_notes(notes: &[Option<Note>]) -> impl Iterator<Item = &Note> + '_ {
+    notes.iter().enumerate().filter_map(move |(i, n)| {
+        n.as_ref()
+            .filter(|n| !notes[..i].iter().flatten().any(|o| o.text == n.text))
+    })
+}
+
+/// Checks if any annotation with `text` is placed at time position `slot`
+fn is_noted(notes: &[Option<Note>], text: &Name, slot: usize) -> bool {
+    notes
+        .iter()
+        .flatten()
+        .any(|n| n.slot == slot && n.text == *text)
+}
+
 /// Map of signals
-pub type SVMap<const N: usize, const P: usize> = SVStruct<LinearMap<&'static str, ValueRec<P>, N>>;
+pub type SVMap<const N: usize, const P: usize> =
+    SVStruct<LinearMap<&'static str, ValueRec<[i32; P]>, N>>;
+
+/// Maximum size of package of map with `n` signals and `p` values in package
+pub const fn max_package_size(n: usize, p: usize) -> usize {
+    // Markers, size and module name
+    use protocol::Protocol;
+    let header = protocol::SVisual::BEGIN.len() + 4 + Name::MAX_SIZE + protocol::SVisual::END.len();
+    header + (n + MAX_NOTES) * decoder::record_size(p)
+}
 
 impl<const N: usize, const P: usize> SVMap<N, P> {
+    /// Compile-time checks of map parameters
+    const VALID: () = {
+        assert!(N > 0, "signal capacity N must be greater than 0");
+        assert!(P > 0, "package size P must be greater than 0");
+        assert!(
+            max_package_size(N, P) <= u32::MAX as usize,
+            "package size does not fit in u32"
+        );
+    };
+
     /// Create new instance
     pub const fn new() -> Self {
-        Self {
-            current: 0,
-            map: LinearMap::new(),
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID;
+        Self::with_map(LinearMap::new())
+    }
+
+    /// Create new instance with registered signals.
+    /// Signals that do not fit in map are skipped, see [`svmap!`] for compile-time checks
+    pub fn with_signals(signals: &'static [SignalDef]) -> Self {
+        let mut map = Self::new();
+        for s in signals {
+            let mut vr = ValueRec::new(s.vtype);
+            vr.front = s.front;
+            if map.map.insert(&s.name, vr).is_err() {
+                break;
+            }
         }
+        map
     }
 
+    /// Iterator over signals with their values at current time position
+    pub fn signals(&self) -> impl Iterator<Item = (&'static str, ValueType, Sample)> + '_ {
+        self.map
+            .iter()
+            .map(move |(&name, vr)| (name, vr.vtype, Sample::new(vr.vtype, vr.vals[self.current])))
+    }
+}
+
+impl<S: SignalStore> SVStruct<S> {
     /// Checks if package is empty
     pub fn is_first(&self) -> bool {
         self.current == 0
@@ -101,48 +897,508 @@ impl<const N: usize, const P: usize> SVMap<N, P> {
 
     /// Checks if package is full
     pub fn is_last(&self) -> bool {
-        self.current == P - 1
+        self.current + 1 == self.map.package()
+    }
+
+    /// Value of signal at current time position
+    fn current_value(&self, vr: &ValueRec<S::Values>) -> i32 {
+        vr.values()[self.current]
+    }
+
+    /// Write value to record of signal, adding it if allowed
+    fn write<K: NewSignal<S>>(
+        &mut self,
+        key: &K,
+        vtype: ValueType,
+        val: i32,
+        front: Front,
+    ) -> Result<(), AddError> {
+        if key.get(&self.map).is_none() {
+            if self.sealed {
+                return Err(AddError::NotRegistered);
+            }
+            key.insert(&mut self.map, vtype)?;
+            self.layout_changed();
+        }
+
+        let current = self.current;
+        let vr = key.get_mut(&mut self.map).ok_or(AddError::NotRegistered)?;
+        #[cfg(not(feature = "unchecked"))]
+        if vr.vtype != vtype {
+            return Err(AddError::TypeMismatch);
+        }
+        vr.write(current, val, front);
+
+        Ok(())
+    }
+
+    /// Write value of signal added by the crate itself, bypassing profile and checks
+    pub(crate) fn set_value(
+        &mut self,
+        name: &'static Name,
+        vtype: ValueType,
+        val: i32,
+        front: Front,
+    ) -> Result<(), AddError> {
+        self.write(&Static(name), vtype, val, front)
     }
 
-    fn set_value(
+    /// Pass value through profile, alarms, thresholds and non-finite policy and write it
+    fn update<K: NewSignal<S>>(
         &mut self,
-        name: &'static str,
+        key: &K,
         vtype: ValueType,
+        front: Front,
         val: i32,
-        only_pos_front: bool,
     ) -> Result<(), AddError> {
-        if !self.map.contains_key(&name) {
-            if self.map.insert(name, ValueRec::new(vtype)).is_err() {
-                return Err(AddError::MapOverflow);
+        let val = if vtype == ValueType::Float && !f32::from_bits(val as u32).is_finite() {
+            match self.non_finite_value(f32::from_bits(val as u32)) {
+                Some(val) => val,
+                None => return self.hold_non_finite(key, front),
+            }
+        } else {
+            val
+        };
+        self.check_alarms(key.name(), vtype, val);
+        self.check_thresholds(key.name(), vtype, val);
+        let val = match self.apply_profile(key.name(), vtype, val) {
+            Some(val) => val,
+            None => return Ok(()),
+        };
+        self.write(key, vtype, val, front)
+    }
+
+    /// Add square wave test signal that toggles every half of `period_packages` packages.
+    ///
+    /// Values are maintained by the map on each `next()` call, so
+    /// a new transport can be verified before wiring real signals
+    pub fn enable_test_signal(
+        &mut self,
+        name: &'static Name,
+        period_packages: u32,
+    ) -> Result<(), AddError> {
+        self.enable_test_pattern(name, period_packages, TestPattern::Square)
+    }
+
+    /// Add test signal with `pattern` repeated every `period_packages` packages
+    pub fn enable_test_pattern(
+        &mut self,
+        name: &'static Name,
+        period_packages: u32,
+        pattern: TestPattern,
+    ) -> Result<(), AddError> {
+        let test = TestSignal {
+            name,
+            pattern,
+            period: period_packages
+                .saturating_mul(self.map.package() as u32)
+                .max(1),
+            tick: 0,
+        };
+        self.set_value(name, test.vtype(), test.value(), Front::Hold)?;
+        self.test = Some(test);
+        Ok(())
+    }
+
+    /// Remove test signal generator. Signal itself stays in map holding last value
+    pub fn disable_test_signal(&mut self) {
+        self.test = None;
+    }
+
+    /// Add signal of type `T` without setting value,
+    /// so `set` can not hit `MapOverflow` later
+    pub fn register<T: Value>(&mut self, name: &'static Name) -> Result<SignalHandle<T>, AddError> {
+        #[cfg(not(feature = "unchecked"))]
+        if matches!(self.map.get(name), Some(vr) if vr.vtype != T::TYPE) {
+            return Err(AddError::TypeMismatch);
+        }
+        if self.map.get(name).is_none() {
+            self.map.insert(name, T::TYPE)?;
+            self.layout_changed();
+            if let Some(vr) = self.map.get_mut(name) {
+                vr.front = T::FRONT;
             }
         }
+        let idx = self.map.position(name).ok_or(AddError::NotRegistered)?;
+        Ok(SignalHandle {
+            idx,
+            name,
+            _type: PhantomData,
+        })
+    }
+
+    /// Record signal only every `factor` time positions holding its value in between,
+    /// e.g. slow temperature in map of fast control variables
+    pub fn set_decimation<K: SignalKey<S>>(
+        &mut self,
+        name: K,
+        factor: u16,
+    ) -> Result<(), AddError> {
+        let vr = name.get_mut(&mut self.map).ok_or(AddError::NotRegistered)?;
+        vr.decimation = factor.max(1);
+        vr.phase = 0;
+        Ok(())
+    }
+
+    /// Set condition of sending full package, e.g. to keep idle device from saturating link
+    pub fn set_send_mode(&mut self, mode: SendMode) {
+        self.send_mode = mode;
+        self.silent = 0;
+    }
+
+    /// Checks if full package should be sent according to send mode
+    fn should_send(&mut self) -> bool {
+        let max_silence = match self.send_mode {
+            SendMode::Always => return true,
+            SendMode::OnChange { max_silence } => max_silence,
+        };
+        let changed = self.notes.iter().any(Option::is_some)
+            || self
+                .map
+                .try_for_each(|_, vr| if vr.changed() { Err(()) } else { Ok(()) })
+                .is_err();
+        if changed || (max_silence != 0 && self.silent >= max_silence) {
+            self.silent = 0;
+            true
+        } else {
+            self.silent = self.silent.saturating_add(1);
+            false
+        }
+    }
 
-        let vr = self.map.get_mut(name).unwrap();
-        vr.vals[self.current] = val;
-        vr.is_only_front = only_pos_front;
+    /// Set band where changes of signal are treated as noise and not recorded,
+    /// so they do not trigger change-triggered sending and aggregation
+    pub fn set_deadband<K: SignalKey<S>>(
+        &mut self,
+        name: K,
+        deadband: Deadband,
+    ) -> Result<(), AddError> {
+        let vr = name.get_mut(&mut self.map).ok_or(AddError::NotRegistered)?;
+        vr.deadband = deadband;
+        Ok(())
+    }
 
+    /// Set combination of several updates of signal at one time position,
+    /// e.g. `Aggregate::Max` to keep current spikes between `next()` calls
+    pub fn set_aggregate<K: SignalKey<S>>(
+        &mut self,
+        name: K,
+        mode: Aggregate,
+    ) -> Result<(), AddError> {
+        let vr = name.get_mut(&mut self.map).ok_or(AddError::NotRegistered)?;
+        vr.aggregate = mode;
         Ok(())
     }
 
+    /// Update value of registered signal without name comparisons
+    pub fn set_handle<T: Value>(
+        &mut self,
+        handle: SignalHandle<T>,
+        value: T,
+    ) -> Result<(), AddError> {
+        self.set_indexed(handle.idx, handle.name, T::TYPE, T::FRONT, value.to_i32())
+    }
+
+    /// Update signal by index in map, falling back to name lookup
+    pub(crate) fn set_indexed(
+        &mut self,
+        idx: usize,
+        name: &'static Name,
+        vtype: ValueType,
+        front: Front,
+        val: i32,
+    ) -> Result<(), AddError> {
+        self.update(&Indexed { idx, name }, vtype, front, val)
+    }
+
+    /// Update several signals of one type at once.
+    ///
+    /// Returns mask of failed entries: bit `i` is set if entry `i` failed,
+    /// failure of entry 31 and further is reported by bit 31
+    pub fn set_many<T, K, I>(&mut self, values: I) -> u32
+    where
+        T: Value,
+        K: NewSignal<S>,
+        I: IntoIterator<Item = (K, T)>,
+    {
+        let mut failed = 0;
+        for (i, (name, value)) in values.into_iter().enumerate() {
+            if self.set(name, value).is_err() {
+                failed |= 1 << i.min(31);
+            }
+        }
+        failed
+    }
+
+    /// Update signals of different types at once by tuple of `(handle, value)` pairs.
+    ///
+    /// Returns mask of failed entries: bit `i` is set if entry `i` failed
+    pub fn set_handles<H: HandleSet<S>>(&mut self, values: H) -> u32 {
+        values.set_all(self)
+    }
+
+    /// Remove signal, so its capacity can be reused. Returns `false` if signal is absent
+    pub fn remove(&mut self, name: &Name) -> bool {
+        if matches!(self.test, Some(t) if t.name == name) {
+            self.test = None;
+        }
+        let removed = self.map.remove(name);
+        if removed {
+            self.layout_changed();
+        }
+        removed
+    }
+
+    /// Remove all signals and annotations, start new package
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.layout_changed();
+        self.test = None;
+        self.notes = [None; MAX_NOTES];
+        self.current = 0;
+    }
+
+    /// Keep signals, but drop all values and annotations, start new package
+    pub fn reset_values(&mut self) {
+        self.map.for_each_mut(ValueRec::reset);
+        self.notes = [None; MAX_NOTES];
+        self.current = 0;
+        self.silent = 0;
+    }
+
+    /// Forbid adding new signals. Setting unregistered signal returns `NotRegistered` error
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// Allow adding new signals by `set`
+    pub fn unseal(&mut self) {
+        self.sealed = false;
+    }
+
     /// Update value of specified type at current time position
-    pub fn set<T: Value>(&mut self, name: &'static Name, value: T) -> Result<(), AddError> {
-        self.set_value(name, T::TYPE, value.to_i32(), T::ONLY_FRONT)
+    pub fn set<T: Value, K: NewSignal<S>>(&mut self, name: K, value: T) -> Result<(), AddError> {
+        self.update(&name, T::TYPE, T::FRONT, value.to_i32())
+    }
+
+    /// Set policy of handling NaN and infinite `f32` values
+    pub fn set_non_finite_policy(&mut self, policy: NonFinite) {
+        self.non_finite = policy;
+    }
+
+    /// Replacement of non-finite `value` by policy, `None` if previous value is held
+    fn non_finite_value(&self, value: f32) -> Option<i32> {
+        match self.non_finite {
+            NonFinite::Pass => Some(value.to_i32()),
+            NonFinite::Clamp => {
+                let value = if value.is_nan() {
+                    0.
+                } else if value > 0. {
+                    f32::MAX
+                } else {
+                    f32::MIN
+                };
+                Some(value.to_i32())
+            }
+            NonFinite::Hold | NonFinite::Flag(_) => None,
+        }
+    }
+
+    /// Repeat previous value of signal instead of non-finite one
+    fn hold_non_finite<K: NewSignal<S>>(&mut self, key: &K, front: Front) -> Result<(), AddError> {
+        let held = key
+            .get(&self.map)
+            .map(|vr| self.current_value(vr))
+            .unwrap_or(0);
+        self.write(key, ValueType::Float, held, front)?;
+        if let NonFinite::Flag(flag) = self.non_finite {
+            self.update(&Static(flag), ValueType::Bool, Front::Rise, 1)?;
+        }
+        Ok(())
+    }
+
+    /// Value of signal at current time position.
+    /// Returns `None` if signal is absent or has other type
+    pub fn get<T: FromValue, K: SignalKey<S>>(&self, name: K) -> Option<T> {
+        name.get(&self.map)
+            .filter(|vr| vr.vtype == T::TYPE)
+            .map(|vr| T::from_i32(self.current_value(vr)))
+    }
+
+    /// Values of signal at all time positions of package
+    pub fn samples<K: SignalKey<S>>(&self, name: K) -> Option<impl Iterator<Item = Sample> + '_> {
+        name.get(&self.map).map(ValueRec::samples)
+    }
+
+    /// Last value of signal in last sent package.
+    /// Returns `None` if signal is absent or has other type
+    pub fn last_sent<T: FromValue, K: SignalKey<S>>(&self, name: K) -> Option<T> {
+        name.get(&self.map)
+            .filter(|vr| vr.vtype == T::TYPE)
+            .map(|vr| T::from_i32(vr.sent))
+    }
+
+    /// Set bool signal from analog `value` using hysteresis `thresholds`,
+    /// so noise around threshold does not produce chattering edges
+    pub fn set_analog_as_bool<T: Copy + PartialOrd, K: NewSignal<S>>(
+        &mut self,
+        name: K,
+        value: T,
+        thresholds: BoolFromAnalog<T>,
+    ) -> Result<(), AddError> {
+        let level = name
+            .get(&self.map)
+            .map(|vr| self.current_value(vr) != 0)
+            .unwrap_or(false);
+        self.set(name, thresholds.level(level, value))
+    }
+
+    /// Expand status register into bool signals, one per bit.
+    ///
+    /// `labels[i]` is the name of bit `i`, bits without label are skipped.
+    /// With `name_prefix` bits are signals of [`group`](Self::group) `name_prefix/label`,
+    /// e.g. `psu/ovp` and `uart/ovp` of the same labels
+    pub fn set_bits(
+        &mut self,
+        name_prefix: Option<&Name>,
+        value: u16,
+        labels: &[&'static Name],
+    ) -> Result<(), AddError> {
+        for (i, &name) in labels.iter().take(16).enumerate() {
+            let level = value & (1 << i) != 0;
+            match name_prefix {
+                Some(prefix) => self.group(*prefix).set(name, level)?,
+                None => self.update(&Static(name), ValueType::Bool, Front::Hold, level as i32)?,
+            }
+        }
+        Ok(())
     }
 }
 
+#[cfg(feature = "nightly")]
+impl<const N: usize, const P: usize> SVMap<N, P>
+where
+    [(); max_package_size(N, P)]:,
+{
+    /// Encode package into buffer of exact maximum package size
+    pub fn encode(&self, module: &Name) -> buffer::Buffer<{ max_package_size(N, P) }> {
+        let mut buffer = buffer::Buffer::new();
+        // Buffer can not overflow
+        buffer.send_package(module, self).ok();
+        buffer
+    }
+}
+
+/// Struct that can be recorded as a set of signals
+///
+/// Can be derived with `#[derive(SvSignals)]` when `derive` feature is enabled
+pub trait SvSignals {
+    /// Set one signal per field at current time position
+    fn record<const N: usize, const P: usize>(&self, sv: &mut SVMap<N, P>) -> Result<(), AddError>;
+}
+
+/// Signal description for registration of whole signal set
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignalDef {
+    /// Signal name
+    pub name: Name,
+    /// Signal type
+    pub vtype: ValueType,
+    /// Behavior on next time position
+    pub front: Front,
+}
+
+impl SignalDef {
+    /// Checks that all names in `signals` are different
+    pub const fn all_unique(signals: &[SignalDef]) -> bool {
+        let mut i = 0;
+        while i < signals.len() {
+            let mut j = i + 1;
+            while j < signals.len() {
+                if signals[i].name.const_eq(&signals[j].name) {
+                    return false;
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        true
+    }
+}
+
+/// Map with registered signals, e.g. `svmap!{ 8, 10; "temp": f32, "rpm": i32, "fault": bool }`
+/// for map of 8 signals with 10 values in package.
+///
+/// Name length, duplicates and capacity are checked at compile time:
+///
+/// ```compile_fail
+/// let map = svisual::svmap! { 4, 10; "temp": f32, "temp": i32 };
+/// ```
+#[macro_export]
+macro_rules! svmap {
+    ($n:expr, $p:expr; $($name:literal : $t:ty),* $(,)?) => {{
+        const SIGNALS: &[$crate::SignalDef] = &[$($crate::SignalDef {
+            name: $crate::Name::new($name),
+            vtype: <$t as $crate::Value>::TYPE,
+            front: <$t as $crate::Value>::FRONT,
+        }),*];
+        const _: () = assert!($crate::SignalDef::all_unique(SIGNALS), "duplicate signal name");
+        const _: () = assert!(SIGNALS.len() <= $n, "too many signals for map capacity");
+        static STATIC_SIGNALS: &[$crate::SignalDef] = SIGNALS;
+        $crate::SVMap::<$n, $p>::with_signals(STATIC_SIGNALS)
+    }};
+}
+
 /// Supported value transfer type
 pub trait Value {
     /// Associated `[ValueType]`
     const TYPE: ValueType;
     /// Only positive front
-    const ONLY_FRONT: bool;
+    #[deprecated(since = "0.4.2", note = "use `FRONT` instead")]
+    const ONLY_FRONT: bool = false;
+    /// Behavior on next time position
+    #[allow(deprecated)]
+    const FRONT: Front = if Self::ONLY_FRONT {
+        Front::Rise
+    } else {
+        Front::Hold
+    };
     /// `i32` representation
     fn to_i32(self) -> i32;
 }
 
+/// Type that can be read back from stored value
+pub trait FromValue: Sized {
+    /// Associated `[ValueType]`
+    const TYPE: ValueType;
+    /// Value from `i32` representation
+    fn from_i32(val: i32) -> Self;
+}
+
+impl FromValue for i32 {
+    const TYPE: ValueType = ValueType::Int;
+    fn from_i32(val: i32) -> Self {
+        val
+    }
+}
+
+impl FromValue for f32 {
+    const TYPE: ValueType = ValueType::Float;
+    fn from_i32(val: i32) -> Self {
+        f32::from_bits(val as u32)
+    }
+}
+
+impl FromValue for bool {
+    const TYPE: ValueType = ValueType::Bool;
+    fn from_i32(val: i32) -> Self {
+        val != 0
+    }
+}
+
 impl Value for i32 {
     const TYPE: ValueType = ValueType::Int;
-    const ONLY_FRONT: bool = false;
+    const FRONT: Front = Front::Hold;
     fn to_i32(self) -> i32 {
         self
     }
@@ -150,7 +1406,7 @@ impl Value for i32 {
 
 impl Value for f32 {
     const TYPE: ValueType = ValueType::Float;
-    const ONLY_FRONT: bool = false;
+    const FRONT: Front = Front::Hold;
     fn to_i32(self) -> i32 {
         self.to_bits() as i32
     }
@@ -158,7 +1414,7 @@ impl Value for f32 {
 
 impl Value for bool {
     const TYPE: ValueType = ValueType::Bool;
-    const ONLY_FRONT: bool = false;
+    const FRONT: Front = Front::Hold;
     fn to_i32(self) -> i32 {
         self as i32
     }
@@ -166,25 +1422,93 @@ impl Value for bool {
 
 impl Value for OnlyFront {
     const TYPE: ValueType = ValueType::Bool;
-    const ONLY_FRONT: bool = true;
+    const FRONT: Front = Front::Rise;
+    fn to_i32(self) -> i32 {
+        self.0 as i32
+    }
+}
+
+impl Value for OnlyFall {
+    const TYPE: ValueType = ValueType::Bool;
+    const FRONT: Front = Front::Fall;
+    fn to_i32(self) -> i32 {
+        self.0 as i32
+    }
+}
+
+impl Value for BothEdges {
+    const TYPE: ValueType = ValueType::Bool;
+    const FRONT: Front = Front::Both;
+    fn to_i32(self) -> i32 {
+        self.0 as i32
+    }
+}
+
+impl Value for PulseCount {
+    const TYPE: ValueType = ValueType::Int;
+    const FRONT: Front = Front::Count;
     fn to_i32(self) -> i32 {
         self.0 as i32
     }
 }
 
-impl<const N: usize, const P: usize> NextValue for SVMap<N, P> {
+impl<T, const NUM: i32, const DEN: i32, const OFFSET: i32> Scaled<T, NUM, DEN, OFFSET> {
+    /// Compile-time checks of scale parameters
+    const VALID: () = assert!(DEN != 0, "denominator DEN must not be 0");
+}
+
+impl<const NUM: i32, const DEN: i32, const OFFSET: i32> Value for Scaled<i32, NUM, DEN, OFFSET> {
+    const TYPE: ValueType = ValueType::Int;
+    const FRONT: Front = Front::Hold;
+    fn to_i32(self) -> i32 {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID;
+        let scaled = self.0 as i64 * NUM as i64 / DEN as i64 + OFFSET as i64;
+        scaled.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+}
+
+impl<const NUM: i32, const DEN: i32, const OFFSET: i32> Value for Scaled<f32, NUM, DEN, OFFSET> {
+    const TYPE: ValueType = ValueType::Float;
+    const FRONT: Front = Front::Hold;
+    fn to_i32(self) -> i32 {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID;
+        let scaled = if DEN == 1 {
+            self.0 * NUM as f32
+        } else {
+            self.0 * NUM as f32 / DEN as f32
+        };
+        (scaled + OFFSET as f32).to_i32()
+    }
+}
+
+impl<S: SignalStore> NextValue for SVStruct<S> {
     fn next<F>(&mut self, f: F)
     where
         F: FnOnce(&Self),
     {
+        if self.paused {
+            return;
+        }
+        self.update_computed();
         let previous = self.current;
         self.current += 1;
-        if self.current >= P {
-            self.current -= P;
-            f(self);
+        if self.current >= self.map.package() {
+            self.current = 0;
+            if self.should_send() {
+                f(self);
+            }
+            self.notes = [None; MAX_NOTES];
         }
-        for (_, v) in self.map.iter_mut() {
-            v.vals[self.current] = if v.is_only_front { 0 } else { v.vals[previous] };
+        let (current, sent) = (self.current, self.current == 0);
+        self.map
+            .for_each_mut(|v| v.advance(previous, current, sent));
+        if let Some(mut test) = self.test {
+            test.tick = (test.tick + 1) % test.period;
+            self.test = Some(test);
+            self.set_value(test.name, test.vtype(), test.value(), Front::Hold)
+                .ok();
         }
     }
 }
@@ -194,95 +1518,378 @@ pub trait SendPackage<V> {
     /// Error type
     type Error;
     /// Send package with module name
-    fn send_package(&mut self, module: &'static Name, values: &V) -> Result<(), Self::Error>;
+    fn send_package(&mut self, module: &Name, values: &V) -> Result<(), Self::Error>;
+
+    /// Send packages of several instances of the same signal set
+    /// with module names `base0`, `base1`, ...
+    fn send_indexed(&mut self, base: &Name, values: &[V]) -> Result<(), Self::Error> {
+        for (idx, v) in values.iter().enumerate() {
+            self.send_package(&Name::indexed(base, idx), v)?;
+        }
+        Ok(())
+    }
 }
 
 /// Implementation of SendPackage for all that support `embedded-hal::serial::Write`
-impl<Tx, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for Tx
+impl<Tx, S> SendPackage<SVStruct<S>> for Tx
 where
     Tx: WriteIter,
+    S: SignalStore,
 {
     type Error = <Tx as WriteIter>::Error;
-    fn send_package(
-        &mut self,
-        module: &'static Name,
-        values: &SVMap<N, P>,
-    ) -> Result<(), Self::Error> {
-        use core::iter::repeat;
-        let vl_size = Name::MAX_SIZE + 4 + P * 4;
-        // Full package size
-        let full_size = (Name::MAX_SIZE + vl_size * values.map.len()) as u32;
-
-        // Open package
-        self.bwrite_iter(
-            "=begin="
-                .bytes()
-                .chain(full_size.to_le_bytes().iter().cloned())
-                // Identifier (name) of the module
-                .chain(module.bytes())
-                .chain(repeat(0).take(Name::MAX_SIZE - module.len())),
-        )?;
-        self.bflush()?;
+    fn send_package(&mut self, module: &Name, values: &SVStruct<S>) -> Result<(), Self::Error> {
+        write_package(
+            self,
+            module,
+            values.map.package(),
+            StoreRecords(&values.map),
+            &values.notes,
+        )
+    }
+}
 
-        for (&name, v) in values.map.iter() {
-            // Identifier (name) of signal
-            self.bwrite_iter(
-                name.bytes()
-                    .chain(repeat(0).take(Name::MAX_SIZE - name.len()))
-                    // Signal type
-                    .chain((v.vtype as i32).to_le_bytes().iter().cloned())
-                    // Values of one signal in package
-                    .chain(v.vals.iter().flat_map(|val| val.to_le_bytes())),
-            )?;
-            self.bflush()?;
-        }
+/// Signal records of package: name, type and values
+pub(crate) trait Records {
+    /// Number of records
+    fn count(&self) -> usize;
+    /// Call `f` on each record in order of sending, stop on first error
+    fn try_for_each<E, F>(&self, f: F) -> Result<(), E>
+    where
+        F: FnMut(&str, ValueType, &[i32]) -> Result<(), E>;
+}
+
+impl<'a, I> Records for I
+where
+    I: Iterator<Item = (&'a str, ValueType, &'a [i32])> + Clone,
+{
+    fn count(&self) -> usize {
+        Iterator::count(self.clone())
+    }
+    fn try_for_each<E, F>(&self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&str, ValueType, &[i32]) -> Result<(), E>,
+    {
+        Iterator::try_for_each(&mut self.clone(), |(name, vtype, vals)| {
+            f(name, vtype, vals)
+        })
+    }
+}
 
-        // Close package
-        self.bwrite_iter("=end=".bytes())?;
-        self.bflush()?;
+/// Records of all signals of store
+pub(crate) struct StoreRecords<'a, S>(pub &'a S);
 
-        Ok(())
+impl<'a, S: SignalStore> Records for StoreRecords<'a, S> {
+    fn count(&self) -> usize {
+        self.0.len()
+    }
+    fn try_for_each<E, F>(&self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&str, ValueType, &[i32]) -> Result<(), E>,
+    {
+        self.0
+            .try_for_each(|name, vr| f(name, vr.vtype, vr.values()))
+    }
+}
+
+/// Write package of `records` with `package` values each and annotations
+pub(crate) fn write_package<Tx, R>(
+    tx: &mut Tx,
+    module: &Name,
+    package: usize,
+    records: R,
+    notes: &[Option<Note>],
+) -> Result<(), Tx::Error>
+where
+    Tx: WriteIter + ?Sized,
+    R: Records,
+{
+    write_package_with::<protocol::SVisual, _, _>(tx, module, package, records, notes)
+}
+
+/// Write package with markers and padding of protocol `C`
+pub(crate) fn write_package_with<C, Tx, R>(
+    tx: &mut Tx,
+    module: &Name,
+    package: usize,
+    records: R,
+    notes: &[Option<Note>],
+) -> Result<(), Tx::Error>
+where
+    C: protocol::Protocol,
+    Tx: WriteIter + ?Sized,
+    R: Records,
+{
+    use protocol::{padded, write_header, write_trailer};
+    let vl_size = Name::MAX_SIZE + 4 + package * 4;
+    // Full package size
+    let count = records.count() + unique_notes(notes).count();
+    let full_size = Name::MAX_SIZE + vl_size * count;
+
+    // Open package
+    write_header::<C, _>(tx, C::BEGIN, full_size, module)?;
+    tx.bflush()?;
+
+    records.try_for_each(|name, vtype, vals| {
+        // Identifier (name) of signal
+        tx.bwrite_iter(
+            padded::<C, _>(name.bytes())
+                // Signal type
+                .chain((vtype as i32).to_le_bytes().iter().cloned())
+                // Values of one signal in package
+                .chain(vals.iter().flat_map(|val| val.to_le_bytes())),
+        )?;
+        tx.bflush()
+    })?;
+
+    for note in unique_notes(notes) {
+        // Annotation as bool signal
+        tx.bwrite_iter(
+            padded::<C, _>(note.name())
+                .chain((ValueType::Bool as i32).to_le_bytes().iter().cloned())
+                .chain(
+                    (0..package)
+                        .flat_map(|slot| (is_noted(notes, &note.text, slot) as i32).to_le_bytes()),
+                ),
+        )?;
+        tx.bflush()?;
     }
+
+    // Close package
+    write_trailer::<C, _>(tx)
 }
 
 /// Compile-time chacked name string
-pub struct Name(&'static str);
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Name {
+    buf: [u8; NAME_SIZE],
+    len: u8,
+}
+
+/// Errors of name validation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NameError {
+    /// Name is empty
+    Empty,
+    /// Name does not fit in `NAME_SIZE - 1` bytes
+    TooLong,
+    /// Name contains `=begin=` or `=end=` marker, or marker of other protocol
+    Reserved,
+    /// Name contains zero byte used as padding
+    ZeroByte,
+}
+
+impl core::fmt::Display for NameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Empty => "name is empty",
+            Self::TooLong => "name is too long",
+            Self::Reserved => "name contains package marker",
+            Self::ZeroByte => "name contains zero byte",
+        })
+    }
+}
+
+impl From<NameError> for AddError {
+    fn from(_: NameError) -> Self {
+        Self::InvalidName
+    }
+}
+
+/// Name used as module identifier
+pub type ModuleName = Name;
+
+/// Size of name field in package, including terminating zero.
+///
+/// Must match receiver, default is 24 as in SVisual server
+#[cfg(not(any(feature = "name-16", feature = "name-32", feature = "name-64")))]
+pub const NAME_SIZE: usize = 24;
+/// Size of name field in package, including terminating zero
+#[cfg(all(
+    feature = "name-16",
+    not(any(feature = "name-32", feature = "name-64"))
+))]
+pub const NAME_SIZE: usize = 16;
+/// Size of name field in package, including terminating zero
+#[cfg(all(feature = "name-32", not(feature = "name-64")))]
+pub const NAME_SIZE: usize = 32;
+/// Size of name field in package, including terminating zero
+#[cfg(feature = "name-64")]
+pub const NAME_SIZE: usize = 64;
 
 impl core::ops::Deref for Name {
     type Target = str;
     fn deref(&self) -> &Self::Target {
-        self.0
+        // Safety: buffer is filled from `str` or ASCII digits only
+        unsafe { core::str::from_utf8_unchecked(&self.buf[..self.len as usize]) }
+    }
+}
+
+impl core::fmt::Debug for Name {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Name {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=str}", &**self)
     }
 }
 
 impl Name {
     /// Maximum length of module/signal name
-    const MAX_SIZE: usize = 24;
+    const MAX_SIZE: usize = NAME_SIZE;
+
+    /// New name instance. Panics at compile time if name is invalid
+    pub const fn new(name: &str) -> Self {
+        match Self::try_new(name) {
+            Ok(name) => name,
+            Err(NameError::Empty) => panic!("name is empty"),
+            Err(NameError::TooLong) => panic!("name is too long"),
+            Err(NameError::Reserved) => panic!("name contains `=begin=` or `=end=` marker"),
+            Err(NameError::ZeroByte) => panic!("name contains zero byte"),
+        }
+    }
+
+    /// New name instance validated at runtime, e.g. assembled from parts
+    pub const fn try_new(name: &str) -> Result<Self, NameError> {
+        use protocol::{Protocol, SVisual};
+        let bytes = name.as_bytes();
+        if bytes.is_empty() {
+            return Err(NameError::Empty);
+        }
+        if bytes.len() >= Self::MAX_SIZE {
+            return Err(NameError::TooLong);
+        }
+        if contains(bytes, SVisual::END) || contains(bytes, SVisual::BEGIN) {
+            return Err(NameError::Reserved);
+        }
+        let mut buf = [0; NAME_SIZE];
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == 0 {
+                return Err(NameError::ZeroByte);
+            }
+            buf[i] = bytes[i];
+            i += 1;
+        }
+        Ok(Self {
+            buf,
+            len: bytes.len() as u8,
+        })
+    }
+
+    /// New name instance validated against markers of protocol `C`
+    pub fn try_new_with<C: protocol::Protocol>(name: &str) -> Result<Self, NameError> {
+        let checked = Self::try_new(name)?;
+        if contains(name.as_bytes(), C::BEGIN) || contains(name.as_bytes(), C::END) {
+            return Err(NameError::Reserved);
+        }
+        Ok(checked)
+    }
+
+    const fn const_eq(&self, other: &Name) -> bool {
+        if self.len != other.len {
+            return false;
+        }
+        let mut i = 0;
+        while i < self.len as usize {
+            if self.buf[i] != other.buf[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    /// Name of instance `idx` made as `base` followed by decimal index, e.g. `motor3`.
+    ///
+    /// Panics if result does not fit in maximum name length
+    pub const fn indexed(base: &Name, idx: usize) -> Self {
+        match Self::try_indexed(base, idx) {
+            Ok(name) => name,
+            Err(_) => panic!("indexed name is too long"),
+        }
+    }
+
+    /// Name of instance `idx` validated at runtime
+    pub const fn try_indexed(base: &Name, idx: usize) -> Result<Self, NameError> {
+        let mut digits = 1;
+        let mut rest = idx / 10;
+        while rest != 0 {
+            digits += 1;
+            rest /= 10;
+        }
+        let len = base.len as usize + digits;
+        if len >= Self::MAX_SIZE {
+            return Err(NameError::TooLong);
+        }
+        let mut buf = base.buf;
+        let mut i = len;
+        let mut rest = idx;
+        while i > base.len as usize {
+            i -= 1;
+            buf[i] = b'0' + (rest % 10) as u8;
+            rest /= 10;
+        }
+        Ok(Self {
+            buf,
+            len: len as u8,
+        })
+    }
+
+    /// Name of signal in group made as `group/name`, e.g. `motor/current`.
+    ///
+    /// Panics if result does not fit in maximum name length
+    pub const fn join(group: &Name, name: &Name) -> Self {
+        match Self::try_join(group, name) {
+            Ok(name) => name,
+            Err(_) => panic!("joined name is too long"),
+        }
+    }
 
-    /// New name instance
-    pub const fn new(name: &'static str) -> Self {
-        assert!(!name.is_empty());
-        assert!(name.len() < Self::MAX_SIZE);
-        assert!(!equal(name, "=end="));
-        assert!(!equal(name, "=begin="));
-        Self(name)
+    /// Name of signal in group validated at runtime
+    pub const fn try_join(group: &Name, name: &Name) -> Result<Self, NameError> {
+        let len = group.len as usize + 1 + name.len as usize;
+        if len >= Self::MAX_SIZE {
+            return Err(NameError::TooLong);
+        }
+        let mut buf = group.buf;
+        buf[group.len as usize] = GROUP_SEPARATOR;
+        let mut i = 0;
+        while i < name.len as usize {
+            buf[group.len as usize + 1 + i] = name.buf[i];
+            i += 1;
+        }
+        Ok(Self {
+            buf,
+            len: len as u8,
+        })
     }
 }
 
-const fn equal(first: &'static str, second: &'static str) -> bool {
-    if first.len() != second.len() {
+/// Separator of group and signal names
+pub const GROUP_SEPARATOR: u8 = b'/';
+
+/// Checks if `needle` occurs in `bytes`
+const fn contains(bytes: &[u8], needle: &[u8]) -> bool {
+    if needle.len() > bytes.len() {
         return false;
     }
-    let fb = first.as_bytes();
-    let sb = second.as_bytes();
-    let mut i = 0;
-    while i < first.len() {
-        if fb[i] != sb[i] {
-            return false;
+    let mut start = 0;
+    while start <= bytes.len() - needle.len() {
+        let mut i = 0;
+        while i < needle.len() && bytes[start + i] == needle[i] {
+            i += 1;
+        }
+        if i == needle.len() {
+            return true;
         }
-        i += 1;
+        start += 1;
     }
-    true
+    false
 }
 
 /// Write iterator
@@ -295,6 +1902,10 @@ pub trait WriteIter {
         WI: Iterator<Item = u8>;
     /// Blocking flush
     fn bflush(&mut self) -> Result<(), Self::Error>;
+    /// End of whole frame (package, alarm, etc.) for framing layers
+    fn bend_frame(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
 
 impl<Tx> WriteIter for Tx
@@ -314,3 +1925,34 @@ where
         nb::block!(self.flush())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const READY: Name = Name::new("ready");
+    const FAULT: Name = Name::new("fault");
+    const PSU: Name = Name::new("psu");
+    const PSU_READY: Name = Name::join(&PSU, &READY);
+    const PSU_FAULT: Name = Name::join(&PSU, &FAULT);
+
+    #[test]
+    fn set_bits() {
+        let mut map = SVMap::<4, 2>::new();
+        map.set_bits(None, 0b10, &[&READY, &FAULT]).unwrap();
+        assert_eq!(map.get(&READY), Some(false));
+        assert_eq!(map.get(&FAULT), Some(true));
+
+        // Prefixed signals of map with static names are registered before
+        assert_eq!(
+            map.set_bits(Some(&PSU), 0b01, &[&READY, &FAULT]),
+            Err(AddError::NotRegistered)
+        );
+        map.register::<bool>(&PSU_READY).unwrap();
+        map.register::<bool>(&PSU_FAULT).unwrap();
+        map.set_bits(Some(&PSU), 0b01, &[&READY, &FAULT]).unwrap();
+        assert_eq!(map.get(&PSU_READY), Some(true));
+        assert_eq!(map.get(&PSU_FAULT), Some(false));
+        assert_eq!(map.get(&READY), Some(false));
+    }
+}
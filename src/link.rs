@@ -0,0 +1,180 @@
+use core::convert::TryFrom;
+
+use crate::decoder::PackageView;
+use crate::seq::SeqTracker;
+
+/// Summary of package arrivals
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinkReport {
+    /// Received packages
+    pub packages: u32,
+    /// Packages estimated as lost by gaps in arrival times
+    pub lost: u32,
+    /// Minimal interval between packages
+    pub min: u32,
+    /// Maximal interval between packages
+    pub max: u32,
+    /// Mean interval between packages
+    pub mean: f32,
+    /// Mean absolute deviation of interval from expected period
+    pub jitter: f32,
+}
+
+impl LinkReport {
+    /// Ratio of lost packages to sent ones
+    pub fn loss_ratio(&self) -> f32 {
+        let sent = self.packages + self.lost;
+        if sent == 0 {
+            0.
+        } else {
+            self.lost as f32 / sent as f32
+        }
+    }
+}
+
+/// Host-side analyzer of package arrival times.
+///
+/// Helps to choose baud rate, package size and decimation factor:
+/// growing jitter means link is close to saturation,
+/// lost packages mean it is already saturated or noisy.
+/// Times are in any monotonic units, e.g. microseconds.
+///
+/// Numbered packages are counted as lost by gaps in sequence numbers
+/// instead of estimation by arrival times
+#[derive(Clone, Copy, Debug)]
+pub struct LinkAnalyzer {
+    period: u32,
+    last: Option<u32>,
+    seq: SeqTracker,
+    report: LinkReport,
+    /// Sum of intervals
+    total: u64,
+    /// Sum of deviations from period
+    deviation: u64,
+    intervals: u32,
+}
+
+impl LinkAnalyzer {
+    /// Create analyzer of packages sent every `period`
+    pub const fn new(period: u32) -> Self {
+        Self {
+            period,
+            last: None,
+            seq: SeqTracker::new(),
+            report: LinkReport {
+                packages: 0,
+                lost: 0,
+                min: u32::MAX,
+                max: 0,
+                mean: 0.,
+                jitter: 0.,
+            },
+            total: 0,
+            deviation: 0,
+            intervals: 0,
+        }
+    }
+
+    /// Register package received at `time`
+    pub fn arrive(&mut self, time: u32) {
+        self.register(time, None);
+    }
+
+    /// Register package with sequence number `seq` received at `time`
+    pub fn arrive_numbered(&mut self, time: u32, seq: u32) {
+        self.register(time, Some(seq));
+    }
+
+    /// Register decoded package received at `time`.
+    ///
+    /// Sequence number of package is used if it is numbered.
+    /// Time of sending is used instead of `time` if package is timestamped,
+    /// so delays of host receiving do not add to jitter
+    pub fn receive(&mut self, package: &PackageView, time: u32) {
+        #[cfg(feature = "timestamp")]
+        let time = package.timestamp().unwrap_or(time);
+        self.register(time, package.sequence());
+    }
+
+    fn register(&mut self, time: u32, seq: Option<u32>) {
+        self.report.packages = self.report.packages.saturating_add(1);
+        let lost = seq.map(|seq| self.seq.check(seq));
+        let last = match self.last.replace(time) {
+            Some(last) => last,
+            None => return,
+        };
+        let interval = time.wrapping_sub(last);
+        // Time from the past means restart of device
+        if interval > u32::MAX / 2 {
+            return;
+        }
+        let lost = lost.unwrap_or_else(|| {
+            // Every missed period over half of it is one lost package
+            let periods = (interval as u64 + self.period as u64 / 2) / self.period.max(1) as u64;
+            u32::try_from(periods.saturating_sub(1)).unwrap_or(u32::MAX)
+        });
+        if lost > 0 {
+            self.report.lost = self.report.lost.saturating_add(lost);
+            return;
+        }
+        self.intervals += 1;
+        self.total += interval as u64;
+        self.deviation += (interval as i64 - self.period as i64).unsigned_abs();
+        self.report.min = self.report.min.min(interval);
+        self.report.max = self.report.max.max(interval);
+    }
+
+    /// Statistics of received packages
+    pub fn report(&self) -> LinkReport {
+        let mut report = self.report;
+        if self.intervals == 0 {
+            report.min = 0;
+        } else {
+            report.mean = self.total as f32 / self.intervals as f32;
+            report.jitter = self.deviation as f32 / self.intervals as f32;
+        }
+        report
+    }
+
+    /// Start new measurement
+    pub fn reset(&mut self) {
+        *self = Self::new(self.period);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jitter_and_loss_by_time() {
+        let mut link = LinkAnalyzer::new(10);
+        assert_eq!(link.report().loss_ratio(), 0.);
+        for &time in &[0, 10, 22, 30, 50, 60] {
+            link.arrive(time);
+        }
+        let report = link.report();
+        assert_eq!((report.packages, report.lost), (6, 1));
+        assert_eq!((report.min, report.max), (8, 12));
+        assert_eq!((report.mean, report.jitter), (10., 1.));
+        assert_eq!(report.loss_ratio(), 1. / 7.);
+
+        // Restart of device does not count as loss
+        link.arrive(5);
+        assert_eq!(link.report().lost, 1);
+        link.reset();
+        assert_eq!(link.report(), LinkAnalyzer::new(10).report());
+    }
+
+    #[test]
+    fn loss_by_sequence() {
+        let mut link = LinkAnalyzer::new(10);
+        // Late package is not lost if its number follows previous one
+        link.arrive_numbered(0, 0);
+        link.arrive_numbered(25, 1);
+        link.arrive_numbered(30, 3);
+        let report = link.report();
+        assert_eq!((report.packages, report.lost), (3, 1));
+        assert_eq!((report.min, report.max), (25, 25));
+    }
+}
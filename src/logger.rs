@@ -0,0 +1,146 @@
+use core::convert::TryFrom;
+
+use log::kv::{Error, Key, Value, VisitSource};
+use log::{Log, Metadata, Record};
+
+use crate::cell::SvCell;
+use crate::{Name, SVMap, ValueType};
+
+/// `log::Log` adapter recording numeric key-values of records as signals, e.g.
+/// `log::info!(current = 1.5, on = true; "motor started")` updates `current` and `on`.
+///
+/// Keys are matched against `signals`, as map keeps only `'static` names, other keys are ignored.
+/// Booleans are recorded as `bool`, integers as `i32` and floats as `f32`,
+/// integer value of signal added as float is converted.
+/// Level of each record is recorded as `Int` signal with error 1 and trace 5.
+///
+/// Records logged inside [`SvCell::with`] are dropped instead of panicking
+pub struct SvLogger<const N: usize, const P: usize> {
+    cell: &'static SvCell<N, P>,
+    signals: &'static [&'static Name],
+    level: Option<&'static Name>,
+}
+
+impl<const N: usize, const P: usize> SvLogger<N, P> {
+    /// Record values into `cell`, level is recorded if `level` is set.
+    /// Install in `static` with `log::set_logger`
+    pub const fn new(
+        cell: &'static SvCell<N, P>,
+        signals: &'static [&'static Name],
+        level: Option<&'static Name>,
+    ) -> Self {
+        Self {
+            cell,
+            signals,
+            level,
+        }
+    }
+}
+
+/// Writer of key-values into map
+struct Visitor<'a, const N: usize, const P: usize> {
+    map: &'a mut SVMap<N, P>,
+    signals: &'static [&'static Name],
+}
+
+impl<'kvs, const N: usize, const P: usize> VisitSource<'kvs> for Visitor<'_, N, P> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        let name = match self.signals.iter().find(|&&n| &**n == key.as_str()) {
+            Some(&name) => name,
+            None => return Ok(()),
+        };
+        let float = matches!(self.map.map.get(&**name), Some(v) if v.vtype == ValueType::Float);
+        // Errors of map can not be reported by logger
+        if let Some(v) = value.to_bool() {
+            self.map.set(name, v).ok();
+        } else if let Some(v) = value.to_i64().and_then(|v| i32::try_from(v).ok()) {
+            if float {
+                self.map.set(name, v as f32).ok();
+            } else {
+                self.map.set(name, v).ok();
+            }
+        } else if let Some(v) = value.to_f64() {
+            self.map.set(name, v as f32).ok();
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize, const P: usize> Log for SvLogger<N, P> {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        critical_section::with(|cs| {
+            let mut map = match self.cell.map.borrow(cs).try_borrow_mut() {
+                Ok(map) => map,
+                Err(_) => return,
+            };
+            if let Some(level) = self.level {
+                map.set(level, record.level() as i32).ok();
+            }
+            let mut visitor = Visitor {
+                map: &mut map,
+                signals: self.signals,
+            };
+            record.key_values().visit(&mut visitor).ok();
+        })
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use log::Level;
+
+    const CURRENT: SVName = SVName::new("current");
+    const ON: SVName = SVName::new("on");
+    const COUNT: SVName = SVName::new("count");
+    const LEVEL: SVName = SVName::new("level");
+    const SIGNALS: [&Name; 3] = [&CURRENT, &ON, &COUNT];
+
+    static CELL: SvCell<4, 2> = SvCell::new();
+    static LOGGER: SvLogger<4, 2> = SvLogger::new(&CELL, &SIGNALS, Some(&LEVEL));
+
+    fn log(level: Level, kvs: &[(&str, Value)]) {
+        LOGGER.log(
+            &Record::builder()
+                .level(level)
+                .key_values(&kvs)
+                .args(format_args!("message"))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn key_values_as_signals() {
+        CELL.with(|m| m.set(&CURRENT, 0.).unwrap());
+        log(
+            Level::Warn,
+            &[
+                ("current", Value::from(2)),
+                ("on", Value::from(true)),
+                ("count", Value::from(7)),
+                ("other", Value::from(1)),
+                ("text", Value::from("ignored")),
+            ],
+        );
+        CELL.with(|m| {
+            assert_eq!(m.get(&CURRENT), Some(2.));
+            assert_eq!(m.get(&ON), Some(true));
+            assert_eq!(m.get(&COUNT), Some(7));
+            assert_eq!(m.get(&LEVEL), Some(Level::Warn as i32));
+            assert_eq!(m.map.len(), 4);
+        });
+
+        // Record logged inside `with` is dropped
+        CELL.with(|_| log(Level::Error, &[("count", Value::from(8))]));
+        assert_eq!(CELL.with(|m| m.get(&COUNT)), Some(7));
+        log(Level::Info, &[("current", Value::from(1.5))]);
+        assert_eq!(CELL.with(|m| m.get(&CURRENT)), Some(1.5));
+    }
+}
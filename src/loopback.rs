@@ -0,0 +1,146 @@
+use crate::buffer::{Buffer, BufferOverflow};
+use crate::decoder::{DecodeError, PackageView};
+use crate::{Name, SVMap, SendPackage, ValueType};
+
+/// Difference between map and its decoded package
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Mismatch {
+    /// Module name is decoded wrong
+    Module,
+    /// Signal of map is absent in package
+    Missing(Name),
+    /// Package has signal absent in map, `None` if its name is invalid
+    Unexpected(Option<Name>),
+    /// Signal type is decoded wrong
+    Type {
+        /// Signal name
+        signal: Name,
+        /// Type in map
+        expected: ValueType,
+        /// Decoded type
+        actual: Option<ValueType>,
+    },
+    /// Value is decoded wrong
+    Value {
+        /// Signal name
+        signal: Name,
+        /// Time position in package
+        slot: usize,
+        /// Value in map in `i32` representation
+        expected: i32,
+        /// Decoded value
+        actual: i32,
+    },
+}
+
+/// Errors of loopback self test
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SelfTestError {
+    /// Package does not fit in buffer
+    Overflow,
+    /// Encoded package is malformed
+    Decode(DecodeError),
+}
+
+impl From<BufferOverflow> for SelfTestError {
+    fn from(_: BufferOverflow) -> Self {
+        Self::Overflow
+    }
+}
+
+impl<const N: usize, const P: usize> SVMap<N, P> {
+    /// Encode package into buffer of size `B`, decode it back and compare with map
+    /// calling `f` on each mismatch, e.g. as power-on self test.
+    ///
+    /// Returns number of mismatches. Annotations are not compared
+    pub fn self_test<F, const B: usize>(
+        &self,
+        module: &Name,
+        mut f: F,
+    ) -> Result<usize, SelfTestError>
+    where
+        F: FnMut(Mismatch),
+    {
+        let mut buf = Buffer::<B>::new();
+        buf.send_package(module, self)?;
+        let (view, _) = PackageView::parse(buf.as_slice(), P).map_err(SelfTestError::Decode)?;
+
+        let mut count = 0;
+        let mut report = |m| {
+            count += 1;
+            f(m)
+        };
+        if view.module() != module.as_bytes() {
+            report(Mismatch::Module);
+        }
+        for (&name, vr) in self.map.iter() {
+            let signal = Name::new(name);
+            let record = match view.records().find(|r| r.name() == name.as_bytes()) {
+                Some(r) => r,
+                None => {
+                    report(Mismatch::Missing(signal));
+                    continue;
+                }
+            };
+            if record.vtype() != Some(vr.vtype) {
+                report(Mismatch::Type {
+                    signal,
+                    expected: vr.vtype,
+                    actual: record.vtype(),
+                });
+                continue;
+            }
+            for (slot, (&expected, actual)) in vr.vals.iter().zip(record.values()).enumerate() {
+                if expected != actual {
+                    report(Mismatch::Value {
+                        signal,
+                        slot,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+        for record in view.records().filter(|r| r.note().is_none()) {
+            if !self.map.keys().any(|name| name.as_bytes() == record.name()) {
+                let signal = record.name_str().and_then(|name| Name::try_new(name).ok());
+                report(Mismatch::Unexpected(signal));
+            }
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+    const C: SVName = SVName::new("c");
+    const NOTE: SVName = SVName::new("note");
+
+    #[test]
+    fn self_test() {
+        let mut map = SVMap::<3, 2>::new();
+        for i in 0..2 {
+            map.set(&A, i).unwrap();
+            map.set(&B, i as f32 * 0.5).unwrap();
+            map.set(&C, i == 1).unwrap();
+            map.annotate(&NOTE).unwrap();
+            if i == 0 {
+                map.next(|_| {});
+            }
+        }
+        let mismatch = |m| panic!("unexpected mismatch {:?}", m);
+        assert_eq!(map.self_test::<_, 2048>(&MODULE, mismatch), Ok(0));
+        assert_eq!(
+            map.self_test::<_, 8>(&MODULE, mismatch),
+            Err(SelfTestError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use crate::buffer::{Buffer, BufferOverflow};
+use crate::gorilla::DecompressError;
+use crate::{Name, SendPackage, WriteIter};
+
+/// Marker of LZ compressed frame
+pub const MARKER: &[u8] = b"=lz=";
+/// Marker and original size
+pub const HEADER_SIZE: usize = MARKER.len() + 4;
+/// Compressed size after compressed package
+pub const TRAILER_SIZE: usize = 4;
+
+/// Maximum distance to repeated bytes
+const WINDOW: usize = 0x1000;
+/// Shortest encoded match
+const MIN_MATCH: usize = 3;
+/// Longest encoded match
+const MAX_MATCH: usize = MIN_MATCH + 0xF;
+
+/// Errors of LZ compressed transport
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LzError<E> {
+    /// Package does not fit in buffer
+    Overflow,
+    /// Transport error
+    Transport(E),
+}
+
+/// Transport compressing whole package with LZSS, e.g. for LoRa or 9600-baud links.
+/// `B` is maximum package size.
+///
+/// Frame is `=lz=`, original size (`u32`), compressed package and compressed size (`u32`),
+/// so package is compressed once while it is sent.
+/// Each group of 8 tokens is preceded by flags byte, set bit is literal byte,
+/// clear bit is 2-byte match of 12-bit distance and 4-bit length.
+/// Receiver restores ordinary package with [`decompress`]
+pub struct Lz<Tx, const B: usize> {
+    tx: Tx,
+    buffer: Buffer<B>,
+}
+
+impl<Tx, const B: usize> Lz<Tx, B> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self {
+            tx,
+            buffer: Buffer::new(),
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+/// Longest match of bytes at `pos` with previous ones: distance and length
+fn longest_match(bytes: &[u8], pos: usize) -> (usize, usize) {
+    let max = (bytes.len() - pos).min(MAX_MATCH);
+    let mut best = (0, 0);
+    if max < MIN_MATCH {
+        return best;
+    }
+    for start in pos.saturating_sub(WINDOW - 1)..pos {
+        let len = bytes[start..]
+            .iter()
+            .zip(&bytes[pos..pos + max])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if len > best.1 {
+            best = (pos - start, len);
+            if len == max {
+                break;
+            }
+        }
+    }
+    best
+}
+
+/// Compress `bytes` into `out`. Returns number of written bytes
+fn compress<E>(bytes: &[u8], mut out: impl FnMut(&[u8]) -> Result<(), E>) -> Result<usize, E> {
+    let mut written = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        // Flags byte and up to 8 tokens of 2 bytes
+        let mut group = [0; 17];
+        let mut len = 1;
+        for bit in 0..8 {
+            if pos >= bytes.len() {
+                break;
+            }
+            let (distance, matched) = longest_match(bytes, pos);
+            if matched >= MIN_MATCH {
+                let token = ((distance as u16) << 4) | (matched - MIN_MATCH) as u16;
+                group[len..len + 2].copy_from_slice(&token.to_le_bytes());
+                len += 2;
+                pos += matched;
+            } else {
+                group[0] |= 1 << bit;
+                group[len] = bytes[pos];
+                len += 1;
+                pos += 1;
+            }
+        }
+        out(&group[..len])?;
+        written += len;
+    }
+    Ok(written)
+}
+
+impl<Tx, V, const B: usize> SendPackage<V> for Lz<Tx, B>
+where
+    Tx: WriteIter,
+    Buffer<B>: SendPackage<V, Error = BufferOverflow>,
+{
+    type Error = LzError<Tx::Error>;
+
+    fn send_package(&mut self, module: &Name, values: &V) -> Result<(), Self::Error> {
+        self.buffer.clear();
+        self.buffer
+            .send_package(module, values)
+            .map_err(|_| LzError::Overflow)?;
+        let package = self.buffer.as_slice();
+
+        let tx = &mut self.tx;
+        tx.bwrite_iter(
+            MARKER
+                .iter()
+                .cloned()
+                .chain((package.len() as u32).to_le_bytes()),
+        )
+        .map_err(LzError::Transport)?;
+        let size = compress(package, |group| tx.bwrite_iter(group.iter().cloned()))
+            .map_err(LzError::Transport)?;
+        tx.bwrite_iter((size as u32).to_le_bytes().iter().cloned())
+            .map_err(LzError::Transport)?;
+        tx.bflush().map_err(LzError::Transport)?;
+        tx.bend_frame().map_err(LzError::Transport)
+    }
+}
+
+/// Restore package from compressed `frame` into `out`.
+///
+/// Returns sizes of consumed frame and restored package
+pub fn decompress(frame: &[u8], out: &mut [u8]) -> Result<(usize, usize), DecompressError> {
+    use DecompressError::*;
+    if frame.len() < HEADER_SIZE {
+        return Err(
+            if MARKER.starts_with(&frame[..frame.len().min(MARKER.len())]) {
+                Incomplete
+            } else {
+                InvalidMarker
+            },
+        );
+    }
+    if !frame.starts_with(MARKER) {
+        return Err(InvalidMarker);
+    }
+    let h = &frame[MARKER.len()..HEADER_SIZE];
+    let original = u32::from_le_bytes([h[0], h[1], h[2], h[3]]) as usize;
+    let mut data = frame[HEADER_SIZE..].iter();
+    let out = out.get_mut(..original).ok_or(Overflow)?;
+
+    let mut pos = 0;
+    while pos < original {
+        let flags = *data.next().ok_or(Incomplete)?;
+        for bit in 0..8 {
+            if pos >= original {
+                break;
+            }
+            if flags & (1 << bit) != 0 {
+                out[pos] = *data.next().ok_or(Incomplete)?;
+                pos += 1;
+            } else {
+                let lo = *data.next().ok_or(Incomplete)?;
+                let hi = *data.next().ok_or(Incomplete)?;
+                let token = u16::from_le_bytes([lo, hi]) as usize;
+                let (distance, len) = (token >> 4, (token & 0xF) + MIN_MATCH);
+                if distance == 0 || distance > pos || pos + len > original {
+                    return Err(Corrupted);
+                }
+                // Byte by byte, match may overlap with itself
+                for i in pos..pos + len {
+                    out[i] = out[i - distance];
+                }
+                pos += len;
+            }
+        }
+    }
+    let size = frame.len() - HEADER_SIZE - data.len();
+    let t = data.as_slice().get(..TRAILER_SIZE).ok_or(Incomplete)?;
+    if u32::from_le_bytes([t[0], t[1], t[2], t[3]]) as usize != size {
+        return Err(Corrupted);
+    }
+    Ok((HEADER_SIZE + size + TRAILER_SIZE, original))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const SAW: SVName = SVName::new("saw");
+
+    fn compressed(bytes: &[u8]) -> Buffer<16384> {
+        let mut frame = Buffer::new();
+        frame.extend_from_slice(MARKER).unwrap();
+        frame
+            .extend_from_slice(&(bytes.len() as u32).to_le_bytes())
+            .unwrap();
+        let size = compress(bytes, |group| frame.extend_from_slice(group)).unwrap();
+        frame
+            .extend_from_slice(&(size as u32).to_le_bytes())
+            .unwrap();
+        frame
+    }
+
+    fn round_trip(bytes: &[u8]) -> usize {
+        let frame = compressed(bytes);
+        let mut out = [0; 8192];
+        assert_eq!(decompress(&frame, &mut out), Ok((frame.len(), bytes.len())));
+        assert_eq!(&out[..bytes.len()], bytes);
+        frame.len()
+    }
+
+    #[test]
+    fn round_trips() {
+        round_trip(&[]);
+        round_trip(b"ab");
+        round_trip(b"abcabcabcabcabcabcabc");
+        // Long runs are copied by overlapping matches
+        assert!(round_trip(&[0; 1000]) < 200);
+        // Repeats farther than window are sent as literals
+        let mut bytes = [0; 6000];
+        let mut seed = 1u32;
+        for b in bytes.iter_mut() {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            *b = (seed >> 24) as u8;
+        }
+        let (head, tail) = bytes.split_at_mut(WINDOW + 100);
+        tail[..100].copy_from_slice(&head[..100]);
+        round_trip(&bytes);
+    }
+
+    #[test]
+    fn package() {
+        let mut map = SVMap::<1, 16>::new();
+        for i in 0..16 {
+            map.set(&SAW, i % 4).unwrap();
+            if i < 15 {
+                map.next(|_| {});
+            }
+        }
+        let mut plain = Buffer::<256>::new();
+        plain.send_package(&MODULE, &map).unwrap();
+        let mut lz = Lz::<_, 256>::new(Buffer::<256>::new());
+        lz.send_package(&MODULE, &map).unwrap();
+        let frame = lz.free();
+        assert!(frame.len() < plain.len());
+        let mut out = [0; 256];
+        let (_, len) = decompress(&frame, &mut out).unwrap();
+        assert_eq!(&out[..len], &plain[..]);
+
+        let mut lz = Lz::<_, 64>::new(Buffer::<256>::new());
+        assert_eq!(lz.send_package(&MODULE, &map), Err(LzError::Overflow));
+    }
+
+    #[test]
+    fn truncated() {
+        let frame = compressed(b"abcabcabcabc");
+        for len in [0, 2, HEADER_SIZE - 1, frame.len() - 1] {
+            assert_eq!(
+                decompress(&frame[..len], &mut [0; 64]),
+                Err(DecompressError::Incomplete)
+            );
+        }
+    }
+
+    #[test]
+    fn corrupted() {
+        let mut frame = compressed(b"abcabcabcabc");
+        frame[1] = b'x';
+        assert_eq!(
+            decompress(&frame, &mut [0; 64]),
+            Err(DecompressError::InvalidMarker)
+        );
+
+        // Original size is smaller than compressed data
+        let mut frame = compressed(b"abcabcabcabc");
+        frame[MARKER.len()] = 3;
+        assert_eq!(
+            decompress(&frame, &mut [0; 128]),
+            Err(DecompressError::Corrupted)
+        );
+
+        // Match refers before start of package
+        let mut frame = compressed(b"abcabcabcabc");
+        let token = HEADER_SIZE + 4;
+        frame[token] = 0xF0;
+        frame[token + 1] = 0xFF;
+        assert_eq!(
+            decompress(&frame, &mut [0; 64]),
+            Err(DecompressError::Corrupted)
+        );
+
+        let frame = compressed(b"abcabcabcabc");
+        assert_eq!(
+            decompress(&frame, &mut [0; 8]),
+            Err(DecompressError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::decoder::{DecodeError, PackageView};
+use crate::WriteIter;
+
+/// Size of authentication tag
+pub const TAG_SIZE: usize = 8;
+
+/// SipHash-2-4 keyed hash, processes bytes one by one
+#[derive(Clone, Copy, Debug)]
+pub struct SipHasher {
+    v: [u64; 4],
+    tail: u64,
+    len: u64,
+}
+
+impl SipHasher {
+    /// Hasher with 128-bit key
+    pub fn new(key: &[u8; 16]) -> Self {
+        let mut k0 = [0; 8];
+        let mut k1 = [0; 8];
+        k0.copy_from_slice(&key[..8]);
+        k1.copy_from_slice(&key[8..]);
+        let (k0, k1) = (u64::from_le_bytes(k0), u64::from_le_bytes(k1));
+        Self {
+            v: [
+                k0 ^ 0x736f_6d65_7073_6575,
+                k1 ^ 0x646f_7261_6e64_6f6d,
+                k0 ^ 0x6c79_6765_6e65_7261,
+                k1 ^ 0x7465_6462_7974_6573,
+            ],
+            tail: 0,
+            len: 0,
+        }
+    }
+
+    fn round(&mut self) {
+        let v = &mut self.v;
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+
+    fn compress(&mut self, m: u64) {
+        self.v[3] ^= m;
+        self.round();
+        self.round();
+        self.v[0] ^= m;
+    }
+
+    /// Process next byte
+    pub fn update(&mut self, byte: u8) {
+        self.tail |= (byte as u64) << (8 * (self.len % 8));
+        self.len += 1;
+        if self.len % 8 == 0 {
+            let m = self.tail;
+            self.tail = 0;
+            self.compress(m);
+        }
+    }
+
+    /// Hash of processed bytes
+    pub fn finish(&self) -> u64 {
+        let mut s = *self;
+        s.compress(s.tail | (s.len << 56));
+        s.v[2] ^= 0xFF;
+        for _ in 0..4 {
+            s.round();
+        }
+        s.v[0] ^ s.v[1] ^ s.v[2] ^ s.v[3]
+    }
+
+    /// Authentication tag of `bytes`
+    pub fn tag(key: &[u8; 16], bytes: &[u8]) -> [u8; TAG_SIZE] {
+        let mut h = Self::new(key);
+        for &b in bytes {
+            h.update(b);
+        }
+        h.finish().to_le_bytes()
+    }
+}
+
+/// Transport appending SipHash-2-4 tag of package bytes from `=begin=` to `=end=`,
+/// so gateway can reject spoofed or tampered packages.
+///
+/// Tag follows each frame written into it, so packages of any container are authenticated.
+/// Tag does not hide data, combine with encryption for secrecy
+pub struct Authenticated<Tx> {
+    tx: Tx,
+    key: [u8; 16],
+    hasher: SipHasher,
+}
+
+impl<Tx> Authenticated<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx, key: [u8; 16]) -> Self {
+        Self {
+            tx,
+            key,
+            hasher: SipHasher::new(&key),
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+impl<Tx: WriteIter> WriteIter for Authenticated<Tx> {
+    type Error = Tx::Error;
+
+    fn bwrite_iter<WI>(&mut self, bytes: WI) -> Result<(), Self::Error>
+    where
+        WI: Iterator<Item = u8>,
+    {
+        let hasher = &mut self.hasher;
+        self.tx.bwrite_iter(bytes.inspect(|&b| hasher.update(b)))
+    }
+
+    fn bflush(&mut self) -> Result<(), Self::Error> {
+        self.tx.bflush()
+    }
+
+    fn bend_frame(&mut self) -> Result<(), Self::Error> {
+        let tag = self.hasher.finish().to_le_bytes();
+        self.hasher = SipHasher::new(&self.key);
+        self.tx.bwrite_iter(tag.iter().cloned())?;
+        self.tx.bflush()?;
+        self.tx.bend_frame()
+    }
+}
+
+/// Errors of tag verification
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MacError {
+    /// More bytes are needed
+    Incomplete,
+    /// Tag does not match
+    Mismatch,
+    /// Package is malformed
+    Decode(DecodeError),
+}
+
+impl<'a> PackageView<'a> {
+    /// Parse package followed by tag of [`Authenticated`] transport with `key`.
+    ///
+    /// Returns package view and the rest of bytes after the tag
+    pub fn parse_authenticated(
+        bytes: &'a [u8],
+        values: usize,
+        key: &[u8; 16],
+    ) -> Result<(Self, &'a [u8]), MacError> {
+        let (view, rest) = Self::parse(bytes, values).map_err(MacError::Decode)?;
+        let tag = rest.get(..TAG_SIZE).ok_or(MacError::Incomplete)?;
+        let expected = SipHasher::tag(key, &bytes[..bytes.len() - rest.len()]);
+        // Constant time comparison
+        if expected.iter().zip(tag).fold(0, |d, (a, b)| d | (a ^ b)) != 0 {
+            return Err(MacError::Mismatch);
+        }
+        Ok((view, &rest[TAG_SIZE..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const KEY: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn reference_vectors() {
+        // Vectors of SipHash paper for key 00..0f and message 00..0e
+        assert_eq!(SipHasher::new(&KEY).finish(), 0x726f_db47_dd0e_0e31);
+        let msg = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14];
+        assert_eq!(
+            SipHasher::tag(&KEY, &msg),
+            [0xe5, 0x45, 0xbe, 0x49, 0x61, 0xca, 0x29, 0xa1]
+        );
+    }
+
+    #[test]
+    fn verify() {
+        let mut map = SVMap::<1, 2>::new();
+        map.set(&A, 5).unwrap();
+        let mut tx = Authenticated::new(Mock::<2048>::new(), KEY);
+        tx.send_package(&MODULE, &map).unwrap();
+        tx.send_package(&MODULE, &map).unwrap();
+        let mut bytes = heapless::Vec::<u8, 2048>::new();
+        bytes.extend_from_slice(tx.free().as_slice()).unwrap();
+
+        // Tag is restarted for each package
+        let (view, rest) = PackageView::parse_authenticated(&bytes, 2, &KEY).unwrap();
+        assert_eq!(view.module_str(), Some("module"));
+        let (_, rest) = PackageView::parse_authenticated(rest, 2, &KEY).unwrap();
+        assert!(rest.is_empty());
+
+        let mut other = KEY;
+        other[0] ^= 1;
+        assert_eq!(
+            PackageView::parse_authenticated(&bytes, 2, &other).map(|_| ()),
+            Err(MacError::Mismatch)
+        );
+        let first = bytes.len() / 2;
+        assert_eq!(
+            PackageView::parse_authenticated(&bytes[..first - 1], 2, &KEY).map(|_| ()),
+            Err(MacError::Incomplete)
+        );
+        // Value byte of signal `a`
+        let pos = first - TAG_SIZE - 5 - 8;
+        bytes[pos] ^= 1;
+        assert_eq!(
+            PackageView::parse_authenticated(&bytes, 2, &KEY).map(|_| ()),
+            Err(MacError::Mismatch)
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use embedded_hal::serial::Write;
+use heapless::Vec;
+
+/// Errors of mock transport
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MockError {
+    /// Error injected with [`Mock::fail_after`]
+    Injected,
+    /// More than `B` bytes are written
+    Overflow,
+}
+
+/// In-memory transport for unit tests off-target.
+///
+/// Records written bytes and injects `WouldBlock` and hard errors on demand.
+/// Implements serial `Write`, so it can be used with any transport wrapper
+#[derive(Clone, Debug, Default)]
+pub struct Mock<const B: usize> {
+    bytes: Vec<u8, B>,
+    block: usize,
+    fail: Option<usize>,
+    blocked: usize,
+    flushes: usize,
+}
+
+impl<const B: usize> Mock<B> {
+    /// Create empty mock
+    pub const fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            block: 0,
+            fail: None,
+            blocked: 0,
+            flushes: 0,
+        }
+    }
+
+    /// Written bytes
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Remove written bytes and reset counters. Injected errors are kept
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.blocked = 0;
+        self.flushes = 0;
+    }
+
+    /// Return `WouldBlock` on next `count` writes, e.g. to emulate busy line
+    pub fn block_next(&mut self, count: usize) {
+        self.block = count;
+    }
+
+    /// Fail with [`MockError::Injected`] after `count` more bytes.
+    /// Failure is repeated until [`recover`](Self::recover)
+    pub fn fail_after(&mut self, count: usize) {
+        self.fail = Some(count);
+    }
+
+    /// Stop failing
+    pub fn recover(&mut self) {
+        self.fail = None;
+    }
+
+    /// Number of returned `WouldBlock`
+    pub fn blocked(&self) -> usize {
+        self.blocked
+    }
+
+    /// Number of flushes
+    pub fn flushes(&self) -> usize {
+        self.flushes
+    }
+}
+
+impl<const B: usize> Write<u8> for Mock<B> {
+    type Error = MockError;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        if self.block > 0 {
+            self.block -= 1;
+            self.blocked += 1;
+            return Err(nb::Error::WouldBlock);
+        }
+        match self.fail {
+            Some(0) => return Err(nb::Error::Other(MockError::Injected)),
+            Some(ref mut left) => *left -= 1,
+            None => {}
+        }
+        self.bytes
+            .push(word)
+            .map_err(|_| nb::Error::Other(MockError::Overflow))
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        if self.fail == Some(0) {
+            return Err(nb::Error::Other(MockError::Injected));
+        }
+        self.flushes += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use heapless::Vec;
+
+use crate::{AddError, Name, NextValue, SVMap, SendPackage};
+
+/// Several logical modules in one client, e.g. `motor`, `battery` and `comms`,
+/// each with own map of `N` signals. `K` is maximum number of modules.
+///
+/// All modules go to next time position at once and are sent as separate packages
+pub struct Modules<const K: usize, const N: usize, const P: usize> {
+    modules: Vec<(Name, SVMap<N, P>), K>,
+}
+
+impl<const K: usize, const N: usize, const P: usize> Modules<K, N, P> {
+    /// Create container without modules
+    pub const fn new() -> Self {
+        Self {
+            modules: Vec::new(),
+        }
+    }
+
+    /// Add module with empty map. Returns index of module,
+    /// `DuplicateModule` error if module is already added
+    pub fn add(&mut self, module: Name) -> Result<usize, AddError> {
+        if self.modules.iter().any(|(name, _)| *name == module) {
+            return Err(AddError::DuplicateModule);
+        }
+        self.modules
+            .push((module, SVMap::new()))
+            .map_err(|_| AddError::MapOverflow)?;
+        Ok(self.modules.len() - 1)
+    }
+
+    /// Number of modules
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    /// Checks if there are no modules
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// Map of module with index
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut SVMap<N, P>> {
+        self.modules.get_mut(idx).map(|(_, map)| map)
+    }
+
+    /// Map of module with name
+    pub fn module_mut(&mut self, module: &Name) -> Option<&mut SVMap<N, P>> {
+        self.modules
+            .iter_mut()
+            .find(|(name, _)| name == module)
+            .map(|(_, map)| map)
+    }
+
+    /// Modules with their maps
+    pub fn iter(&self) -> impl Iterator<Item = (&Name, &SVMap<N, P>)> {
+        self.modules.iter().map(|(name, map)| (name, map))
+    }
+
+    /// Go to next time position in all modules, `f` is called on each full package
+    pub fn next<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Name, &SVMap<N, P>),
+    {
+        for (name, map) in self.modules.iter_mut() {
+            map.next(|map| f(name, map));
+        }
+    }
+
+    /// Go to next time position in all modules sending full packages to `tx`.
+    ///
+    /// All modules are advanced even if sending fails, first error is returned
+    pub fn send_next<Tx>(&mut self, tx: &mut Tx) -> Result<(), Tx::Error>
+    where
+        Tx: SendPackage<SVMap<N, P>>,
+    {
+        let mut result = Ok(());
+        self.next(|name, map| {
+            let sent = tx.send_package(name, map);
+            if result.is_ok() {
+                result = sent;
+            }
+        });
+        result
+    }
+}
+
+impl<const K: usize, const N: usize, const P: usize> Default for Modules<K, N, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+
+    const MOTOR: SVName = SVName::new("motor");
+    const BATTERY: SVName = SVName::new("battery");
+    const COMMS: SVName = SVName::new("comms");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn separate_packages() {
+        let mut modules = Modules::<2, 2, 1>::new();
+        assert_eq!(modules.add(MOTOR), Ok(0));
+        assert_eq!(modules.add(MOTOR), Err(AddError::DuplicateModule));
+        assert_eq!(modules.add(BATTERY), Ok(1));
+        assert_eq!(modules.add(COMMS), Err(AddError::MapOverflow));
+        assert_eq!(
+            display(AddError::DuplicateModule),
+            "module is already added"
+        );
+
+        modules.get_mut(0).unwrap().set(&A, 1).unwrap();
+        modules.module_mut(&BATTERY).unwrap().set(&A, 2).unwrap();
+        let mut buf = Buffer::<2048>::new();
+        modules.send_next(&mut buf).unwrap();
+
+        let mut expected = Buffer::<2048>::new();
+        for (name, map) in modules.iter() {
+            expected.send_package(name, map).unwrap();
+        }
+        assert_eq!(&*buf, &*expected);
+    }
+
+    /// Display text of error
+    fn display(e: AddError) -> heapless::String<32> {
+        use core::fmt::Write;
+        let mut s = heapless::String::new();
+        write!(s, "{}", e).unwrap();
+        s
+    }
+}
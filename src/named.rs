@@ -0,0 +1,88 @@
+use crate::{Name, NextValue, SendPackage};
+
+/// Container bound to its module name, so name can not be mismatched between sends
+pub struct Named<M> {
+    module: Name,
+    map: M,
+}
+
+impl<M> Named<M> {
+    /// Bind `map` to `module`
+    pub const fn new(module: Name, map: M) -> Self {
+        Self { module, map }
+    }
+
+    /// Module name
+    pub fn module(&self) -> &Name {
+        &self.module
+    }
+
+    /// Release container
+    pub fn free(self) -> M {
+        self.map
+    }
+
+    /// Send package of container with bound module name
+    pub fn send<Tx: SendPackage<M>>(&self, tx: &mut Tx) -> Result<(), Tx::Error> {
+        tx.send_package(&self.module, &self.map)
+    }
+}
+
+impl<M: NextValue> Named<M> {
+    /// Go to next time position, full package is sent to `tx`
+    pub fn next_send<Tx: SendPackage<M>>(&mut self, tx: &mut Tx) -> Result<(), Tx::Error> {
+        let module = &self.module;
+        let mut result = Ok(());
+        self.map.next(|map| result = tx.send_package(module, map));
+        result
+    }
+}
+
+impl<M> core::ops::Deref for Named<M> {
+    type Target = M;
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<M> core::ops::DerefMut for Named<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn bound_module() {
+        let mut named = Named::new(MODULE, SVMap::<1, 2>::new());
+        let (mut tx, mut expected) = (Mock::<512>::new(), Mock::<512>::new());
+        for i in 0..4 {
+            named.set(&A, i).unwrap();
+            named.next_send(&mut tx).unwrap();
+        }
+        named.send(&mut tx).unwrap();
+        assert_eq!(named.module(), &MODULE);
+
+        let mut map = SVMap::<1, 2>::new();
+        for i in 0..4 {
+            map.set(&A, i).unwrap();
+            map.next(|m| expected.send_package(&MODULE, m).unwrap());
+        }
+        expected.send_package(&MODULE, &map).unwrap();
+        assert_eq!(tx.as_slice(), expected.as_slice());
+
+        tx.fail_after(0);
+        named.set(&A, 0).unwrap();
+        assert_eq!(named.next_send(&mut tx), Ok(()));
+        named.set(&A, 0).unwrap();
+        assert!(named.next_send(&mut tx).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use crate::store::SignalStore;
+use crate::{Name, SVStruct, SendPackage, ValueType, WriteIter};
+
+/// Transport encoding packages in postcard wire format for custom receivers.
+///
+/// Package is encoded as serde data model of
+///
+/// ```text
+/// struct Package<'a> { module: &'a str, signals: Vec<Signal<'a>>, notes: Vec<Note<'a>> }
+/// struct Signal<'a> { name: &'a str, values: Values }
+/// enum Values { Bool(Vec<bool>), Int(Vec<i32>), Float(Vec<f32>) }
+/// struct Note<'a> { text: &'a str, slot: u32 }
+/// ```
+///
+/// so receiver decodes it with `postcard::from_bytes`. Packages of any container are encoded
+/// and sent into any byte transport, so layers of SVisual format are shared, e.g. checksum.
+/// Frames are not delimited, wrap [`Cobs`](crate::cobs::Cobs) transport to match
+/// `postcard::from_bytes_cobs`
+pub struct Postcard<Tx> {
+    tx: Tx,
+}
+
+impl<Tx: WriteIter> Postcard<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self { tx }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+/// Unsigned LEB128 varint
+fn varint(mut val: u32) -> impl Iterator<Item = u8> {
+    let mut buf = [0; 5];
+    let mut len = 0;
+    loop {
+        let byte = (val & 0x7F) as u8;
+        val >>= 7;
+        if val == 0 {
+            buf[len] = byte;
+            len += 1;
+            break;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+    (0..len).map(move |i| buf[i])
+}
+
+/// Length-prefixed string
+fn string(s: &str) -> impl Iterator<Item = u8> + '_ {
+    varint(s.len() as u32).chain(s.bytes())
+}
+
+impl<Tx, S> SendPackage<SVStruct<S>> for Postcard<Tx>
+where
+    Tx: WriteIter,
+    S: SignalStore,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVStruct<S>) -> Result<(), Self::Error> {
+        let tx = &mut self.tx;
+        tx.bwrite_iter(string(module).chain(varint(values.map.len() as u32)))?;
+        values.map.try_for_each(|name, v| {
+            let vals = v.values();
+            tx.bwrite_iter(
+                string(name)
+                    .chain(varint(v.vtype as u32))
+                    .chain(varint(vals.len() as u32)),
+            )?;
+            for &val in vals {
+                match v.vtype {
+                    ValueType::Bool => tx.bwrite_iter(core::iter::once((val != 0) as u8))?,
+                    // Zigzag encoding of signed integer
+                    ValueType::Int => tx.bwrite_iter(varint(((val << 1) ^ (val >> 31)) as u32))?,
+                    ValueType::Float => tx.bwrite_iter(val.to_le_bytes().iter().cloned())?,
+                }
+            }
+            tx.bflush()
+        })?;
+        let notes = values.notes.iter().flatten();
+        tx.bwrite_iter(varint(notes.clone().count() as u32))?;
+        for note in notes {
+            tx.bwrite_iter(string(&note.text).chain(varint(note.slot as u32)))?;
+        }
+        tx.bflush()?;
+        tx.bend_frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+    use crate::store::FnvMap;
+    use crate::transport::TransportBuilder;
+    use serde::Deserialize;
+    use std::vec::Vec;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Package<'a> {
+        module: &'a str,
+        #[serde(borrow)]
+        signals: Vec<Signal<'a>>,
+        #[serde(borrow)]
+        notes: Vec<Note<'a>>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Signal<'a> {
+        name: &'a str,
+        values: Values,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Values {
+        Bool(Vec<bool>),
+        Int(Vec<i32>),
+        Float(Vec<f32>),
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Note<'a> {
+        text: &'a str,
+        slot: u32,
+    }
+
+    const MODULE: SVName = SVName::new("module");
+    const ON: SVName = SVName::new("on");
+    const COUNT: SVName = SVName::new("count");
+    const LEVEL: SVName = SVName::new("level");
+    const START: SVName = SVName::new("start");
+
+    #[test]
+    fn from_bytes() {
+        let mut map = SVMap::<4, 2>::new();
+        for i in 0..2 {
+            map.set(&ON, i == 1).unwrap();
+            map.set(&COUNT, -i).unwrap();
+            map.set(&LEVEL, i as f32 + 0.5).unwrap();
+            if i == 0 {
+                map.annotate(&START).unwrap();
+                map.next(|_| {});
+            }
+        }
+        let mut tx = Postcard::new(Buffer::<256>::new());
+        tx.send_package(&MODULE, &map).unwrap();
+        let bytes = tx.free();
+        let package: Package = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            package,
+            Package {
+                module: "module",
+                signals: std::vec![
+                    Signal {
+                        name: "on",
+                        values: Values::Bool(std::vec![false, true]),
+                    },
+                    Signal {
+                        name: "count",
+                        values: Values::Int(std::vec![0, -1]),
+                    },
+                    Signal {
+                        name: "level",
+                        values: Values::Float(std::vec![0.5, 1.5]),
+                    },
+                ],
+                notes: std::vec![Note {
+                    text: "start",
+                    slot: 0,
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn shared_layers() {
+        let mut map = FnvMap::<2, 2>::new();
+        map.set(&COUNT, 3).unwrap();
+        let mut tx = TransportBuilder::new(Buffer::<256>::new())
+            .cobs()
+            .postcard()
+            .build();
+        tx.send_package(&MODULE, &map).unwrap();
+        let mut bytes = tx.free().free();
+        let package: Package = postcard::from_bytes_cobs(&mut bytes).unwrap();
+        assert_eq!(package.signals[0].values, Values::Int(std::vec![3, 0]));
+    }
+}
@@ -1,3 +1,4 @@
 pub use crate::NextValue as _svisual_NextValue;
 pub use crate::SendPackage as _svisual_SendPackage;
-pub use crate::{Name as SVName, SVMap};
+pub use crate::SvSignals as _svisual_SvSignals;
+pub use crate::{ModuleName, Name as SVName, SVMap};
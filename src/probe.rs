@@ -0,0 +1,54 @@
+use heapless::Vec;
+
+use crate::buffer::BufferOverflow;
+use crate::WriteIter;
+
+/// Transport logging each frame as defmt byte slice, e.g. over RTT of probe-rs connected target.
+///
+/// Frame is collected in buffer of `B` bytes and logged at `info` level at the end of frame,
+/// so packages share the channel with ordinary log messages.
+/// Host tool takes byte slices of `svisual` messages from decoded log and passes them to server
+pub struct DefmtTx<const B: usize> {
+    frame: Vec<u8, B>,
+}
+
+impl<const B: usize> DefmtTx<B> {
+    /// Create transport
+    pub const fn new() -> Self {
+        Self { frame: Vec::new() }
+    }
+}
+
+impl<const B: usize> Default for DefmtTx<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const B: usize> WriteIter for DefmtTx<B> {
+    type Error = BufferOverflow;
+
+    fn bwrite_iter<WI>(&mut self, bytes: WI) -> Result<(), Self::Error>
+    where
+        WI: Iterator<Item = u8>,
+    {
+        for b in bytes {
+            if self.frame.push(b).is_err() {
+                // Frame is dropped, next write starts new one
+                self.frame.clear();
+                return Err(BufferOverflow);
+            }
+        }
+        Ok(())
+    }
+
+    fn bflush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn bend_frame(&mut self) -> Result<(), Self::Error> {
+        defmt::info!("svisual {=[u8]}", &self.frame[..]);
+        self.frame.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use crate::store::SignalStore;
+use crate::{Name, SVStruct, ValueType};
+
+/// Transformation of signal in profile
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transform {
+    /// Signal is never recorded
+    Exclude,
+    /// `value * scale + offset`
+    Linear {
+        /// Multiplier
+        scale: f32,
+        /// Added offset
+        offset: f32,
+    },
+    /// Map `min..=max` range to `0..=1` for `Float` and `0..=100` for `Int` signals
+    Normalize {
+        /// Value mapped to 0
+        min: f32,
+        /// Value mapped to 1 (or 100)
+        max: f32,
+    },
+}
+
+/// Transformation of one signal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProfileRule {
+    /// Signal name
+    pub name: Name,
+    /// Applied transformation
+    pub transform: Transform,
+}
+
+impl Transform {
+    /// Transform `i32` representation of value. Bool values are not changed
+    pub fn apply(&self, vtype: ValueType, val: i32) -> i32 {
+        let (scale, offset) = match *self {
+            Self::Exclude => return val,
+            Self::Linear { scale, offset } => (scale, offset),
+            Self::Normalize { min, max } => {
+                let range = if vtype == ValueType::Int { 100. } else { 1. };
+                let scale = range / (max - min);
+                (scale, -min * scale)
+            }
+        };
+        match vtype {
+            ValueType::Bool => val,
+            ValueType::Int => round(val as f32 * scale + offset),
+            ValueType::Float => (f32::from_bits(val as u32) * scale + offset).to_bits() as i32,
+        }
+    }
+}
+
+fn round(x: f32) -> i32 {
+    if x < 0. {
+        (x - 0.5) as i32
+    } else {
+        (x + 0.5) as i32
+    }
+}
+
+impl<S: SignalStore> SVStruct<S> {
+    /// Set profile transforming or excluding selected signals,
+    /// e.g. to hide absolute values in customer-facing builds:
+    /// `#[cfg(not(debug_assertions))] map.set_profile(&RELEASE_PROFILE)`
+    pub fn set_profile(&mut self, profile: &'static [ProfileRule]) {
+        self.profile = profile;
+    }
+
+    /// Apply profile to value, `None` if signal is excluded
+    pub(crate) fn apply_profile(&self, name: &Name, vtype: ValueType, val: i32) -> Option<i32> {
+        match self.profile.iter().find(|r| r.name == *name) {
+            Some(ProfileRule {
+                transform: Transform::Exclude,
+                ..
+            }) => None,
+            Some(r) => Some(r.transform.apply(vtype, val)),
+            None => Some(val),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const RAW: SVName = SVName::new("raw");
+    const LEVEL: SVName = SVName::new("level");
+    const RATIO: SVName = SVName::new("ratio");
+    const SECRET: SVName = SVName::new("secret");
+    const FLAG: SVName = SVName::new("flag");
+
+    static PROFILE: [ProfileRule; 4] = [
+        ProfileRule {
+            name: LEVEL,
+            transform: Transform::Normalize { min: 10., max: 20. },
+        },
+        ProfileRule {
+            name: RATIO,
+            transform: Transform::Normalize { min: 10., max: 20. },
+        },
+        ProfileRule {
+            name: SECRET,
+            transform: Transform::Exclude,
+        },
+        ProfileRule {
+            name: FLAG,
+            transform: Transform::Linear {
+                scale: 3.,
+                offset: 1.,
+            },
+        },
+    ];
+
+    #[test]
+    fn transforms() {
+        let linear = Transform::Linear {
+            scale: 0.5,
+            offset: -1.,
+        };
+        assert_eq!(linear.apply(ValueType::Int, 5), 2);
+        assert_eq!(linear.apply(ValueType::Int, -5), -4);
+        assert_eq!(
+            linear.apply(ValueType::Float, 4f32.to_bits() as i32),
+            1f32.to_bits() as i32
+        );
+        assert_eq!(linear.apply(ValueType::Bool, 1), 1);
+        assert_eq!(Transform::Exclude.apply(ValueType::Int, 7), 7);
+    }
+
+    #[test]
+    fn applied_on_update() {
+        let mut map = SVMap::<5, 2>::new();
+        map.set_profile(&PROFILE);
+        map.set(&RAW, 15).unwrap();
+        map.set(&LEVEL, 15).unwrap();
+        map.set(&RATIO, 15.).unwrap();
+        map.set(&SECRET, 1).unwrap();
+        map.set(&FLAG, true).unwrap();
+        assert_eq!(map.get(&RAW), Some(15));
+        assert_eq!(map.get(&LEVEL), Some(50));
+        assert_eq!(map.get(&RATIO), Some(0.5));
+        assert_eq!(map.get::<i32, _>(&SECRET), None);
+        assert_eq!(map.get(&FLAG), Some(true));
+    }
+}
@@ -0,0 +1,106 @@
+use core::iter::repeat;
+use core::marker::PhantomData;
+
+use crate::{write_package_with, Name, SVMap, SendPackage, WriteIter, NAME_SIZE};
+
+/// Constants of package wire format.
+///
+/// Layout is kept: marker, little-endian `u32` size, module name,
+/// records and closing marker. Size of name field is selected by `name-*` features
+pub trait Protocol {
+    /// Marker opening package
+    const BEGIN: &'static [u8];
+    /// Marker closing package
+    const END: &'static [u8];
+    /// Byte filling names up to name field size
+    const PADDING: u8;
+}
+
+/// Protocol of original SVisual server
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SVisual;
+
+impl Protocol for SVisual {
+    const BEGIN: &'static [u8] = b"=begin=";
+    const END: &'static [u8] = b"=end=";
+    const PADDING: u8 = 0;
+}
+
+/// Name bytes padded up to name field size with padding of protocol `C`
+pub(crate) fn padded<C: Protocol, I>(name: I) -> impl Iterator<Item = u8> + Clone
+where
+    I: Iterator<Item = u8> + Clone,
+{
+    let len = name.clone().count();
+    name.chain(repeat(C::PADDING).take(NAME_SIZE - len))
+}
+
+/// Write frame header: `marker`, little-endian `u32` body size and padded module name
+pub(crate) fn write_header<C: Protocol, Tx: WriteIter + ?Sized>(
+    tx: &mut Tx,
+    marker: &[u8],
+    size: usize,
+    module: &Name,
+) -> Result<(), Tx::Error> {
+    tx.bwrite_iter(
+        marker
+            .iter()
+            .cloned()
+            .chain((size as u32).to_le_bytes())
+            .chain(padded::<C, _>(module.bytes())),
+    )
+}
+
+/// Close frame with end marker of protocol `C`
+pub(crate) fn write_trailer<C: Protocol, Tx: WriteIter + ?Sized>(
+    tx: &mut Tx,
+) -> Result<(), Tx::Error> {
+    tx.bwrite_iter(C::END.iter().cloned())?;
+    tx.bflush()?;
+    tx.bend_frame()
+}
+
+/// Transport sending packages with markers and padding of protocol `C`,
+/// e.g. for forks of SVisual server.
+///
+/// Decode them with [`PackageView::parse_with`](crate::decoder::PackageView::parse_with)
+pub struct WithProtocol<Tx, C> {
+    tx: Tx,
+    _protocol: PhantomData<C>,
+}
+
+impl<Tx, C: Protocol> WithProtocol<Tx, C> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self {
+            tx,
+            _protocol: PhantomData,
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+impl<Tx, C, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for WithProtocol<Tx, C>
+where
+    Tx: WriteIter,
+    C: Protocol,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        write_package_with::<C, _, _>(
+            &mut self.tx,
+            module,
+            P,
+            values
+                .map
+                .iter()
+                .map(|(&name, v)| (name, v.vtype, &v.vals[..])),
+            &values.notes,
+        )
+    }
+}
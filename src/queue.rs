@@ -0,0 +1,100 @@
+use heapless::spsc::Consumer;
+
+use crate::{AddError, Front, Name, SVMap, SignalHandle, Value, ValueType};
+
+/// Update of registered signal, pushed by interrupt handler into
+/// [`heapless::spsc::Queue`] and applied in main loop by [`SVMap::drain`].
+///
+/// Producer only copies few words, so interrupt latency does not depend
+/// on number of signals in map
+#[derive(Clone, Copy, Debug)]
+pub struct Update {
+    idx: usize,
+    name: &'static Name,
+    vtype: ValueType,
+    front: Front,
+    val: i32,
+}
+
+impl Update {
+    /// Update of signal `handle` with `value`
+    pub fn new<T: Value>(handle: SignalHandle<T>, value: T) -> Self {
+        Self {
+            idx: handle.idx,
+            name: handle.name,
+            vtype: T::TYPE,
+            front: T::FRONT,
+            val: value.to_i32(),
+        }
+    }
+
+    /// Signal name
+    pub fn name(&self) -> &'static Name {
+        self.name
+    }
+}
+
+impl<const N: usize, const P: usize> SVMap<N, P> {
+    /// Apply all queued updates at current time position, call before `next()`.
+    ///
+    /// Several updates of one signal are combined as successive `set` calls,
+    /// each update costs as [`set_handle`](SVMap::set_handle).
+    /// Returns number of applied updates or last error, failed updates are dropped
+    pub fn drain<const Q: usize>(
+        &mut self,
+        rx: &mut Consumer<'_, Update, Q>,
+    ) -> Result<usize, AddError> {
+        let mut applied = 0;
+        let mut error = None;
+        while let Some(u) = rx.dequeue() {
+            match self.set_indexed(u.idx, u.name, u.vtype, u.front, u.val) {
+                Ok(()) => applied += 1,
+                Err(e) => error = Some(e),
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(applied),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use heapless::spsc::Queue;
+
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+
+    #[test]
+    fn drain_updates() {
+        let mut map = SVMap::<2, 2>::new();
+        let a = map.register::<i32>(&A).unwrap();
+        let b = map.register::<f32>(&B).unwrap();
+        let mut queue = Queue::<Update, 4>::new();
+        let (mut tx, mut rx) = queue.split();
+        tx.enqueue(Update::new(a, 1)).unwrap();
+        tx.enqueue(Update::new(b, 0.5)).unwrap();
+        tx.enqueue(Update::new(a, 3)).unwrap();
+        assert_eq!(map.drain(&mut rx), Ok(3));
+        // Successive updates are combined as `set` calls
+        let mut expected = SVMap::<2, 2>::new();
+        expected.set(&A, 1).unwrap();
+        expected.set(&A, 3).unwrap();
+        assert_eq!(map.get::<i32, _>(&A), expected.get(&A));
+        assert_eq!(map.get(&B), Some(0.5));
+        assert_eq!(map.drain(&mut rx), Ok(0));
+
+        // Failed update is dropped, others are applied
+        map.seal();
+        assert!(map.remove(&A));
+        tx.enqueue(Update::new(a, 2)).unwrap();
+        tx.enqueue(Update::new(b, 1.5)).unwrap();
+        assert_eq!(Update::new(a, 2).name(), &A);
+        assert_eq!(map.drain(&mut rx), Err(AddError::NotRegistered));
+        assert_eq!(map.get(&B), Some(1.5));
+        assert!(rx.dequeue().is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{NextValue, SVMap};
+
+/// Group of signals with own package size and tick rate.
+///
+/// Several groups share one base tick and one transport as different modules,
+/// e.g. 10 kHz current loops and 1 Hz temperatures:
+/// `temps.next(|m| uart.send_package(&TEMPS, m).unwrap())` on each 10 kHz tick
+/// with `divider` of 10000
+pub struct RateGroup<const N: usize, const P: usize> {
+    map: SVMap<N, P>,
+    divider: u32,
+    tick: u32,
+}
+
+impl<const N: usize, const P: usize> RateGroup<N, P> {
+    /// Create group going to next time position every `divider` base ticks
+    pub const fn new(divider: u32) -> Self {
+        Self {
+            map: SVMap::new(),
+            divider: if divider == 0 { 1 } else { divider },
+            tick: 0,
+        }
+    }
+
+    /// Checks if next base tick advances time position of group
+    pub fn is_due(&self) -> bool {
+        self.tick + 1 == self.divider
+    }
+
+    /// Count base tick, go to next time position of group every `divider` ticks.
+    /// `F` is send package function
+    pub fn next<F>(&mut self, f: F)
+    where
+        F: FnOnce(&SVMap<N, P>),
+    {
+        self.tick += 1;
+        if self.tick >= self.divider {
+            self.tick = 0;
+            self.map.next(f);
+        }
+    }
+}
+
+impl<const N: usize, const P: usize> core::ops::Deref for RateGroup<N, P> {
+    type Target = SVMap<N, P>;
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<const N: usize, const P: usize> core::ops::DerefMut for RateGroup<N, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::Sample;
+
+    const T: SVName = SVName::new("t");
+
+    #[test]
+    fn divided_ticks() {
+        let mut group = RateGroup::<1, 2>::new(3);
+        let mut sent = 0;
+        for i in 0..6 {
+            assert_eq!(group.is_due(), i % 3 == 2);
+            group.set(&T, i).unwrap();
+            group.next(|m| {
+                // Last value before each time position is recorded
+                assert!(m.samples(&T).unwrap().eq([Sample::Int(2), Sample::Int(5)]));
+                sent += 1;
+            });
+        }
+        assert_eq!(sent, 1);
+        assert!(RateGroup::<1, 2>::new(0).is_due());
+    }
+}
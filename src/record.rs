@@ -0,0 +1,179 @@
+use heapless::Vec;
+
+use crate::decoder::{DecodeError, PackageView};
+use crate::WriteIter;
+
+/// File on littlefs, FAT or other file system.
+///
+/// Mirrors file API of `littlefs2` and `embedded-sdmmc`, so existing drivers are easy to adapt
+pub trait LogFile {
+    /// Error type
+    type Error;
+    /// Append `bytes` to the end of file
+    fn append(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+    /// Read bytes starting at `offset`, returns number of read bytes, 0 at end of file
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+/// Size of write chunk of recorder
+const CHUNK: usize = 64;
+
+/// Transport recording raw packages into file, e.g. black-box log on SD card.
+///
+/// Bytes are appended in chunks, so file system is not called for each byte
+pub struct Recorder<F> {
+    file: F,
+    chunk: Vec<u8, CHUNK>,
+}
+
+impl<F: LogFile> Recorder<F> {
+    /// Record into end of `file`
+    pub fn new(file: F) -> Self {
+        Self {
+            file,
+            chunk: Vec::new(),
+        }
+    }
+
+    /// Release file
+    pub fn free(self) -> F {
+        self.file
+    }
+}
+
+impl<F: LogFile> WriteIter for Recorder<F> {
+    type Error = F::Error;
+
+    fn bwrite_iter<WI>(&mut self, bytes: WI) -> Result<(), Self::Error>
+    where
+        WI: Iterator<Item = u8>,
+    {
+        for b in bytes {
+            if self.chunk.push(b).is_err() {
+                self.file.append(&self.chunk)?;
+                self.chunk.clear();
+                self.chunk.push(b).ok();
+            }
+        }
+        Ok(())
+    }
+
+    fn bflush(&mut self) -> Result<(), Self::Error> {
+        if !self.chunk.is_empty() {
+            self.file.append(&self.chunk)?;
+            self.chunk.clear();
+        }
+        Ok(())
+    }
+}
+
+/// Errors of replay of recorded file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReplayError<F, E> {
+    /// File error
+    File(F),
+    /// Transport error
+    Transport(E),
+    /// Recorded data is not valid package
+    Decode(DecodeError),
+    /// Package does not fit in buffer
+    Overflow,
+}
+
+/// Send packages with `values` values per signal recorded in `file` over `tx`.
+///
+/// Packages are read into buffer of `B` bytes, incomplete package at the end of file is skipped.
+/// Returns number of sent packages
+pub fn replay<F, Tx, const B: usize>(
+    file: &mut F,
+    tx: &mut Tx,
+    values: usize,
+) -> Result<usize, ReplayError<F::Error, Tx::Error>>
+where
+    F: LogFile,
+    Tx: WriteIter,
+{
+    let mut buf = [0; B];
+    let mut filled = 0;
+    let mut offset = 0;
+    let mut sent = 0;
+    loop {
+        let read = file
+            .read(offset, &mut buf[filled..])
+            .map_err(ReplayError::File)?;
+        offset += read as u32;
+        filled += read;
+        loop {
+            let len = match PackageView::parse(&buf[..filled], values) {
+                Ok((_, rest)) => filled - rest.len(),
+                Err(DecodeError::Incomplete) => break,
+                Err(e) => return Err(ReplayError::Decode(e)),
+            };
+            tx.bwrite_iter(buf[..len].iter().cloned())
+                .map_err(ReplayError::Transport)?;
+            tx.bflush().map_err(ReplayError::Transport)?;
+            tx.bend_frame().map_err(ReplayError::Transport)?;
+            buf.copy_within(len..filled, 0);
+            filled -= len;
+            sent += 1;
+        }
+        if filled == B {
+            return Err(ReplayError::Overflow);
+        }
+        if read == 0 {
+            return Ok(sent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    /// File reading at most 50 bytes at once
+    struct Ram(Vec<u8, 4096>);
+
+    impl LogFile for Ram {
+        type Error = ();
+        fn append(&mut self, bytes: &[u8]) -> Result<(), ()> {
+            self.0.extend_from_slice(bytes)
+        }
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<usize, ()> {
+            let rest = self.0.get(offset as usize..).ok_or(())?;
+            let len = rest.len().min(bytes.len()).min(50);
+            bytes[..len].copy_from_slice(&rest[..len]);
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let mut map = SVMap::<1, 2>::new();
+        let mut recorder = Recorder::new(Ram(Vec::new()));
+        for i in 0..6 {
+            map.set(&A, i).unwrap();
+            map.next(|m| recorder.send_package(&MODULE, m).unwrap());
+        }
+        let mut file = recorder.free();
+        let recorded = file.0.len();
+        assert!(recorded > CHUNK);
+        // Package interrupted by power loss
+        let mut partial = [0; 10];
+        partial.copy_from_slice(&file.0[..10]);
+        file.append(&partial).unwrap();
+
+        let mut tx = Mock::<2048>::new();
+        assert_eq!(replay::<_, _, 512>(&mut file, &mut tx, 2), Ok(3));
+        assert_eq!(tx.as_slice(), &file.0[..recorded]);
+        assert_eq!(
+            replay::<_, _, 16>(&mut file, &mut Mock::<2048>::new(), 2),
+            Err(ReplayError::Overflow)
+        );
+    }
+}
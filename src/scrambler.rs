@@ -0,0 +1,138 @@
+use crate::WriteIter;
+
+/// Feedback taps of `x^7 + x^6 + 1` polynomial
+pub const PRBS7: u32 = (1 << 6) | (1 << 5);
+/// Feedback taps of `x^15 + x^14 + 1` polynomial
+pub const PRBS15: u32 = (1 << 14) | (1 << 13);
+
+/// Self-synchronizing LFSR scrambler.
+///
+/// Each bit is XORed with parity of tapped previously scrambled bits,
+/// so descrambler recovers by itself shortly after byte loss
+#[derive(Clone, Copy, Debug)]
+pub struct Scrambler {
+    taps: u32,
+    state: u32,
+}
+
+impl Scrambler {
+    /// Create scrambler with feedback `taps` (bit `i` is delay `i + 1`)
+    pub const fn new(taps: u32) -> Self {
+        Self { taps, state: 0 }
+    }
+
+    /// Scramble byte, LSB first
+    pub fn scramble(&mut self, byte: u8) -> u8 {
+        let mut out = 0;
+        for i in 0..8 {
+            let bit = ((byte >> i) & 1) as u32 ^ self.feedback();
+            self.state = (self.state << 1) | bit;
+            out |= (bit as u8) << i;
+        }
+        out
+    }
+
+    /// Descramble byte, LSB first
+    pub fn descramble(&mut self, byte: u8) -> u8 {
+        let mut out = 0;
+        for i in 0..8 {
+            let bit = ((byte >> i) & 1) as u32;
+            out |= ((bit ^ self.feedback()) as u8) << i;
+            self.state = (self.state << 1) | bit;
+        }
+        out
+    }
+
+    /// Descramble bytes in place
+    pub fn descramble_in_place(&mut self, bytes: &mut [u8]) {
+        for b in bytes {
+            *b = self.descramble(*b);
+        }
+    }
+
+    fn feedback(&self) -> u32 {
+        (self.state & self.taps).count_ones() & 1
+    }
+}
+
+/// Transport that whitens all bytes written to it
+pub struct Scrambled<Tx> {
+    tx: Tx,
+    scrambler: Scrambler,
+}
+
+impl<Tx> Scrambled<Tx> {
+    /// Wrap transport with scrambler using feedback `taps`
+    pub const fn new(tx: Tx, taps: u32) -> Self {
+        Self {
+            tx,
+            scrambler: Scrambler::new(taps),
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+impl<Tx: WriteIter> WriteIter for Scrambled<Tx> {
+    type Error = Tx::Error;
+
+    fn bwrite_iter<WI>(&mut self, bytes: WI) -> Result<(), Self::Error>
+    where
+        WI: Iterator<Item = u8>,
+    {
+        let scrambler = &mut self.scrambler;
+        self.tx.bwrite_iter(bytes.map(|b| scrambler.scramble(b)))
+    }
+
+    fn bflush(&mut self) -> Result<(), Self::Error> {
+        self.tx.bflush()
+    }
+
+    fn bend_frame(&mut self) -> Result<(), Self::Error> {
+        self.tx.bend_frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+
+    fn data() -> [u8; 32] {
+        let mut data = [0; 32];
+        for (i, b) in data.iter_mut().enumerate() {
+            *b = (i as u8).wrapping_mul(37) ^ 0x5a;
+        }
+        data
+    }
+
+    #[test]
+    fn round_trip() {
+        for &taps in &[PRBS7, PRBS15] {
+            let mut tx = Scrambled::new(Mock::<64>::new(), taps);
+            tx.bwrite_iter(data().iter().cloned()).unwrap();
+            tx.bend_frame().unwrap();
+            let mut bytes = [0; 32];
+            bytes.copy_from_slice(tx.free().as_slice());
+            assert_ne!(bytes, data());
+            Scrambler::new(taps).descramble_in_place(&mut bytes);
+            assert_eq!(bytes, data());
+        }
+    }
+
+    #[test]
+    fn resynchronizes_after_loss() {
+        let mut scrambler = Scrambler::new(PRBS15);
+        let mut bytes = data();
+        bytes.iter_mut().for_each(|b| *b = scrambler.scramble(*b));
+        // First byte is lost, descrambler starts in wrong state
+        let mut received = [0; 31];
+        received.copy_from_slice(&bytes[1..]);
+        Scrambler::new(PRBS15).descramble_in_place(&mut received);
+        // State is filled with received bits after 15 bits
+        assert_eq!(received[2..], data()[3..]);
+    }
+}
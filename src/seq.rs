@@ -0,0 +1,178 @@
+use core::iter::once;
+
+use crate::decoder::PackageView;
+use crate::{write_package, Name, SVMap, SendPackage, ValueType, WriteIter};
+
+/// Name of record with package sequence number
+pub const SEQ_NAME: Name = Name::new("@seq");
+
+/// Name of record with number of packages dropped by transport errors
+pub const DROPPED_NAME: Name = Name::new("@dropped");
+
+/// Transport numbering packages.
+///
+/// Sequence number and counter of packages dropped by transport errors
+/// are sent as `Int` records [`SEQ_NAME`] and [`DROPPED_NAME`],
+/// so they are plotted as diagnostic signals and host can detect lost packages.
+/// Stack it over byte level layers, e.g. `Sequenced::new(Checked::<_, Crc16>::new(uart))`,
+/// so records are covered by checksum
+pub struct Sequenced<Tx> {
+    tx: Tx,
+    seq: u32,
+    dropped: u32,
+}
+
+impl<Tx> Sequenced<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self {
+            tx,
+            seq: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+
+    /// Sequence number of next package
+    pub fn seq(&self) -> u32 {
+        self.seq
+    }
+
+    /// Number of packages dropped by transport errors
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+impl<Tx, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for Sequenced<Tx>
+where
+    Tx: WriteIter,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        let seq = [self.seq as i32; P];
+        let dropped = [self.dropped as i32; P];
+        self.seq = self.seq.wrapping_add(1);
+        write_package(
+            &mut self.tx,
+            module,
+            P,
+            once((&*SEQ_NAME, ValueType::Int, &seq[..]))
+                .chain(once((&*DROPPED_NAME, ValueType::Int, &dropped[..])))
+                .chain(
+                    values
+                        .map
+                        .iter()
+                        .map(|(&name, v)| (name, v.vtype, &v.vals[..])),
+                ),
+            &values.notes,
+        )
+        .map_err(|e| {
+            self.dropped = self.dropped.wrapping_add(1);
+            e
+        })
+    }
+}
+
+impl PackageView<'_> {
+    /// Sequence number if package is numbered
+    pub fn sequence(&self) -> Option<u32> {
+        self.records()
+            .find(|r| r.name() == SEQ_NAME.as_bytes())
+            .and_then(|r| r.get(0))
+            .map(|s| s as u32)
+    }
+}
+
+/// Host-side checker of package sequence numbers
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SeqTracker {
+    next: Option<u32>,
+    lost: u32,
+}
+
+impl SeqTracker {
+    /// Create checker
+    pub const fn new() -> Self {
+        Self {
+            next: None,
+            lost: 0,
+        }
+    }
+
+    /// Register received sequence number. Returns number of packages lost before it
+    pub fn check(&mut self, seq: u32) -> u32 {
+        let lost = match self.next {
+            Some(next) => seq.wrapping_sub(next),
+            None => 0,
+        };
+        // Number from the past means restart of device
+        let lost = if lost > u32::MAX / 2 { 0 } else { lost };
+        self.lost = self.lost.wrapping_add(lost);
+        self.next = Some(seq.wrapping_add(1));
+        lost
+    }
+
+    /// Total number of lost packages
+    pub fn lost(&self) -> u32 {
+        self.lost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn numbered_packages() {
+        let mut map = SVMap::<1, 2>::new();
+        map.set(&A, 1).unwrap();
+        let mut tx = Sequenced::new(Buffer::<2048>::new());
+        tx.send_package(&MODULE, &map).unwrap();
+        tx.send_package(&MODULE, &map).unwrap();
+        assert_eq!(tx.seq(), 2);
+        let bytes = tx.free();
+        let (first, rest) = PackageView::parse(&bytes, 2).unwrap();
+        let (second, _) = PackageView::parse(rest, 2).unwrap();
+        assert_eq!(first.sequence(), Some(0));
+        assert_eq!(second.sequence(), Some(1));
+        assert!(second.records().any(|r| r.name() == A.as_bytes()));
+
+        let mut tx = Sequenced::new(Mock::<2048>::new());
+        tx.tx.fail_after(0);
+        tx.send_package(&MODULE, &map).unwrap_err();
+        assert_eq!((tx.seq(), tx.dropped()), (1, 1));
+        tx.tx.recover();
+        tx.send_package(&MODULE, &map).unwrap();
+        let (view, _) = PackageView::parse(tx.tx.as_slice(), 2).unwrap();
+        let dropped = view.records().find(|r| r.name() == DROPPED_NAME.as_bytes());
+        assert_eq!(view.sequence(), Some(1));
+        assert_eq!(dropped.and_then(|r| r.get(0)), Some(1));
+    }
+
+    #[test]
+    fn tracker() {
+        let mut tracker = SeqTracker::new();
+        assert_eq!(tracker.check(5), 0);
+        assert_eq!(tracker.check(6), 0);
+        assert_eq!(tracker.check(9), 2);
+        // Restart of device
+        assert_eq!(tracker.check(0), 0);
+        assert_eq!(tracker.lost(), 2);
+
+        let mut tracker = SeqTracker::new();
+        tracker.check(u32::MAX);
+        assert_eq!(tracker.check(1), 1);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{write_package, Name, SVMap, SendPackage, WriteIter};
+
+/// Transport sending only signals changed since previous package.
+///
+/// Packages keep ordinary format, number of signals follows from package size.
+/// Absent signal holds last value of previous package.
+/// Full package is sent first and then every `refresh` packages,
+/// so receiver connected later learns all signals
+pub struct Sparse<Tx> {
+    tx: Tx,
+    refresh: u32,
+    count: u32,
+}
+
+impl<Tx> Sparse<Tx> {
+    /// Wrap transport. `refresh` 0 sends only first package full
+    pub fn new(tx: Tx, refresh: u32) -> Self {
+        Self {
+            tx,
+            refresh,
+            count: 0,
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+
+    /// Request full package next time, e.g. when receiver reconnects
+    pub fn refresh(&mut self) {
+        self.count = 0;
+    }
+}
+
+impl<Tx, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for Sparse<Tx>
+where
+    Tx: WriteIter,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        let full = self.count == 0;
+        self.count = self.count.saturating_add(1);
+        if self.count == self.refresh {
+            self.count = 0;
+        }
+        write_package(
+            &mut self.tx,
+            module,
+            P,
+            values
+                .map
+                .iter()
+                .filter(|(_, v)| full || v.changed())
+                .map(|(&name, v)| (name, v.vtype, &v.vals[..])),
+            &values.notes,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const MOVING: SVName = SVName::new("moving");
+    const STEADY: SVName = SVName::new("steady");
+
+    fn contains(data: &[u8], part: &[u8]) -> bool {
+        data.windows(part.len()).any(|w| w == part)
+    }
+
+    #[test]
+    fn changed_and_refresh() {
+        let mut map = SVMap::<2, 1>::new();
+        let mut tx = Sparse::new(Mock::<2048>::new(), 3);
+        let mut sent = [(false, false); 6];
+        for (i, s) in sent.iter_mut().enumerate() {
+            if i == 2 {
+                tx.refresh();
+            }
+            map.set(&MOVING, i as i32).unwrap();
+            map.set(&STEADY, 1).unwrap();
+            map.next(|m| tx.send_package(&MODULE, m).unwrap());
+            let data = tx.tx.as_slice();
+            *s = (contains(data, b"moving"), contains(data, b"steady"));
+            tx.tx.clear();
+        }
+        // Full package first, then every 3 packages or on request
+        assert_eq!(
+            sent,
+            [
+                (true, true),
+                (true, false),
+                (true, true),
+                (true, false),
+                (true, false),
+                (true, true),
+            ]
+        );
+    }
+}
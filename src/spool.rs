@@ -0,0 +1,255 @@
+use crate::buffer::{Buffer, BufferOverflow};
+use crate::storage::Storage;
+use crate::{Name, SendPackage, WriteIter};
+
+/// Errors of spooling transport
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpoolError<E> {
+    /// Package does not fit in buffer
+    Overflow,
+    /// Storage error
+    Storage(E),
+}
+
+/// Transport saving packages into storage region while link is down.
+///
+/// Packages that transport fails to send are appended to region `base..base + capacity`
+/// as little-endian `u16` length and package bytes. Region is used as ring buffer,
+/// so space of replayed packages is reused at once. Saved packages are replayed in order
+/// before next package once transport works again, packages that do not fit are dropped.
+/// Positions in region are kept in RAM, so spool is lost on reset.
+///
+/// With `embedded-storage` feature any flash or EEPROM driver can be used as `storage`
+pub struct Spool<Tx, S, const B: usize> {
+    tx: Tx,
+    storage: S,
+    base: u32,
+    capacity: u32,
+    /// Ring position of first saved package
+    head: u32,
+    /// Size of saved packages
+    used: u32,
+    dropped: u32,
+}
+
+fn send_raw<Tx: WriteIter>(tx: &mut Tx, bytes: &[u8]) -> Result<(), Tx::Error> {
+    tx.bwrite_iter(bytes.iter().cloned())?;
+    tx.bflush()?;
+    tx.bend_frame()
+}
+
+impl<Tx: WriteIter, S: Storage, const B: usize> Spool<Tx, S, B> {
+    /// Compile-time check that package length fits in saved `u16`
+    const VALID: () = assert!(B <= u16::MAX as usize, "B does not fit in u16");
+
+    /// Wrap transport, spool into `capacity` bytes of `storage` starting at `base`
+    pub fn new(tx: Tx, storage: S, base: u32, capacity: u32) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID;
+        Self {
+            tx,
+            storage,
+            base,
+            capacity,
+            head: 0,
+            used: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Release transport and storage
+    pub fn free(self) -> (Tx, S) {
+        (self.tx, self.storage)
+    }
+
+    /// Checks if there are no saved packages
+    pub fn is_empty(&self) -> bool {
+        self.used == 0
+    }
+
+    /// Size of saved packages in bytes
+    pub fn pending(&self) -> u32 {
+        self.used
+    }
+
+    /// Number of packages dropped because region was full
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Send saved packages in order. Returns `true` if all packages are sent
+    pub fn replay(&mut self) -> Result<bool, SpoolError<S::Error>> {
+        let mut package = [0; B];
+        while !self.is_empty() {
+            let mut len = [0; 2];
+            self.read_at(self.head, &mut len)?;
+            let len = u16::from_le_bytes(len) as usize;
+            let package = package.get_mut(..len).ok_or(SpoolError::Overflow)?;
+            self.read_at(self.advance(self.head, 2), package)?;
+            if send_raw(&mut self.tx, package).is_err() {
+                return Ok(false);
+            }
+            self.head = self.advance(self.head, 2 + len as u32);
+            self.used -= 2 + len as u32;
+        }
+        Ok(true)
+    }
+
+    fn save(&mut self, package: &[u8]) -> Result<(), SpoolError<S::Error>> {
+        let size = 2 + package.len() as u32;
+        if size > self.capacity - self.used {
+            self.dropped = self.dropped.wrapping_add(1);
+            return Ok(());
+        }
+        let tail = self.advance(self.head, self.used);
+        self.write_at(tail, &(package.len() as u16).to_le_bytes())?;
+        self.write_at(self.advance(tail, 2), package)?;
+        self.used += size;
+        Ok(())
+    }
+
+    /// Ring position `n` bytes after `pos`
+    fn advance(&self, pos: u32, n: u32) -> u32 {
+        ((pos as u64 + n as u64) % self.capacity as u64) as u32
+    }
+
+    /// Storage offset of ring position `pos` and number of bytes of `len`
+    /// that fit before end of region, the rest continues from `base`
+    fn split(&self, pos: u32, len: usize) -> (u32, usize) {
+        let first = len.min((self.capacity - pos) as usize);
+        (self.base + pos, first)
+    }
+
+    fn read_at(&mut self, pos: u32, bytes: &mut [u8]) -> Result<(), SpoolError<S::Error>> {
+        let (offset, first) = self.split(pos, bytes.len());
+        let (head, tail) = bytes.split_at_mut(first);
+        self.storage
+            .read(offset, head)
+            .map_err(SpoolError::Storage)?;
+        if !tail.is_empty() {
+            self.storage
+                .read(self.base, tail)
+                .map_err(SpoolError::Storage)?;
+        }
+        Ok(())
+    }
+
+    fn write_at(&mut self, pos: u32, bytes: &[u8]) -> Result<(), SpoolError<S::Error>> {
+        let (offset, first) = self.split(pos, bytes.len());
+        let (head, tail) = bytes.split_at(first);
+        self.storage
+            .write(offset, head)
+            .map_err(SpoolError::Storage)?;
+        if !tail.is_empty() {
+            self.storage
+                .write(self.base, tail)
+                .map_err(SpoolError::Storage)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Tx, S, V, const B: usize> SendPackage<V> for Spool<Tx, S, B>
+where
+    Tx: WriteIter,
+    S: Storage,
+    Buffer<B>: SendPackage<V, Error = BufferOverflow>,
+{
+    type Error = SpoolError<S::Error>;
+
+    fn send_package(&mut self, module: &Name, values: &V) -> Result<(), Self::Error> {
+        let mut package = Buffer::new();
+        package
+            .send_package(module, values)
+            .map_err(|_| SpoolError::Overflow)?;
+        if self.replay()? && send_raw(&mut self.tx, &package).is_ok() {
+            return Ok(());
+        }
+        self.save(&package)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+    use crate::storage::tests::Ram;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+    /// Start of spool region in storage
+    const BASE: u32 = 8;
+
+    fn map(i: i32) -> SVMap<1, 2> {
+        let mut map = SVMap::new();
+        map.set(&A, i).unwrap();
+        map
+    }
+
+    /// Package of `map(i)` as sent by transport
+    fn package(i: i32) -> Buffer<2048> {
+        let mut buf = Buffer::new();
+        buf.send_package(&MODULE, &map(i)).unwrap();
+        buf
+    }
+
+    type TestSpool = Spool<Mock<2048>, Ram<1024>, 2048>;
+
+    /// Spool with space for 2.5 saved packages
+    fn spool() -> (TestSpool, u32) {
+        let size = 2 + package(0).len() as u32;
+        let mut tx = Mock::new();
+        tx.fail_after(0);
+        (Spool::new(tx, Ram([0; 1024]), BASE, size * 5 / 2), size)
+    }
+
+    fn concat(packages: &[i32]) -> heapless::Vec<u8, 2048> {
+        let mut bytes = heapless::Vec::new();
+        for &i in packages {
+            bytes.extend_from_slice(&package(i)).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn replay_in_order_and_drop_when_full() {
+        let (mut spool, size) = spool();
+        for i in 0..3 {
+            spool.send_package(&MODULE, &map(i)).unwrap();
+        }
+        assert_eq!(spool.pending(), 2 * size);
+        assert_eq!(spool.dropped(), 1);
+        assert!(!spool.replay().unwrap());
+        // Region starts at `BASE`
+        assert_eq!(spool.storage.0[..BASE as usize], [0; BASE as usize]);
+
+        spool.tx.recover();
+        spool.tx.clear();
+        spool.send_package(&MODULE, &map(3)).unwrap();
+        assert!(spool.is_empty());
+        assert_eq!(spool.tx.as_slice(), &*concat(&[0, 1, 3]));
+    }
+
+    #[test]
+    fn wrap_around() {
+        let (mut spool, size) = spool();
+        spool.send_package(&MODULE, &map(0)).unwrap();
+        spool.send_package(&MODULE, &map(1)).unwrap();
+
+        // Only first saved package is replayed
+        spool.tx.fail_after(size as usize - 2 + 1);
+        spool.send_package(&MODULE, &map(2)).unwrap();
+        assert_eq!(spool.pending(), 2 * size);
+        assert_eq!(spool.dropped(), 0);
+        // Package 2 is saved over end of region
+        assert!(spool.head + spool.used > spool.capacity);
+
+        spool.tx.recover();
+        spool.tx.clear();
+        spool.send_package(&MODULE, &map(3)).unwrap();
+        assert!(spool.is_empty());
+        assert_eq!(spool.tx.as_slice(), &*concat(&[1, 2, 3]));
+    }
+}
@@ -0,0 +1,137 @@
+use heapless::Vec;
+
+use crate::{write_package, Name, SVMap, SendPackage, ValueType, WriteIter};
+
+/// First byte of stale flag record name
+pub const STALE_PREFIX: u8 = b'!';
+
+impl<const N: usize, const P: usize> SVMap<N, P> {
+    /// Checks if signal was not set since start of current package,
+    /// e.g. dead sensor. Check it at `is_last()` or in send package function.
+    ///
+    /// Returns `None` if signal is absent
+    pub fn is_stale(&self, name: &Name) -> Option<bool> {
+        self.map.get(&**name).map(|vr| !vr.fresh)
+    }
+
+    /// Signals that were not set since start of current package
+    pub fn stale(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.map
+            .iter()
+            .filter(|(_, vr)| !vr.fresh)
+            .map(|(&name, _)| name)
+    }
+}
+
+/// Name of stale flag: prefix and signal name truncated to fit in name
+fn flag_name(name: &str) -> Name {
+    let mut end = name.len().min(Name::MAX_SIZE - 2);
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut buf = [0; Name::MAX_SIZE];
+    buf[0] = STALE_PREFIX;
+    buf[1..end + 1].copy_from_slice(&name.as_bytes()[..end]);
+    Name {
+        buf,
+        len: end as u8 + 1,
+    }
+}
+
+/// Transport appending stale flag of each signal to package.
+///
+/// Flags are `Bool` records named `!name` that are high during whole package
+/// if signal was not set in it. Package size is doubled
+pub struct StaleFlags<Tx> {
+    tx: Tx,
+}
+
+impl<Tx> StaleFlags<Tx> {
+    /// Wrap transport
+    pub fn new(tx: Tx) -> Self {
+        Self { tx }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+}
+
+impl<Tx, const N: usize, const P: usize> SendPackage<SVMap<N, P>> for StaleFlags<Tx>
+where
+    Tx: WriteIter,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &SVMap<N, P>) -> Result<(), Self::Error> {
+        let flags: Vec<(Name, [i32; P]), N> = values
+            .map
+            .iter()
+            .map(|(&name, vr)| (flag_name(name), [!vr.fresh as i32; P]))
+            .collect();
+        write_package(
+            &mut self.tx,
+            module,
+            P,
+            values
+                .map
+                .iter()
+                .map(|(&name, v)| (name, v.vtype, &v.vals[..]))
+                .chain(
+                    flags
+                        .iter()
+                        .map(|(name, vals)| (&**name, ValueType::Bool, &vals[..])),
+                ),
+            &values.notes,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const ALIVE: SVName = SVName::new("alive");
+    const DEAD: SVName = SVName::new("dead");
+
+    fn contains(data: &[u8], part: &[u8]) -> bool {
+        data.windows(part.len()).any(|w| w == part)
+    }
+
+    #[test]
+    fn stale_signals() {
+        let mut map = SVMap::<2, 2>::new();
+        let mut tx = StaleFlags::new(Mock::<2048>::new());
+        map.set(&ALIVE, 1).unwrap();
+        map.set(&DEAD, 1).unwrap();
+        map.next(|_| {});
+        map.set(&ALIVE, 2).unwrap();
+        assert_eq!(map.is_stale(&DEAD), Some(false));
+        map.next(|m| tx.send_package(&MODULE, m).unwrap());
+        for i in 0..2 {
+            assert_eq!(map.is_stale(&DEAD), Some(true));
+            map.set(&ALIVE, i).unwrap();
+        }
+        assert_eq!(map.is_stale(&ALIVE), Some(false));
+        assert_eq!(map.is_stale(&SVName::new("absent")), None);
+        map.next(|m| {
+            assert!(m.stale().eq(["dead"]));
+            tx.send_package(&MODULE, m).unwrap();
+        });
+        let tx = tx.free();
+        assert!(contains(tx.as_slice(), b"!dead"));
+        assert!(contains(tx.as_slice(), b"!alive"));
+    }
+
+    #[test]
+    fn flag_name_truncated() {
+        let long = [b'x'; Name::MAX_SIZE];
+        let flag = flag_name(core::str::from_utf8(&long).unwrap());
+        assert_eq!(flag.len(), Name::MAX_SIZE - 1);
+        assert!(flag.starts_with('!'));
+    }
+}
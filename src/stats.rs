@@ -0,0 +1,200 @@
+use heapless::{String, Vec};
+
+use crate::{
+    write_package, AddError, Name, NameError, SVMap, Sample, SendPackage, ValueType, WriteIter,
+    NAME_SIZE,
+};
+
+/// Suffixes of derived signals
+const SUFFIXES: [&str; 3] = ["_min", "_max", "_mean"];
+
+/// Statistics of one source signal
+struct Tracker<const P: usize> {
+    source: &'static Name,
+    names: [Name; 3],
+    min: f32,
+    max: f32,
+    sum: f32,
+    count: u32,
+    vals: [[i32; P]; 3],
+}
+
+/// Envelopes of signals sent as low rate stream.
+///
+/// For each tracked signal `name_min`, `name_max` and `name_mean` `Float` signals
+/// are computed over `window` time positions of source map, one time position
+/// of statistics package per window. `K` is maximum number of tracked signals
+pub struct Stats<const K: usize, const P: usize> {
+    trackers: Vec<Tracker<P>, K>,
+    window: u32,
+    tick: u32,
+    current: usize,
+}
+
+fn suffixed(base: &Name, suffix: &str) -> Result<Name, NameError> {
+    let mut s: String<NAME_SIZE> = String::new();
+    s.push_str(base)
+        .and_then(|_| s.push_str(suffix))
+        .map_err(|_| NameError::TooLong)?;
+    Name::try_new(&s)
+}
+
+impl<const K: usize, const P: usize> Stats<K, P> {
+    /// Create statistics over `window` time positions
+    pub const fn new(window: u32) -> Self {
+        Self {
+            trackers: Vec::new(),
+            window: if window == 0 { 1 } else { window },
+            tick: 0,
+            current: 0,
+        }
+    }
+
+    /// Compute statistics of signal
+    pub fn track(&mut self, name: &'static Name) -> Result<(), AddError> {
+        if self.trackers.iter().any(|t| t.source == name) {
+            return Ok(());
+        }
+        let mut names = [*name; 3];
+        for (n, suffix) in names.iter_mut().zip(SUFFIXES.iter()) {
+            *n = suffixed(name, suffix)?;
+        }
+        self.trackers
+            .push(Tracker {
+                source: name,
+                names,
+                min: f32::INFINITY,
+                max: f32::NEG_INFINITY,
+                sum: 0.,
+                count: 0,
+                vals: [[0; P]; 3],
+            })
+            .map_err(|_| AddError::MapOverflow)
+    }
+
+    /// Stop computing statistics of signal. Returns `false` if signal is not tracked
+    pub fn untrack(&mut self, name: &Name) -> bool {
+        match self.trackers.iter().position(|t| t.source == name) {
+            Some(idx) => {
+                self.trackers.swap_remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Accumulate values at current time position of `map`, call before its `next()`.
+    /// `F` is send package function of statistics stream
+    pub fn next<F, const N: usize, const MP: usize>(&mut self, map: &SVMap<N, MP>, f: F)
+    where
+        F: FnOnce(&Self),
+    {
+        for t in self.trackers.iter_mut() {
+            if let Some(vr) = map.map.get(&**t.source) {
+                let val = Sample::new(vr.vtype, vr.vals[map.current]).as_f32();
+                t.min = t.min.min(val);
+                t.max = t.max.max(val);
+                t.sum += val;
+                t.count += 1;
+            }
+        }
+        self.tick += 1;
+        if self.tick < self.window {
+            return;
+        }
+        self.tick = 0;
+        let current = self.current;
+        let previous = current.checked_sub(1).unwrap_or(P - 1);
+        for t in self.trackers.iter_mut() {
+            let stats = [t.min, t.max, t.sum / t.count as f32];
+            for (vals, s) in t.vals.iter_mut().zip(stats.iter()) {
+                // Absent signal holds previous statistics
+                vals[current] = if t.count == 0 {
+                    vals[previous]
+                } else {
+                    s.to_bits() as i32
+                };
+            }
+            t.min = f32::INFINITY;
+            t.max = f32::NEG_INFINITY;
+            t.sum = 0.;
+            t.count = 0;
+        }
+        self.current += 1;
+        if self.current == P {
+            self.current = 0;
+            f(self);
+        }
+    }
+}
+
+impl<Tx, const K: usize, const P: usize> SendPackage<Stats<K, P>> for Tx
+where
+    Tx: WriteIter,
+{
+    type Error = <Tx as WriteIter>::Error;
+    fn send_package(&mut self, module: &Name, values: &Stats<K, P>) -> Result<(), Self::Error> {
+        write_package(
+            self,
+            module,
+            P,
+            values.trackers.iter().flat_map(|t| {
+                t.names
+                    .iter()
+                    .zip(t.vals.iter())
+                    .map(|(name, vals)| (&**name, ValueType::Float, &vals[..]))
+            }),
+            &[],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::PackageView;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("stats");
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+    const C: SVName = SVName::new("c");
+
+    #[test]
+    fn windows() {
+        let mut map = SVMap::<2, 1>::new();
+        let mut stats = Stats::<2, 2>::new(2);
+        stats.track(&A).unwrap();
+        stats.track(&A).unwrap();
+        stats.track(&B).unwrap();
+        assert_eq!(stats.track(&C), Err(AddError::MapOverflow));
+        let mut tx = Mock::<2048>::new();
+        for &a in &[1, 3, 5, 4] {
+            map.set(&A, a).unwrap();
+            stats.next(&map, |s| tx.send_package(&MODULE, s).unwrap());
+            map.next(|_| {});
+        }
+        let (view, _) = PackageView::parse(tx.as_slice(), 2).unwrap();
+        let records: [(&str, [f32; 2]); 3] = [
+            ("a_min", [1., 4.]),
+            ("a_max", [3., 5.]),
+            ("a_mean", [2., 4.5]),
+        ];
+        assert_eq!(view.len(), 6);
+        for (record, (name, vals)) in view.records().zip(records.iter()) {
+            assert_eq!(record.name_str(), Some(*name));
+            assert_eq!(record.vtype(), Some(ValueType::Float));
+            assert!(record
+                .values()
+                .map(|v| f32::from_bits(v as u32))
+                .eq(vals.iter().cloned()));
+        }
+        // Statistics of absent signal hold initial zeros
+        assert!(view.records().skip(3).all(|r| r.values().all(|v| v == 0)));
+
+        assert!(stats.untrack(&B));
+        assert!(!stats.untrack(&B));
+        stats.track(&C).unwrap();
+    }
+}
@@ -0,0 +1,380 @@
+use core::convert::{Infallible, TryFrom};
+
+use crate::buffer::BufferOverflow;
+use crate::{Front, Name, SVMap, ValueRec, ValueType};
+
+/// Byte-addressable storage.
+///
+/// Mirrors `embedded_storage::Storage` and is implemented for all its drivers
+/// with `embedded-storage` feature
+pub trait Storage {
+    /// Error type
+    type Error;
+    /// Read `bytes.len()` bytes starting at `offset`
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error>;
+    /// Write `bytes` starting at `offset`
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+#[cfg(feature = "embedded-storage")]
+impl<T: embedded_storage::Storage> Storage for T {
+    type Error = T::Error;
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        embedded_storage::ReadStorage::read(self, offset, bytes)
+    }
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        embedded_storage::Storage::write(self, offset, bytes)
+    }
+}
+
+/// Errors of restoring signal set
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RestoreError<E> {
+    /// Storage error
+    Storage(E),
+    /// Storage does not contain valid signal set
+    Invalid,
+    /// Stored signal is absent in provided list of names
+    UnknownName,
+    /// Overflow of container
+    MapOverflow,
+}
+
+const MAGIC: [u8; 4] = *b"SVms";
+/// Stored signal: name, type, front
+const RECORD_SIZE: usize = Name::MAX_SIZE + 2;
+
+const SNAPSHOT_MAGIC: [u8; 4] = *b"SVsn";
+/// Snapshot header: magic, number of signals, package size, time position
+const SNAPSHOT_HEADER: usize = 4 + 2 + 2 + 2;
+
+/// Find stored zero-padded name in `names`
+fn find_name(stored: &[u8], names: &[&'static Name]) -> Option<&'static Name> {
+    let len = stored.iter().position(|&b| b == 0).unwrap_or(stored.len());
+    names
+        .iter()
+        .find(|n| n.as_bytes() == &stored[..len])
+        .cloned()
+}
+
+impl<const N: usize, const P: usize> SVMap<N, P> {
+    /// Compile-time check that stored counters fit in u16
+    const STORABLE: () = {
+        assert!(N <= u16::MAX as usize, "N does not fit in u16");
+        assert!(P <= u16::MAX as usize, "P does not fit in u16");
+    };
+
+    /// Save registered signals (names and types, not values) to `store`
+    pub fn persist<S: Storage>(&self, store: &mut S) -> Result<(), S::Error> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::STORABLE;
+        let mut header = [0; 6];
+        header[..4].copy_from_slice(&MAGIC);
+        header[4..].copy_from_slice(&(self.map.len() as u16).to_le_bytes());
+        store.write(0, &header)?;
+        let mut offset = header.len() as u32;
+        for (name, v) in self.map.iter() {
+            let mut record = [0; RECORD_SIZE];
+            record[..name.len()].copy_from_slice(name.as_bytes());
+            record[Name::MAX_SIZE] = v.vtype as u8;
+            record[Name::MAX_SIZE + 1] = v.front as u8;
+            store.write(offset, &record)?;
+            offset += RECORD_SIZE as u32;
+        }
+        Ok(())
+    }
+
+    /// Register signals saved by [`persist`](Self::persist) in the same order.
+    ///
+    /// Stored names are matched against `names`, as map keeps only `'static` names.
+    /// Returns number of restored signals
+    pub fn restore<S: Storage>(
+        &mut self,
+        store: &mut S,
+        names: &[&'static Name],
+    ) -> Result<usize, RestoreError<S::Error>> {
+        let mut header = [0; 6];
+        store.read(0, &mut header).map_err(RestoreError::Storage)?;
+        if header[..4] != MAGIC {
+            return Err(RestoreError::Invalid);
+        }
+        let count = u16::from_le_bytes([header[4], header[5]]) as usize;
+        let mut offset = header.len() as u32;
+        for _ in 0..count {
+            let mut record = [0; RECORD_SIZE];
+            store
+                .read(offset, &mut record)
+                .map_err(RestoreError::Storage)?;
+            offset += RECORD_SIZE as u32;
+
+            let name: &'static str =
+                find_name(&record[..Name::MAX_SIZE], names).ok_or(RestoreError::UnknownName)?;
+            let vtype =
+                ValueType::try_from(record[Name::MAX_SIZE]).map_err(|_| RestoreError::Invalid)?;
+            let front =
+                Front::try_from(record[Name::MAX_SIZE + 1]).map_err(|_| RestoreError::Invalid)?;
+            if !self.map.contains_key(&name) {
+                let mut vr = ValueRec::new(vtype);
+                vr.front = front;
+                self.map
+                    .insert(name, vr)
+                    .map_err(|_| RestoreError::MapOverflow)?;
+                self.layout_changed();
+            }
+        }
+        Ok(count)
+    }
+
+    /// Size of snapshot of current signals
+    pub fn snapshot_size(&self) -> usize {
+        SNAPSHOT_HEADER + self.map.len() * (RECORD_SIZE + 1 + 4 + 4 * P)
+    }
+
+    /// Save signals with their values and time position into `bytes`,
+    /// e.g. in retained RAM before deep sleep. Returns size of snapshot
+    pub fn snapshot(&self, bytes: &mut [u8]) -> Result<usize, BufferOverflow> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::STORABLE;
+        let size = self.snapshot_size();
+        let out = bytes.get_mut(..size).ok_or(BufferOverflow)?;
+        out[..4].copy_from_slice(&SNAPSHOT_MAGIC);
+        out[4..6].copy_from_slice(&(self.map.len() as u16).to_le_bytes());
+        out[6..8].copy_from_slice(&(P as u16).to_le_bytes());
+        out[8..10].copy_from_slice(&(self.current as u16).to_le_bytes());
+        let mut offset = SNAPSHOT_HEADER;
+        for (name, v) in self.map.iter() {
+            let record = &mut out[offset..offset + RECORD_SIZE + 5 + 4 * P];
+            record[..Name::MAX_SIZE].fill(0);
+            record[..name.len()].copy_from_slice(name.as_bytes());
+            record[Name::MAX_SIZE] = v.vtype as u8;
+            record[Name::MAX_SIZE + 1] = v.front as u8;
+            record[RECORD_SIZE] = v.level as u8;
+            record[RECORD_SIZE + 1..RECORD_SIZE + 5].copy_from_slice(&v.sent.to_le_bytes());
+            for (chunk, val) in record[RECORD_SIZE + 5..].chunks_mut(4).zip(v.vals.iter()) {
+                chunk.copy_from_slice(&val.to_le_bytes());
+            }
+            offset += record.len();
+        }
+        Ok(size)
+    }
+
+    /// Restore signals and values saved by [`snapshot`](Self::snapshot),
+    /// so recording continues from saved time position.
+    ///
+    /// Stored names are matched against `names`, as map keeps only `'static` names.
+    /// Whole snapshot is checked before map is changed, so on error map stays intact.
+    /// Returns number of restored signals
+    pub fn restore_snapshot(
+        &mut self,
+        bytes: &[u8],
+        names: &[&'static Name],
+    ) -> Result<usize, RestoreError<Infallible>> {
+        let rec_size = RECORD_SIZE + 5 + 4 * P;
+        let header = bytes.get(..SNAPSHOT_HEADER).ok_or(RestoreError::Invalid)?;
+        if header[..4] != SNAPSHOT_MAGIC || header[6..8] != (P as u16).to_le_bytes() {
+            return Err(RestoreError::Invalid);
+        }
+        let count = u16::from_le_bytes([header[4], header[5]]) as usize;
+        let current = u16::from_le_bytes([header[8], header[9]]) as usize;
+        let records = bytes
+            .get(SNAPSHOT_HEADER..SNAPSHOT_HEADER + count * rec_size)
+            .ok_or(RestoreError::Invalid)?;
+        if current >= P {
+            return Err(RestoreError::Invalid);
+        }
+        let parse = |record: &[u8]| -> Result<_, RestoreError<Infallible>> {
+            let name: &'static str =
+                find_name(&record[..Name::MAX_SIZE], names).ok_or(RestoreError::UnknownName)?;
+            let vtype =
+                ValueType::try_from(record[Name::MAX_SIZE]).map_err(|_| RestoreError::Invalid)?;
+            let front =
+                Front::try_from(record[Name::MAX_SIZE + 1]).map_err(|_| RestoreError::Invalid)?;
+            Ok((name, vtype, front))
+        };
+        let mut added = 0;
+        for (i, record) in records.chunks(rec_size).enumerate() {
+            let (name, _, _) = parse(record)?;
+            let repeated = records
+                .chunks(rec_size)
+                .take(i)
+                .any(|r| r[..Name::MAX_SIZE] == record[..Name::MAX_SIZE]);
+            if !repeated && !self.map.contains_key(&name) {
+                added += 1;
+            }
+        }
+        if self.map.len() + added > N {
+            return Err(RestoreError::MapOverflow);
+        }
+        for record in records.chunks(rec_size) {
+            let (name, vtype, front) = parse(record)?;
+            if !self.map.contains_key(&name) {
+                self.map
+                    .insert(name, ValueRec::new(vtype))
+                    .map_err(|_| RestoreError::MapOverflow)?;
+                self.layout_changed();
+            }
+            let vr = self.map.get_mut(name).unwrap();
+            vr.vtype = vtype;
+            vr.front = front;
+            vr.level = record[RECORD_SIZE] != 0;
+            let s = &record[RECORD_SIZE + 1..RECORD_SIZE + 5];
+            vr.sent = i32::from_le_bytes([s[0], s[1], s[2], s[3]]);
+            for (val, chunk) in vr.vals.iter_mut().zip(record[RECORD_SIZE + 5..].chunks(4)) {
+                *val = i32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            }
+        }
+        self.current = current;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+    use crate::OnlyFront;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+    const NAMES: [&Name; 2] = [&A, &B];
+
+    /// Storage in RAM
+    pub(crate) struct Ram<const S: usize>(pub [u8; S]);
+
+    impl<const S: usize> Storage for Ram<S> {
+        type Error = ();
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), ()> {
+            let offset = offset as usize;
+            let src = self.0.get(offset..offset + bytes.len()).ok_or(())?;
+            bytes.copy_from_slice(src);
+            Ok(())
+        }
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), ()> {
+            let offset = offset as usize;
+            let dst = self.0.get_mut(offset..offset + bytes.len()).ok_or(())?;
+            dst.copy_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    /// Storage without space
+    struct Small;
+
+    impl Storage for Small {
+        type Error = ();
+        fn read(&mut self, _: u32, _: &mut [u8]) -> Result<(), ()> {
+            Err(())
+        }
+        fn write(&mut self, _: u32, _: &[u8]) -> Result<(), ()> {
+            Err(())
+        }
+    }
+
+    #[test]
+    fn persist_restore() {
+        let mut map = SVMap::<4, 2>::new();
+        map.set(&A, 1).unwrap();
+        map.set(&B, OnlyFront(true)).unwrap();
+        let mut ram = Ram([0; 256]);
+        map.persist(&mut ram).unwrap();
+
+        let mut restored = SVMap::<4, 2>::new();
+        assert_eq!(restored.restore(&mut ram, &NAMES).unwrap(), 2);
+        let keys = |m: &SVMap<4, 2>| {
+            m.map
+                .iter()
+                .map(|(&k, v)| (k, v.vtype, v.front))
+                .collect::<heapless::Vec<_, 4>>()
+        };
+        assert_eq!(keys(&restored), keys(&map));
+        assert!(matches!(
+            restored.restore(&mut Small, &NAMES),
+            Err(RestoreError::Storage(()))
+        ));
+        assert!(matches!(
+            restored.restore(&mut ram, &[&A]),
+            Err(RestoreError::UnknownName)
+        ));
+        assert!(matches!(
+            restored.restore(&mut Ram([0; 256]), &NAMES),
+            Err(RestoreError::Invalid)
+        ));
+        assert!(matches!(map.persist(&mut Small), Err(())));
+    }
+
+    #[test]
+    fn snapshot_continues_recording() {
+        let mut map = SVMap::<4, 4>::new();
+        let mut bytes = [0; 256];
+        for i in 0..6 {
+            map.set(&A, i).unwrap();
+            map.set(&B, i as f32 / 2.).unwrap();
+            map.next(|_| {});
+        }
+        let size = map.snapshot(&mut bytes).unwrap();
+        assert_eq!(size, map.snapshot_size());
+        assert_eq!(map.snapshot(&mut [0; 8]), Err(BufferOverflow));
+
+        let mut restored = SVMap::<4, 4>::new();
+        assert_eq!(
+            restored.restore_snapshot(&bytes[..size], &NAMES).unwrap(),
+            2
+        );
+        let (mut a, mut b) = (Buffer::<2048>::new(), Buffer::<2048>::new());
+        for i in 6..8 {
+            map.set(&A, i).unwrap();
+            restored.set(&A, i).unwrap();
+            map.next(|m| a.send_package(&MODULE, m).unwrap());
+            restored.next(|m| b.send_package(&MODULE, m).unwrap());
+        }
+        assert!(!a.is_empty());
+        assert_eq!(&*a, &*b);
+
+        // Map stays intact on error
+        let mut other = SVMap::<1, 4>::new();
+        assert!(matches!(
+            other.restore_snapshot(&bytes[..size], &NAMES),
+            Err(RestoreError::MapOverflow)
+        ));
+        assert!(matches!(
+            other.restore_snapshot(&bytes[..size - 1], &NAMES),
+            Err(RestoreError::Invalid)
+        ));
+        assert!(other.map.is_empty());
+    }
+
+    #[cfg(feature = "embedded-storage")]
+    #[test]
+    fn embedded_storage_driver() {
+        struct Eeprom([u8; 256]);
+        impl embedded_storage::ReadStorage for Eeprom {
+            type Error = ();
+            fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), ()> {
+                let offset = offset as usize;
+                bytes.copy_from_slice(&self.0[offset..offset + bytes.len()]);
+                Ok(())
+            }
+            fn capacity(&self) -> usize {
+                self.0.len()
+            }
+        }
+        impl embedded_storage::Storage for Eeprom {
+            fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), ()> {
+                let offset = offset as usize;
+                self.0[offset..offset + bytes.len()].copy_from_slice(bytes);
+                Ok(())
+            }
+        }
+
+        let mut map = SVMap::<4, 2>::new();
+        map.set(&A, 1).unwrap();
+        let mut eeprom = Eeprom([0; 256]);
+        map.persist(&mut eeprom).unwrap();
+        let mut restored = SVMap::<4, 2>::new();
+        assert_eq!(restored.restore(&mut eeprom, &NAMES).unwrap(), 1);
+        assert_eq!(restored.map.keys().next(), Some(&"a"));
+    }
+}
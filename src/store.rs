@@ -0,0 +1,293 @@
+use heapless::{FnvIndexMap, LinearMap};
+
+use crate::{AddError, Name, SVStruct, ValueRec, ValueType};
+
+/// Storage of signal records, e.g. hash map for large signal sets.
+///
+/// All containers of the crate are [`SVStruct`] over some store,
+/// so profiles, alarms, computed signals and send modes work with any of them
+pub trait SignalStore {
+    /// Values of one signal in package, e.g. `[i32; P]`
+    type Values: AsRef<[i32]> + AsMut<[i32]>;
+    /// Number of values in package
+    fn package(&self) -> usize;
+    /// Number of signals
+    fn len(&self) -> usize;
+    /// Checks if there are no signals
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Record of signal
+    fn get(&self, name: &str) -> Option<&ValueRec<Self::Values>>;
+    /// Mutable record of signal
+    fn get_mut(&mut self, name: &str) -> Option<&mut ValueRec<Self::Values>>;
+    /// Position of signal in order of sending
+    fn position(&self, name: &str) -> Option<usize>;
+    /// Name and record of signal at position `idx` in order of sending
+    fn get_index_mut(&mut self, idx: usize) -> Option<(&str, &mut ValueRec<Self::Values>)>;
+    /// Add empty record of new signal
+    fn insert(&mut self, name: &'static str, vtype: ValueType) -> Result<(), AddError>;
+    /// Add empty record of new signal with name that is not `'static`.
+    ///
+    /// Stores keeping `&'static str` names return `NotRegistered`,
+    /// so such signals must be added before
+    fn insert_copied(&mut self, _name: &str, _vtype: ValueType) -> Result<(), AddError> {
+        Err(AddError::NotRegistered)
+    }
+    /// Remove record of signal. Returns `false` if signal is absent
+    fn remove(&mut self, name: &str) -> bool;
+    /// Remove all signals
+    fn clear(&mut self);
+    /// Call `f` on each signal in order of sending, stop on first error
+    fn try_for_each<E, F>(&self, f: F) -> Result<(), E>
+    where
+        F: FnMut(&str, &ValueRec<Self::Values>) -> Result<(), E>;
+    /// Call `f` on each record
+    fn for_each_mut<F: FnMut(&mut ValueRec<Self::Values>)>(&mut self, f: F);
+}
+
+/// Store that keeps `&'static` names, so signals are added by `&'static Name`
+pub trait StaticNames: SignalStore {}
+
+/// Identifier of signal in store `S`, e.g. `&Name`
+pub trait SignalKey<S: SignalStore + ?Sized> {
+    /// Signal name
+    fn name(&self) -> &Name;
+    /// Record of signal
+    fn get<'s>(&self, store: &'s S) -> Option<&'s ValueRec<S::Values>> {
+        store.get(self.name())
+    }
+    /// Mutable record of signal
+    fn get_mut<'s>(&self, store: &'s mut S) -> Option<&'s mut ValueRec<S::Values>> {
+        store.get_mut(self.name())
+    }
+}
+
+/// Identifier that adds new signal to store `S` on first update, e.g. `&'static Name`
+pub trait NewSignal<S: SignalStore + ?Sized>: SignalKey<S> {
+    /// Add empty record of signal
+    fn insert(&self, store: &mut S, vtype: ValueType) -> Result<(), AddError>;
+}
+
+impl<S: SignalStore + ?Sized> SignalKey<S> for &Name {
+    fn name(&self) -> &Name {
+        self
+    }
+}
+
+impl<S: StaticNames + ?Sized> NewSignal<S> for &'static Name {
+    fn insert(&self, store: &mut S, vtype: ValueType) -> Result<(), AddError> {
+        let name: &'static Name = self;
+        store.insert(name, vtype)
+    }
+}
+
+/// Signal with static name added by the crate itself, e.g. threshold flag
+pub(crate) struct Static(pub &'static Name);
+
+impl<S: SignalStore + ?Sized> SignalKey<S> for Static {
+    fn name(&self) -> &Name {
+        self.0
+    }
+}
+
+impl<S: SignalStore + ?Sized> NewSignal<S> for Static {
+    fn insert(&self, store: &mut S, vtype: ValueType) -> Result<(), AddError> {
+        store.insert(self.0, vtype)
+    }
+}
+
+/// Signal with runtime name, e.g. of group member
+pub(crate) struct Copied<'n>(pub &'n Name);
+
+impl<'n, S: SignalStore + ?Sized> SignalKey<S> for Copied<'n> {
+    fn name(&self) -> &Name {
+        self.0
+    }
+}
+
+impl<'n, S: SignalStore + ?Sized> NewSignal<S> for Copied<'n> {
+    fn insert(&self, store: &mut S, vtype: ValueType) -> Result<(), AddError> {
+        store.insert_copied(self.0, vtype)
+    }
+}
+
+/// Signal at known position in store, falling back to name lookup
+pub(crate) struct Indexed {
+    pub idx: usize,
+    pub name: &'static Name,
+}
+
+impl<S: SignalStore + ?Sized> SignalKey<S> for Indexed {
+    fn name(&self) -> &Name {
+        self.name
+    }
+    fn get_mut<'s>(&self, store: &'s mut S) -> Option<&'s mut ValueRec<S::Values>> {
+        let name: &str = self.name;
+        // Signal can be moved in store after removal of other signal
+        if matches!(store.get_index_mut(self.idx), Some((k, _)) if core::ptr::eq(k, name)) {
+            store.get_index_mut(self.idx).map(|(_, vr)| vr)
+        } else {
+            store.get_mut(name)
+        }
+    }
+}
+
+impl<S: SignalStore + ?Sized> NewSignal<S> for Indexed {
+    fn insert(&self, store: &mut S, vtype: ValueType) -> Result<(), AddError> {
+        store.insert(self.name, vtype)
+    }
+}
+
+macro_rules! impl_map_store {
+    ($map:ident) => {
+        impl<const N: usize, const P: usize> SignalStore
+            for $map<&'static str, ValueRec<[i32; P]>, N>
+        {
+            type Values = [i32; P];
+            fn package(&self) -> usize {
+                P
+            }
+            fn len(&self) -> usize {
+                $map::len(self)
+            }
+            fn get(&self, name: &str) -> Option<&ValueRec<[i32; P]>> {
+                $map::get(self, name)
+            }
+            fn get_mut(&mut self, name: &str) -> Option<&mut ValueRec<[i32; P]>> {
+                $map::get_mut(self, name)
+            }
+            fn position(&self, name: &str) -> Option<usize> {
+                self.keys().position(|&k| k == name)
+            }
+            fn get_index_mut(&mut self, idx: usize) -> Option<(&str, &mut ValueRec<[i32; P]>)> {
+                self.iter_mut().nth(idx).map(|(&k, vr)| (k, vr))
+            }
+            fn insert(&mut self, name: &'static str, vtype: ValueType) -> Result<(), AddError> {
+                $map::insert(self, name, ValueRec::new(vtype))
+                    .map(|_| ())
+                    .map_err(|_| AddError::MapOverflow)
+            }
+            fn remove(&mut self, name: &str) -> bool {
+                $map::remove(self, name).is_some()
+            }
+            fn clear(&mut self) {
+                $map::clear(self)
+            }
+            fn try_for_each<E, F>(&self, mut f: F) -> Result<(), E>
+            where
+                F: FnMut(&str, &ValueRec<[i32; P]>) -> Result<(), E>,
+            {
+                self.iter().try_for_each(|(&k, vr)| f(k, vr))
+            }
+            fn for_each_mut<F: FnMut(&mut ValueRec<[i32; P]>)>(&mut self, f: F) {
+                self.values_mut().for_each(f)
+            }
+        }
+
+        impl<const N: usize, const P: usize> StaticNames
+            for $map<&'static str, ValueRec<[i32; P]>, N>
+        {
+        }
+    };
+}
+
+impl_map_store!(LinearMap);
+impl_map_store!(FnvIndexMap);
+
+/// Map of signals with pluggable storage
+pub type StoreMap<S> = SVStruct<S>;
+
+/// Map of signals with O(1) lookup, `N` must be power of 2
+pub type FnvMap<const N: usize, const P: usize> =
+    StoreMap<FnvIndexMap<&'static str, ValueRec<[i32; P]>, N>>;
+
+impl<const N: usize, const P: usize> FnvMap<N, P> {
+    /// Create new instance
+    pub fn new() -> Self {
+        Self::with_store(FnvIndexMap::new())
+    }
+}
+
+impl<S: SignalStore> StoreMap<S> {
+    /// Create new instance with `store`
+    pub fn with_store(store: S) -> Self {
+        Self::with_map(store)
+    }
+}
+
+impl<S: SignalStore + Default> Default for StoreMap<S> {
+    fn default() -> Self {
+        Self::with_store(S::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Buffer;
+    use crate::prelude::*;
+    use crate::profile::{ProfileRule, Transform};
+    use crate::SendMode;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+    const B: SVName = SVName::new("b");
+    const HIDDEN: SVName = SVName::new("hidden");
+    const RULES: [ProfileRule; 2] = [
+        ProfileRule {
+            name: HIDDEN,
+            transform: Transform::Exclude,
+        },
+        ProfileRule {
+            name: B,
+            transform: Transform::Linear {
+                scale: 2.,
+                offset: 1.,
+            },
+        },
+    ];
+
+    /// Package of the same updates recorded by map `M`
+    fn record<S: StaticNames>(mut map: SVStruct<S>) -> Buffer<512> {
+        let mut buf = Buffer::new();
+        map.set_profile(&RULES);
+        map.set_send_mode(SendMode::OnChange { max_silence: 0 });
+        for i in 0..8 {
+            map.set(&A, i / 4).unwrap();
+            map.set(&B, 0.5).unwrap();
+            map.set(&HIDDEN, true).unwrap();
+            map.next(|m| buf.send_package(&MODULE, m).unwrap());
+        }
+        buf
+    }
+
+    #[test]
+    fn same_as_linear_map() {
+        let fnv = record(FnvMap::<4, 2>::new());
+        assert!(!fnv.is_empty());
+        assert_eq!(&*fnv, &*record(SVMap::<4, 2>::new()));
+
+        let mut map = FnvMap::<4, 2>::new();
+        map.set_profile(&RULES);
+        map.set(&B, 0.5).unwrap();
+        map.set(&HIDDEN, true).unwrap();
+        assert_eq!(map.get::<f32, _>(&B), Some(2.));
+        assert_eq!(map.get::<bool, _>(&HIDDEN), None);
+    }
+
+    #[test]
+    fn remove_changes_layout() {
+        let mut map = FnvMap::<4, 2>::new();
+        map.set(&A, 1).unwrap();
+        let layout = map.layout;
+        assert!(!map.remove(&B));
+        assert_eq!(map.layout, layout);
+        assert!(map.remove(&A));
+        assert_ne!(map.layout, layout);
+        assert_eq!(map.get::<i32, _>(&A), None);
+
+        map.seal();
+        assert_eq!(map.set(&A, 1), Err(AddError::NotRegistered));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::alarm::{entries, Active};
+use crate::store::SignalStore;
+use crate::{Front, Name, SVStruct, Sample, ValueType};
+
+/// High and low thresholds of signal with automatic flag signal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Threshold {
+    /// Signal name
+    pub name: Name,
+    /// Name of `Bool` signal that is high while threshold is crossed
+    pub flag: Name,
+    /// Flag is raised below this value, `f32::NEG_INFINITY` disables it
+    pub low: f32,
+    /// Flag is raised above this value, `f32::INFINITY` disables it
+    pub high: f32,
+    /// Flag is cleared after value returns inside the range by this margin
+    pub hysteresis: f32,
+}
+
+impl Threshold {
+    /// New flag state from previous `active` state and `value`
+    pub fn active(&self, active: bool, value: f32) -> bool {
+        if active {
+            !(self.low + self.hysteresis <= value && value <= self.high - self.hysteresis)
+        } else {
+            value < self.low || value > self.high
+        }
+    }
+}
+
+impl<S: SignalStore> SVStruct<S> {
+    /// Set thresholds of signals. First 32 thresholds are checked.
+    ///
+    /// Flag signals are updated with their sources, so they are plotted with data
+    /// and can start capture with [`watch!`](crate::watch!) condition like `flag == 1`.
+    ///
+    /// Thresholds are compared with raw value after [`NonFinite`](crate::NonFinite) policy
+    /// and before profile, so they are set in units of the source
+    pub fn set_thresholds(&mut self, thresholds: &'static [Threshold]) {
+        self.thresholds = thresholds;
+        self.threshold_active = Active::NONE;
+    }
+
+    /// Checks if any threshold is crossed
+    pub fn threshold_crossed(&self) -> bool {
+        self.threshold_active.any()
+    }
+
+    /// Update flags of thresholds of signal
+    pub(crate) fn check_thresholds(&mut self, name: &Name, vtype: ValueType, val: i32) {
+        let value = Sample::new(vtype, val).as_f32();
+        for (i, t) in entries(self.thresholds, name, |t| &t.name) {
+            let active = t.active(self.threshold_active.get(i), value);
+            self.threshold_active.set(i, active);
+            // Flag of sealed map must be registered, otherwise it is not recorded
+            self.set_value(&t.flag, ValueType::Bool, active as i32, Front::Hold)
+                .ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::profile::{ProfileRule, Transform};
+    use crate::NonFinite;
+
+    const LEVEL: Name = Name::new("level");
+    const HIGH: Name = Name::new("level_high");
+    const THRESHOLDS: [Threshold; 1] = [Threshold {
+        name: LEVEL,
+        flag: HIGH,
+        low: f32::NEG_INFINITY,
+        high: 10.,
+        hysteresis: 2.,
+    }];
+
+    #[test]
+    fn hysteresis() {
+        let mut map = SVMap::<2, 4>::new();
+        map.set_thresholds(&THRESHOLDS);
+        // Raw value is compared, profile only changes recorded value
+        map.set_profile(&[ProfileRule {
+            name: LEVEL,
+            transform: Transform::Linear {
+                scale: 0.1,
+                offset: 0.,
+            },
+        }]);
+        let mut flags = [false; 5];
+        for (flag, &v) in flags.iter_mut().zip(&[5., 11., 9., 7., 11.]) {
+            map.set(&LEVEL, v).unwrap();
+            *flag = map.get(&HIGH).unwrap();
+        }
+        assert_eq!(flags, [false, true, true, false, true]);
+        assert!(map.threshold_crossed());
+        assert_eq!(map.get(&LEVEL), Some(1.1));
+    }
+
+    #[test]
+    fn after_non_finite_policy() {
+        let mut map = SVMap::<2, 4>::new();
+        map.set_thresholds(&THRESHOLDS);
+        map.set(&LEVEL, 5.).unwrap();
+        map.set_non_finite_policy(NonFinite::Hold);
+        map.set(&LEVEL, f32::INFINITY).unwrap();
+        assert!(!map.threshold_crossed());
+        map.set_non_finite_policy(NonFinite::Clamp);
+        map.set(&LEVEL, f32::INFINITY).unwrap();
+        assert_eq!(map.get(&HIGH), Some(true));
+    }
+}
@@ -0,0 +1,93 @@
+use embedded_hal::timer::{CountDown, Periodic};
+
+use crate::NextValue;
+
+/// Fixed time base of map driven by periodic timer,
+/// so package positions do not jitter with application loop
+pub struct Sampler<Tim> {
+    timer: Tim,
+}
+
+impl<Tim: CountDown + Periodic> Sampler<Tim> {
+    /// Start `timer` with sampling `period`
+    pub fn new<T: Into<Tim::Time>>(mut timer: Tim, period: T) -> Self {
+        timer.start(period);
+        Self { timer }
+    }
+
+    /// Release timer
+    pub fn free(self) -> Tim {
+        self.timer
+    }
+
+    /// Go to next time position of `map` if period elapsed.
+    /// `F` is send package function. Returns `true` if map advanced
+    pub fn poll<M, F>(&mut self, map: &mut M, f: F) -> bool
+    where
+        M: NextValue,
+        F: FnOnce(&M),
+    {
+        if self.timer.wait().is_ok() {
+            map.next(f);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Wait for end of period and go to next time position of `map`
+    pub fn wait<M, F>(&mut self, map: &mut M, f: F)
+    where
+        M: NextValue,
+        F: FnOnce(&M),
+    {
+        nb::block!(self.timer.wait()).ok();
+        map.next(f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const A: SVName = SVName::new("a");
+
+    /// Timer elapsing on every `period` wait
+    struct Timer {
+        period: u32,
+        left: u32,
+    }
+
+    impl CountDown for Timer {
+        type Time = u32;
+        fn start<T: Into<u32>>(&mut self, count: T) {
+            self.period = count.into();
+            self.left = self.period;
+        }
+        fn wait(&mut self) -> nb::Result<(), void::Void> {
+            self.left -= 1;
+            if self.left > 0 {
+                return Err(nb::Error::WouldBlock);
+            }
+            self.left = self.period;
+            Ok(())
+        }
+    }
+
+    impl Periodic for Timer {}
+
+    #[test]
+    fn advance_on_period() {
+        let mut sampler = Sampler::new(Timer { period: 0, left: 0 }, 3u32);
+        let mut map = SVMap::<1, 2>::new();
+        let mut sent = 0;
+        map.set(&A, 1).unwrap();
+        let advanced: [bool; 6] = [(); 6].map(|_| sampler.poll(&mut map, |_| sent += 1));
+        assert_eq!(advanced, [false, false, true, false, false, true]);
+        assert_eq!(sent, 1);
+        sampler.wait(&mut map, |_| sent += 1);
+        assert_eq!(sampler.free().left, 3);
+        assert_eq!(sent, 1);
+    }
+}
@@ -0,0 +1,411 @@
+use core::marker::PhantomData;
+
+use crate::checksum::{Checked, Checksum, Crc16, Crc32, Fletcher16};
+use crate::chunk::Chunked;
+use crate::cobs::Cobs;
+#[cfg(feature = "crypto")]
+use crate::crypto::{Aead, Encrypted};
+use crate::escape::Escaped;
+use crate::gorilla::Compressed;
+use crate::handshake::Hello;
+#[cfg(feature = "lz")]
+use crate::lz::Lz;
+#[cfg(feature = "crypto")]
+use crate::mac::Authenticated;
+use crate::postcard::Postcard;
+use crate::scrambler::Scrambled;
+use crate::{Name, SendPackage, WriteIter};
+
+/// Package level layer placed on top of byte level layers
+pub trait Layer<Tx> {
+    /// Resulting transport
+    type Output;
+    /// Wrap byte level transport
+    fn wrap(self, tx: Tx) -> Self::Output;
+}
+
+/// No package level layer
+pub struct Plain;
+
+impl<Tx> Layer<Tx> for Plain {
+    type Output = Tx;
+    fn wrap(self, tx: Tx) -> Tx {
+        tx
+    }
+}
+
+/// Compression of float signals
+pub struct WithCompression;
+
+impl<Tx> Layer<Tx> for WithCompression {
+    type Output = Compressed<Tx>;
+    fn wrap(self, tx: Tx) -> Self::Output {
+        Compressed::new(tx)
+    }
+}
+
+/// Postcard encoding of packages
+pub struct WithPostcard;
+
+impl<Tx: WriteIter> Layer<Tx> for WithPostcard {
+    type Output = Postcard<Tx>;
+    fn wrap(self, tx: Tx) -> Self::Output {
+        Postcard::new(tx)
+    }
+}
+
+/// LZ compression of whole packages using buffer of size `B`
+#[cfg(feature = "lz")]
+pub struct WithLz<const B: usize>;
+
+#[cfg(feature = "lz")]
+impl<Tx, const B: usize> Layer<Tx> for WithLz<B> {
+    type Output = Lz<Tx, B>;
+    fn wrap(self, tx: Tx) -> Self::Output {
+        Lz::new(tx)
+    }
+}
+
+/// Encryption with cipher `C` and package buffer of size `B`
+#[cfg(feature = "crypto")]
+pub struct WithEncryption<C, const B: usize> {
+    cipher: C,
+    nonce_prefix: [u8; 4],
+}
+
+#[cfg(feature = "crypto")]
+impl<Tx, C: Aead, const B: usize> Layer<Tx> for WithEncryption<C, B> {
+    type Output = Encrypted<Tx, C, B>;
+    fn wrap(self, tx: Tx) -> Self::Output {
+        Encrypted::new(tx, self.cipher, self.nonce_prefix)
+    }
+}
+
+/// Stages of [`TransportBuilder`], byte level layers are added from the wire up
+pub mod stage {
+    /// No layers yet
+    pub struct Wire;
+    /// Frames are split into chunks
+    pub struct Chunks;
+    /// Frames are delimited with COBS or escaping
+    pub struct Framed;
+    /// Bytes are whitened
+    pub struct Whitened;
+    /// Frames are followed by checksum or tag trailers
+    pub struct Trailers;
+
+    /// Stage where framing can be added
+    pub trait BeforeFraming {}
+    impl BeforeFraming for Wire {}
+    impl BeforeFraming for Chunks {}
+
+    /// Stage where scrambling can be added
+    pub trait BeforeScrambling {}
+    impl BeforeScrambling for Wire {}
+    impl BeforeScrambling for Chunks {}
+    impl BeforeScrambling for Framed {}
+}
+
+/// Builder of transport stack, e.g.
+/// `TransportBuilder::new(uart).chunked(64).cobs().scrambled(PRBS7).crc16().build()`.
+///
+/// Byte level layers are added from the wire up and their order is checked at compile time:
+/// chunking, then COBS or escape framing, then scrambling, then checksum and tag trailers.
+/// So receiver finds frame delimiters before descrambling and trailers cover original bytes.
+/// Trailers can be stacked in any order, e.g. CRC under SipHash tag.
+///
+/// Package level layer (compression, LZ, encryption or postcard encoding) encodes whole package,
+/// so it is only one and is always placed on top, regardless of call order
+///
+/// ```compile_fail
+/// use svisual::{mock::Mock, transport::TransportBuilder};
+///
+/// // Chunking must be nearer to the wire than COBS
+/// let tx = TransportBuilder::new(Mock::<64>::new()).cobs().chunked(16);
+/// ```
+pub struct TransportBuilder<Tx, L = Plain, St = stage::Wire> {
+    tx: Tx,
+    layer: L,
+    _stage: PhantomData<St>,
+}
+
+impl<Tx> TransportBuilder<Tx> {
+    /// Start stack from raw transport
+    pub fn new(tx: Tx) -> Self {
+        Self {
+            tx,
+            layer: Plain,
+            _stage: PhantomData,
+        }
+    }
+}
+
+impl<Tx, St> TransportBuilder<Tx, Plain, St> {
+    /// Compress float signals
+    pub fn compressed(self) -> TransportBuilder<Tx, WithCompression, St> {
+        self.layer(WithCompression)
+    }
+
+    /// Encode packages in postcard format instead of SVisual one
+    pub fn postcard(self) -> TransportBuilder<Tx, WithPostcard, St> {
+        self.layer(WithPostcard)
+    }
+
+    /// Compress whole packages with LZ using buffer of `B` bytes
+    #[cfg(feature = "lz")]
+    pub fn lz<const B: usize>(self) -> TransportBuilder<Tx, WithLz<B>, St> {
+        self.layer(WithLz)
+    }
+
+    /// Encrypt packages using buffer of `B` bytes
+    #[cfg(feature = "crypto")]
+    pub fn encrypted<C: Aead, const B: usize>(
+        self,
+        cipher: C,
+        nonce_prefix: [u8; 4],
+    ) -> TransportBuilder<Tx, WithEncryption<C, B>, St> {
+        self.layer(WithEncryption {
+            cipher,
+            nonce_prefix,
+        })
+    }
+
+    fn layer<L>(self, layer: L) -> TransportBuilder<Tx, L, St> {
+        TransportBuilder {
+            tx: self.tx,
+            layer,
+            _stage: PhantomData,
+        }
+    }
+}
+
+impl<Tx, L> TransportBuilder<Tx, L> {
+    /// Split frames into chunks of at most `size` bytes
+    pub fn chunked(self, size: usize) -> TransportBuilder<Chunked<Tx>, L, stage::Chunks>
+    where
+        Tx: WriteIter,
+    {
+        self.wrap(|tx| Chunked::new(tx, size))
+    }
+}
+
+impl<Tx, L, St: stage::BeforeFraming> TransportBuilder<Tx, L, St> {
+    /// Frame packages with COBS
+    pub fn cobs(self) -> TransportBuilder<Cobs<Tx>, L, stage::Framed>
+    where
+        Tx: WriteIter,
+    {
+        self.wrap(Cobs::new)
+    }
+
+    /// Escape `=` bytes, so payload never aliases package markers
+    pub fn escaped(self) -> TransportBuilder<Escaped<Tx>, L, stage::Framed>
+    where
+        Tx: WriteIter,
+    {
+        self.wrap(Escaped::new)
+    }
+}
+
+impl<Tx, L, St: stage::BeforeScrambling> TransportBuilder<Tx, L, St> {
+    /// Whiten bytes with scrambler using feedback `taps`
+    pub fn scrambled(self, taps: u32) -> TransportBuilder<Scrambled<Tx>, L, stage::Whitened> {
+        self.wrap(|tx| Scrambled::new(tx, taps))
+    }
+}
+
+impl<Tx, L, St> TransportBuilder<Tx, L, St> {
+    /// Append checksum trailer of algorithm `C`
+    pub fn checksum<C: Checksum>(self) -> TransportBuilder<Checked<Tx, C>, L, stage::Trailers> {
+        self.wrap(Checked::new)
+    }
+
+    /// Append CRC-16 trailer
+    pub fn crc16(self) -> TransportBuilder<Checked<Tx, Crc16>, L, stage::Trailers> {
+        self.checksum()
+    }
+
+    /// Append CRC-32 trailer
+    pub fn crc32(self) -> TransportBuilder<Checked<Tx, Crc32>, L, stage::Trailers> {
+        self.checksum()
+    }
+
+    /// Append Fletcher-16 trailer
+    pub fn fletcher16(self) -> TransportBuilder<Checked<Tx, Fletcher16>, L, stage::Trailers> {
+        self.checksum()
+    }
+
+    /// Append SipHash tag with `key`
+    #[cfg(feature = "crypto")]
+    pub fn authenticated(
+        self,
+        key: [u8; 16],
+    ) -> TransportBuilder<Authenticated<Tx>, L, stage::Trailers> {
+        self.wrap(|tx| Authenticated::new(tx, key))
+    }
+
+    /// Assemble transport stack
+    pub fn build(self) -> L::Output
+    where
+        L: Layer<Tx>,
+    {
+        self.layer.wrap(self.tx)
+    }
+
+    fn wrap<T, S>(self, f: impl FnOnce(Tx) -> T) -> TransportBuilder<T, L, S> {
+        TransportBuilder {
+            tx: f(self.tx),
+            layer: self.layer,
+            _stage: PhantomData,
+        }
+    }
+}
+
+/// Transport that may be not available yet, e.g. USB is not enumerated or TCP is not connected.
+///
+/// Packages sent without transport are counted and dropped without error.
+/// Hello frame is sent each time transport is attached, so receiver learns extensions
+/// of client regardless of startup order
+pub struct MaybeTransport<Tx> {
+    tx: Option<Tx>,
+    hello: Option<(Hello, Name)>,
+    dropped: u32,
+}
+
+impl<Tx> MaybeTransport<Tx> {
+    /// Create without transport and hello frame
+    pub const fn new() -> Self {
+        Self {
+            tx: None,
+            hello: None,
+            dropped: 0,
+        }
+    }
+
+    /// Create without transport, `hello` of `module` is sent on attaching
+    pub const fn with_hello(hello: Hello, module: Name) -> Self {
+        Self {
+            tx: None,
+            hello: Some((hello, module)),
+            dropped: 0,
+        }
+    }
+
+    /// Detach transport, e.g. on disconnection
+    pub fn detach(&mut self) -> Option<Tx> {
+        self.tx.take()
+    }
+
+    /// Checks if transport is attached
+    pub fn is_ready(&self) -> bool {
+        self.tx.is_some()
+    }
+
+    /// Attached transport
+    pub fn transport(&mut self) -> Option<&mut Tx> {
+        self.tx.as_mut()
+    }
+
+    /// Number of packages dropped without transport
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+impl<Tx: WriteIter> MaybeTransport<Tx> {
+    /// Attach available transport and send hello frame.
+    ///
+    /// Returns previous transport. Transport stays attached if hello frame is not sent
+    pub fn attach(&mut self, tx: Tx) -> Result<Option<Tx>, (Option<Tx>, Tx::Error)> {
+        let previous = self.tx.replace(tx);
+        match (&mut self.tx, &self.hello) {
+            (Some(tx), Some((hello, module))) => match hello.send(tx, module) {
+                Ok(()) => Ok(previous),
+                Err(e) => Err((previous, e)),
+            },
+            _ => Ok(previous),
+        }
+    }
+}
+
+impl<Tx> Default for MaybeTransport<Tx> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Tx, V> SendPackage<V> for MaybeTransport<Tx>
+where
+    Tx: SendPackage<V>,
+{
+    type Error = Tx::Error;
+
+    fn send_package(&mut self, module: &Name, values: &V) -> Result<(), Self::Error> {
+        match &mut self.tx {
+            Some(tx) => tx.send_package(module, values),
+            None => {
+                self.dropped = self.dropped.wrapping_add(1);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+    use crate::scrambler::PRBS7;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    type Stack = Checked<Checked<Scrambled<Cobs<Chunked<Mock<2048>>>>, Crc16>, Fletcher16>;
+
+    #[test]
+    fn layers_from_wire_up() {
+        let mut map = SVMap::<1, 2>::new();
+        map.set(&A, 7).unwrap();
+
+        let mut built = TransportBuilder::new(Mock::<2048>::new())
+            .chunked(16)
+            .cobs()
+            .scrambled(PRBS7)
+            .crc16()
+            .fletcher16()
+            .build();
+        let mut manual = Checked::<_, Fletcher16>::new(Checked::<_, Crc16>::new(Scrambled::new(
+            Cobs::new(Chunked::new(Mock::<2048>::new(), 16)),
+            PRBS7,
+        )));
+        built.send_package(&MODULE, &map).unwrap();
+        manual.send_package(&MODULE, &map).unwrap();
+        let wire = |tx: Stack| tx.free().free().free().free().free();
+        let (built, manual) = (wire(built), wire(manual));
+        assert!(!built.as_slice().is_empty());
+        assert_eq!(built.as_slice(), manual.as_slice());
+        // COBS is under scrambler, so frame delimiter stays on the wire
+        assert_eq!(built.as_slice().iter().filter(|&&b| b == 0).count(), 1);
+        assert_eq!(built.as_slice().last(), Some(&0));
+    }
+
+    #[test]
+    fn package_layer_on_top() {
+        let mut map = SVMap::<1, 2>::new();
+        map.set(&A, 7.5).unwrap();
+        let mut tx = TransportBuilder::new(Mock::<2048>::new())
+            .compressed()
+            .escaped()
+            .crc32()
+            .build();
+        tx.send_package(&MODULE, &map).unwrap();
+        let mut manual =
+            Compressed::new(Checked::<_, Crc32>::new(Escaped::new(Mock::<2048>::new())));
+        manual.send_package(&MODULE, &map).unwrap();
+        assert_eq!(
+            tx.free().free().free().as_slice(),
+            manual.free().free().free().as_slice()
+        );
+    }
+}
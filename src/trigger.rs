@@ -0,0 +1,141 @@
+use heapless::Deque;
+
+use crate::buffer::{Buffer, BufferOverflow};
+use crate::{Name, SendPackage, WriteIter};
+
+/// Errors of pre-trigger capture
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CaptureError<E> {
+    /// Package does not fit in buffer
+    Overflow,
+    /// Transport error
+    Transport(E),
+}
+
+/// Transport keeping last `K` packages (up to `B` bytes each) in ring buffer
+/// instead of sending them.
+///
+/// On [`trigger`](Self::trigger) history is flushed and following packages are sent,
+/// so rare faults are captured with their prehistory without continuous streaming.
+/// Trigger condition can be checked with [`Watch`](crate::watch::Watch)
+pub struct PreTrigger<Tx, const K: usize, const B: usize> {
+    tx: Tx,
+    history: Deque<Buffer<B>, K>,
+    post: u32,
+    remaining: u32,
+}
+
+impl<Tx: WriteIter, const K: usize, const B: usize> PreTrigger<Tx, K, B> {
+    /// Wrap transport. `post` packages are sent after trigger, then capture restarts
+    pub fn new(tx: Tx, post: u32) -> Self {
+        Self {
+            tx,
+            history: Deque::new(),
+            post,
+            remaining: 0,
+        }
+    }
+
+    /// Release transport
+    pub fn free(self) -> Tx {
+        self.tx
+    }
+
+    /// Checks if packages are sent after trigger
+    pub fn is_triggered(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Number of packages in history
+    pub fn history(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Send history and start sending of following packages.
+    /// Repeated trigger prolongs sending
+    pub fn trigger(&mut self) -> Result<(), Tx::Error> {
+        while let Some(package) = self.history.pop_front() {
+            self.tx.bwrite_iter(package.iter().cloned())?;
+            self.tx.bflush()?;
+            self.tx.bend_frame()?;
+        }
+        self.remaining = self.post;
+        Ok(())
+    }
+}
+
+impl<Tx, V, const K: usize, const B: usize> SendPackage<V> for PreTrigger<Tx, K, B>
+where
+    Tx: WriteIter,
+    Buffer<B>: SendPackage<V, Error = BufferOverflow>,
+{
+    type Error = CaptureError<Tx::Error>;
+
+    fn send_package(&mut self, module: &Name, values: &V) -> Result<(), Self::Error> {
+        let mut package = Buffer::new();
+        package
+            .send_package(module, values)
+            .map_err(|_| CaptureError::Overflow)?;
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            self.tx
+                .bwrite_iter(package.iter().cloned())
+                .map_err(CaptureError::Transport)?;
+            self.tx.bflush().map_err(CaptureError::Transport)?;
+            return self.tx.bend_frame().map_err(CaptureError::Transport);
+        }
+        if self.history.is_full() {
+            self.history.pop_front();
+        }
+        self.history.push_back(package).ok();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+    use crate::prelude::*;
+
+    const MODULE: SVName = SVName::new("module");
+    const A: SVName = SVName::new("a");
+
+    #[test]
+    fn history_and_post() {
+        let mut map = SVMap::<1, 1>::new();
+        let mut tx = PreTrigger::<_, 2, 512>::new(Mock::<2048>::new(), 1);
+        let mut expected = Buffer::<2048>::new();
+        for i in 0..6 {
+            if i == 3 {
+                assert_eq!(tx.history(), 2);
+                tx.trigger().unwrap();
+                assert!(tx.is_triggered());
+                assert_eq!(tx.history(), 0);
+            }
+            map.set(&A, i).unwrap();
+            map.next(|m| {
+                // History of 2 packages before trigger and 1 package after it
+                if (1..4).contains(&i) {
+                    expected.send_package(&MODULE, m).unwrap();
+                }
+                tx.send_package(&MODULE, m).unwrap();
+            });
+        }
+        assert!(!tx.is_triggered());
+        assert_eq!(tx.history(), 2);
+        assert_eq!(tx.free().as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn overflow() {
+        let mut map = SVMap::<1, 1>::new();
+        let mut tx = PreTrigger::<_, 2, 8>::new(Mock::<64>::new(), 0);
+        map.set(&A, 1).unwrap();
+        map.next(|m| {
+            assert_eq!(tx.send_package(&MODULE, m), Err(CaptureError::Overflow));
+        });
+        assert_eq!(tx.history(), 0);
+    }
+}
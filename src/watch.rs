@@ -0,0 +1,152 @@
+use crate::{Name, SVMap, Sample};
+
+/// Comparison operator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cmp {
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+    /// `==`
+    Eq,
+    /// `!=`
+    Ne,
+}
+
+/// Static evaluation tree of condition over signals, usually built with [`watch!`](crate::watch!)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Watch {
+    /// Compare signal value with constant. Absent signal never matches
+    Cmp(Name, Cmp, f32),
+    /// Negation
+    Not(&'static Watch),
+    /// All conditions are true
+    All(&'static [Watch]),
+    /// Any condition is true
+    Any(&'static [Watch]),
+}
+
+impl Watch {
+    /// Evaluate condition on values at current time position
+    pub fn eval<const N: usize, const P: usize>(&self, map: &SVMap<N, P>) -> bool {
+        match self {
+            Self::Cmp(name, cmp, rhs) => match map.map.get(&**name) {
+                Some(vr) => {
+                    let lhs = Sample::new(vr.vtype, vr.vals[map.current]).as_f32();
+                    match cmp {
+                        Cmp::Lt => lhs < *rhs,
+                        Cmp::Le => lhs <= *rhs,
+                        Cmp::Gt => lhs > *rhs,
+                        Cmp::Ge => lhs >= *rhs,
+                        Cmp::Eq => lhs == *rhs,
+                        Cmp::Ne => lhs != *rhs,
+                    }
+                }
+                None => false,
+            },
+            Self::Not(w) => !w.eval(map),
+            Self::All(ws) => ws.iter().all(|w| w.eval(map)),
+            Self::Any(ws) => ws.iter().any(|w| w.eval(map)),
+        }
+    }
+}
+
+/// Condition over signals compiled into static [`Watch`] tree,
+/// e.g. `watch!(current > 10 && duty > 0.9 || !(fault == 0))`.
+///
+/// Signals are identifiers or string literals compared with numbers.
+/// `&&` binds tighter than `||`, parentheses group conditions
+#[macro_export]
+macro_rules! watch {
+    (@or [$($cur:tt)+] [$($acc:expr),*] || $($rest:tt)+) => {
+        $crate::watch!(@or [] [$($acc,)* $crate::watch!(@and [] [] $($cur)+)] $($rest)+)
+    };
+    (@or [$($cur:tt)*] [$($acc:expr),*] $t:tt $($rest:tt)*) => {
+        $crate::watch!(@or [$($cur)* $t] [$($acc),*] $($rest)*)
+    };
+    (@or [$($cur:tt)+] [$($acc:expr),*]) => {
+        $crate::watch::Watch::Any(&[$($acc,)* $crate::watch!(@and [] [] $($cur)+)])
+    };
+    (@and [$($cur:tt)+] [$($acc:expr),*] && $($rest:tt)+) => {
+        $crate::watch!(@and [] [$($acc,)* $crate::watch!(@atom $($cur)+)] $($rest)+)
+    };
+    (@and [$($cur:tt)*] [$($acc:expr),*] $t:tt $($rest:tt)*) => {
+        $crate::watch!(@and [$($cur)* $t] [$($acc),*] $($rest)*)
+    };
+    (@and [$($cur:tt)+] [$($acc:expr),*]) => {
+        $crate::watch::Watch::All(&[$($acc,)* $crate::watch!(@atom $($cur)+)])
+    };
+    (@atom ! $($t:tt)+) => {
+        $crate::watch::Watch::Not(&$crate::watch!(@atom $($t)+))
+    };
+    (@atom ($($t:tt)+)) => {
+        $crate::watch!(@or [] [] $($t)+)
+    };
+    (@atom $name:ident $($t:tt)+) => {
+        $crate::watch!(@cmp (stringify!($name)) $($t)+)
+    };
+    (@atom $name:literal $($t:tt)+) => {
+        $crate::watch!(@cmp ($name) $($t)+)
+    };
+    (@cmp $name:tt < $v:expr) => { $crate::watch!(@new $name Lt $v) };
+    (@cmp $name:tt <= $v:expr) => { $crate::watch!(@new $name Le $v) };
+    (@cmp $name:tt > $v:expr) => { $crate::watch!(@new $name Gt $v) };
+    (@cmp $name:tt >= $v:expr) => { $crate::watch!(@new $name Ge $v) };
+    (@cmp $name:tt == $v:expr) => { $crate::watch!(@new $name Eq $v) };
+    (@cmp $name:tt != $v:expr) => { $crate::watch!(@new $name Ne $v) };
+    (@new ($name:expr) $cmp:ident $v:expr) => {
+        $crate::watch::Watch::Cmp($crate::Name::new($name), $crate::watch::Cmp::$cmp, $v as f32)
+    };
+    ($($t:tt)+) => {{
+        const WATCH: $crate::watch::Watch = $crate::watch!(@or [] [] $($t)+);
+        WATCH
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    const CURRENT: SVName = SVName::new("current");
+    const DUTY: SVName = SVName::new("duty");
+    const FAULT: SVName = SVName::new("fault");
+
+    #[test]
+    fn macro_tree() {
+        assert_eq!(
+            crate::watch!(current > 10 && "duty" <= 0.5 || fault != 0),
+            Watch::Any(&[
+                Watch::All(&[
+                    Watch::Cmp(CURRENT, Cmp::Gt, 10.),
+                    Watch::Cmp(DUTY, Cmp::Le, 0.5)
+                ]),
+                Watch::All(&[Watch::Cmp(FAULT, Cmp::Ne, 0.)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn eval() {
+        const TRIP: Watch = crate::watch!(current > 10 && duty > 0.9 || !(fault == 0));
+        let mut map = SVMap::<3, 2>::new();
+        // Absent signal never matches, so negation of it does
+        assert!(TRIP.eval(&map));
+        map.set(&FAULT, 0).unwrap();
+        assert!(!TRIP.eval(&map));
+        map.set(&CURRENT, 12).unwrap();
+        map.set(&DUTY, 0.5).unwrap();
+        assert!(!TRIP.eval(&map));
+        map.set(&DUTY, 0.95).unwrap();
+        assert!(TRIP.eval(&map));
+        map.next(|_| {});
+        map.set(&CURRENT, 8).unwrap();
+        assert!(!TRIP.eval(&map));
+        map.set(&FAULT, 3).unwrap();
+        assert!(TRIP.eval(&map));
+    }
+}
@@ -0,0 +1,119 @@
+//! Derive macros for [svisual](https://crates.io/crates/svisual)
+//!
+//! Use them through the `derive` feature of `svisual`
+
+#![deny(missing_docs)]
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+/// Implements `svisual::SvSignals` for a struct with named fields.
+///
+/// Every field becomes one signal named after the field ident.
+/// Field types must implement `svisual::Value` and `Copy`.
+#[proc_macro_derive(SvSignals)]
+pub fn derive_sv_signals(input: TokenStream) -> TokenStream {
+    match parse_struct(input) {
+        Ok((ident, fields)) => expand(&ident, &fields),
+        Err(msg) => compile_error(msg),
+    }
+}
+
+fn expand(ident: &str, fields: &[String]) -> TokenStream {
+    let mut body = String::new();
+    for field in fields {
+        body.push_str(&format!(
+            "{{ const NAME: ::svisual::Name = ::svisual::Name::new({:?}); sv.set(&NAME, self.{})?; }}",
+            field.trim_start_matches("r#"),
+            field
+        ));
+    }
+    format!(
+        "impl ::svisual::SvSignals for {} {{
+            fn record<const N: usize, const P: usize>(
+                &self,
+                sv: &mut ::svisual::SVMap<N, P>,
+            ) -> ::core::result::Result<(), ::svisual::AddError> {{
+                {}
+                ::core::result::Result::Ok(())
+            }}
+        }}",
+        ident, body
+    )
+    .parse()
+    .unwrap()
+}
+
+fn compile_error(msg: &str) -> TokenStream {
+    format!("::core::compile_error!({:?});", msg)
+        .parse()
+        .unwrap()
+}
+
+/// Returns struct ident and names of its fields
+fn parse_struct(input: TokenStream) -> Result<(String, Vec<String>), &'static str> {
+    let mut tokens = input.into_iter().peekable();
+    // Skip attributes and visibility up to `struct` keyword
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(i)) if i.to_string() == "struct" => break,
+            Some(TokenTree::Ident(i)) if i.to_string() == "enum" || i.to_string() == "union" => {
+                return Err("SvSignals can only be derived for structs")
+            }
+            Some(_) => {}
+            None => return Err("expected struct"),
+        }
+    }
+    let ident = match tokens.next() {
+        Some(TokenTree::Ident(i)) => i.to_string(),
+        _ => return Err("expected struct name"),
+    };
+    let body = match tokens.next() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => g.stream(),
+        Some(TokenTree::Punct(p)) if p.as_char() == '<' => {
+            return Err("SvSignals does not support generic structs")
+        }
+        _ => return Err("SvSignals can only be derived for structs with named fields"),
+    };
+
+    let mut fields = Vec::new();
+    let mut tokens = body.into_iter().peekable();
+    while tokens.peek().is_some() {
+        // Skip field attributes and visibility
+        let name = loop {
+            match tokens.next() {
+                Some(TokenTree::Punct(p)) if p.as_char() == '#' => {
+                    tokens.next();
+                }
+                Some(TokenTree::Ident(i)) if i.to_string() == "pub" => {
+                    if let Some(TokenTree::Group(g)) = tokens.peek() {
+                        if g.delimiter() == Delimiter::Parenthesis {
+                            tokens.next();
+                        }
+                    }
+                }
+                Some(TokenTree::Ident(i)) => break i.to_string(),
+                _ => return Err("expected field name"),
+            }
+        };
+        match tokens.next() {
+            Some(TokenTree::Punct(p)) if p.as_char() == ':' => {}
+            _ => return Err("expected `:` after field name"),
+        }
+        // Skip field type up to the next top-level comma
+        let mut depth = 0usize;
+        for tt in tokens.by_ref() {
+            if let TokenTree::Punct(p) = &tt {
+                match p.as_char() {
+                    '<' => depth += 1,
+                    '>' => depth = depth.saturating_sub(1),
+                    ',' if depth == 0 => break,
+                    _ => {}
+                }
+            }
+        }
+        fields.push(name);
+    }
+    Ok((ident, fields))
+}